@@ -0,0 +1,90 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use web_crawler::crawler::{read_body, BufferPool};
+
+/// Many medium-sized pages, as in a typical crawl
+const PAGES: usize = 1000;
+const PAGE_SIZE: usize = 64 * 1024;
+const MAX_SIZE: usize = 10 * 1024 * 1024;
+
+/// System allocator that counts allocations, reallocations included
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[derive(Clone, Copy, Debug)]
+enum Mode {
+    /// No Content-Length, fresh buffer per page
+    Unpooled,
+    /// Fresh buffer per page, sized from Content-Length
+    Presized,
+    /// No Content-Length, pooled buffer shared with the body
+    Pooled,
+    /// Pooled buffer, sized from Content-Length
+    PooledPresized,
+}
+
+/// Read every page the way the fetcher would in `mode`, dropping each body
+/// before the next read as a worker does once the page is parsed
+fn read_pages(pages: &[Vec<u8>], mode: Mode, pool: &BufferPool) -> usize {
+    pages
+        .iter()
+        .map(|page| {
+            let (content_length, pool) = match mode {
+                Mode::Unpooled => (None, None),
+                Mode::Presized => (Some(page.len()), None),
+                Mode::Pooled => (None, Some(pool)),
+                Mode::PooledPresized => (Some(page.len()), Some(pool)),
+            };
+            read_body(&page[..], content_length, MAX_SIZE, pool).unwrap().0.len()
+        })
+        .sum()
+}
+
+fn bench_read_body(c: &mut Criterion) {
+    let pages: Vec<Vec<u8>> = (0..PAGES)
+        .map(|i| format!("<p>page {}</p>", i).repeat(PAGE_SIZE / 16).into_bytes())
+        .collect();
+    let modes = [Mode::Unpooled, Mode::Presized, Mode::Pooled, Mode::PooledPresized];
+
+    for mode in modes {
+        let pool = BufferPool::new(1);
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        read_pages(&pages, mode, &pool);
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        println!("read_body/{:?}: {} allocations for {} pages", mode, allocations, PAGES);
+    }
+
+    let mut group = c.benchmark_group("read_body");
+    group.throughput(Throughput::Bytes(pages.iter().map(Vec::len).sum::<usize>() as u64));
+    for mode in modes {
+        let pool = BufferPool::new(1);
+        group.bench_with_input(BenchmarkId::from_parameter(format!("{:?}", mode)), &mode, |b, &mode| {
+            b.iter(|| read_pages(&pages, mode, &pool))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_read_body);
+criterion_main!(benches);
@@ -0,0 +1,61 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use url::Url;
+use web_crawler::crawler::{Frontier, UrlFrontier};
+
+const URLS_PER_TASK: usize = 1_000;
+
+fn urls(task: usize) -> Vec<Url> {
+    (0..URLS_PER_TASK)
+        .map(|i| Url::parse(&format!("https://host{}.example.com/page/{}", task, i)).unwrap())
+        .collect()
+}
+
+/// Tasks concurrently add their URLs to a shared frontier, then drain it
+async fn add_and_pop(tasks: usize, fixtures: &[Vec<Url>]) {
+    let frontier = UrlFrontier::new(tasks * URLS_PER_TASK);
+
+    let mut handles = Vec::with_capacity(tasks);
+    for urls in fixtures.iter().take(tasks).cloned() {
+        let frontier = frontier.clone();
+        handles.push(tokio::spawn(async move {
+            for url in urls {
+                frontier.add(url, 1).await;
+            }
+        }));
+    }
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    let mut handles = Vec::with_capacity(tasks);
+    for _ in 0..tasks {
+        let frontier = frontier.clone();
+        handles.push(tokio::spawn(async move {
+            while frontier.pop().await.is_some() {}
+        }));
+    }
+    for handle in handles {
+        handle.await.unwrap();
+    }
+}
+
+fn bench_contention(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .enable_all()
+        .build()
+        .unwrap();
+    let fixtures: Vec<Vec<Url>> = (0..16).map(urls).collect();
+
+    let mut group = c.benchmark_group("frontier");
+    for tasks in [1, 4, 16] {
+        group.throughput(Throughput::Elements((tasks * URLS_PER_TASK) as u64));
+        group.bench_with_input(BenchmarkId::new("add_pop", tasks), &tasks, |b, &tasks| {
+            b.to_async(&runtime).iter(|| add_and_pop(tasks, &fixtures))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_contention);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use url::Url;
+use web_crawler::crawler::Parser;
+
+const ARTICLE: &str = include_str!("fixtures/article.html");
+
+/// Fixed mix of crawlable pages, assets and non-HTTP links
+fn link_list(count: usize) -> Vec<Url> {
+    let suffixes = ["", ".html", ".png", ".pdf", "/", ".js", "?page=2", ".JPG"];
+    (0..count)
+        .map(|i| {
+            let scheme = if i % 10 == 0 { "ftp" } else { "https" };
+            let suffix = suffixes[i % suffixes.len()];
+            Url::parse(&format!("{}://host{}.example.com/path/{}{}", scheme, i % 50, i, suffix)).unwrap()
+        })
+        .collect()
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let parser = Parser::new();
+    let base = Url::parse("https://example.com/articles/crawler-design").unwrap();
+
+    let mut group = c.benchmark_group("parser");
+    group.throughput(Throughput::Bytes(ARTICLE.len() as u64));
+    group.bench_function("parse_article", |b| {
+        b.iter(|| parser.parse(black_box(ARTICLE), &base).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_filter_links(c: &mut Criterion) {
+    let parser = Parser::new();
+    let links = link_list(10_000);
+
+    let mut group = c.benchmark_group("parser");
+    group.throughput(Throughput::Elements(links.len() as u64));
+    group.bench_function("filter_links_10k", |b| {
+        b.iter(|| parser.filter_links(black_box(links.clone())))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_filter_links);
+criterion_main!(benches);
@@ -1,7 +1,6 @@
 //! Basic example of using the web crawler
 
 use web_crawler::prelude::*;
-use web_crawler::crawler::CrawlerBuilder;
 use url::Url;
 
 #[tokio::main]
@@ -13,9 +12,9 @@ async fn main() -> Result<()> {
     let crawler = CrawlerBuilder::new()
         .max_depth(3)
         .max_pages(100)
-        .max_concurrent_requests(10)
-        .user_agent("MyBot/1.0")
-        .build()?;
+        .max_concurrent(10)
+        .user_agent("MyBot/1.0".to_string())
+        .build();
     
     // Add seed URLs
     let seed_urls = vec![
@@ -27,22 +26,16 @@ async fn main() -> Result<()> {
         crawler.add_seed(Url::parse(url)?).await?;
     }
     
-    // Set up progress callback
-    let progress_callback = |stats| {
-        println!("Progress: {} pages crawled, {} in queue", 
-                 stats.pages_crawled, stats.queue_size);
-    };
-    
     // Start crawling
     println!("Starting crawl...");
-    let results = crawler.crawl_with_callback(progress_callback).await?;
+    let stats = crawler.crawl().await?;
     
     // Print results
     println!("\nCrawl completed!");
-    println!("Total pages crawled: {}", results.total_pages);
-    println!("Successful: {}", results.successful);
-    println!("Failed: {}", results.failed);
-    println!("Total time: {:?}", results.duration);
+    println!("Total pages crawled: {}", stats.pages_crawled);
+    println!("Failed: {}", stats.pages_failed);
+    println!("Links found: {}", stats.total_links_found);
+    println!("Total time: {:?}", stats.duration());
     
     Ok(())
-}
\ No newline at end of file
+}
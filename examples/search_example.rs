@@ -0,0 +1,5 @@
+//! Search example (placeholder until the search engine lands)
+
+fn main() {
+    println!("Search example placeholder");
+}
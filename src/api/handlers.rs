@@ -0,0 +1,471 @@
+use crate::api::{AppState, JobReport};
+use crate::common::error::Error;
+use crate::crawler::{CrawlerBuilder, ReloadableConfig};
+use crate::search::{query::DEFAULT_LIMIT, CollapseBy, FacetField, SearchQuery, SearchResults, SortOrder};
+use axum::extract::{Path, Query, RawQuery, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use url::Url;
+use uuid::Uuid;
+
+/// Largest page of search results a client may request
+pub const MAX_SEARCH_LIMIT: usize = 100;
+
+/// Queries with fewer hits than this get "did you mean" suggestions
+pub const SUGGEST_BELOW_HITS: usize = 3;
+
+/// Error returned by API handlers as a JSON body
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message: message.into(),
+        }
+    }
+
+    fn unavailable(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            message: message.into(),
+        }
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: message.into(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = Json(serde_json::json!({ "error": self.message }));
+        (self.status, body).into_response()
+    }
+}
+
+/// Body of `POST /crawl`
+#[derive(Debug, Deserialize)]
+pub struct CrawlRequest {
+    pub seed_urls: Vec<String>,
+    pub max_pages: Option<usize>,
+    pub max_depth: Option<usize>,
+}
+
+/// Response of `POST /crawl`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrawlStarted {
+    pub id: Uuid,
+}
+
+/// `POST /crawl` - launch a crawl in the background
+pub async fn start_crawl(
+    State(state): State<AppState>,
+    Json(request): Json<CrawlRequest>,
+) -> Result<(StatusCode, Json<CrawlStarted>), ApiError> {
+    if request.seed_urls.is_empty() {
+        return Err(ApiError::bad_request("seed_urls must not be empty"));
+    }
+
+    let mut builder = CrawlerBuilder::new();
+    if let Some(max_pages) = request.max_pages {
+        builder = builder.max_pages(max_pages);
+    }
+    if let Some(max_depth) = request.max_depth {
+        builder = builder.max_depth(max_depth);
+    }
+    if let Some(storage) = &state.storage {
+        builder = builder.storage(storage.clone());
+    }
+    if let Some(indexer) = &state.indexer {
+        builder = builder.indexer(indexer.clone());
+    }
+    let crawler = builder.build();
+
+    for seed in &request.seed_urls {
+        let url = Url::parse(seed)
+            .map_err(|e| ApiError::bad_request(format!("Invalid seed URL {}: {}", seed, e)))?;
+        crawler
+            .add_seed(url)
+            .await
+            .map_err(|e| ApiError::bad_request(format!("Invalid seed URL {}: {}", seed, e)))?;
+    }
+
+    let id = state.jobs.launch(crawler).await;
+    Ok((StatusCode::ACCEPTED, Json(CrawlStarted { id })))
+}
+
+/// `GET /crawl/{id}` - live status and statistics of a crawl
+pub async fn get_crawl(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<JobReport>, ApiError> {
+    state
+        .jobs
+        .report(id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("No crawl job {}", id)))
+}
+
+/// `DELETE /crawl/{id}` - cancel a running crawl
+pub async fn cancel_crawl(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<JobReport>, ApiError> {
+    state
+        .jobs
+        .cancel(id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("No crawl job {}", id)))
+}
+
+/// `PUT /crawl/{id}/config` - change the politeness settings of a crawl
+pub async fn reload_crawl_config(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(changes): Json<ReloadableConfig>,
+) -> Result<Json<JobReport>, ApiError> {
+    state
+        .jobs
+        .reload(id, &changes)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("No crawl job {}", id)))
+}
+
+/// Query string of `GET /search`. Facets are given as repeated
+/// `facets[]` parameters and domain boosts as repeated `boost=host:factor`
+/// parameters, which are read from the raw query string.
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    pub q: String,
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub sort: SortOrder,
+    #[serde(default)]
+    pub highlight: bool,
+    #[serde(default)]
+    pub collapse_by: CollapseBy,
+}
+
+/// Response of `GET /search`
+#[derive(Debug, Serialize)]
+pub struct SearchResponse {
+    #[serde(flatten)]
+    pub results: SearchResults,
+    /// Corrected queries, offered when the query found few results
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub did_you_mean: Vec<String>,
+}
+
+/// `GET /search` - one page of results for a query
+pub async fn search(
+    State(state): State<AppState>,
+    Query(params): Query<SearchParams>,
+    RawQuery(raw): RawQuery,
+) -> Result<Json<SearchResponse>, ApiError> {
+    let searcher = state
+        .searcher
+        .as_ref()
+        .ok_or_else(|| ApiError::unavailable("Search index is not available"))?;
+
+    let mut query = SearchQuery::new(params.q.clone())
+        .offset(params.offset)
+        .limit(params.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_SEARCH_LIMIT))
+        .sort(params.sort)
+        .highlight(params.highlight)
+        .collapse_by(params.collapse_by);
+    let raw = raw.unwrap_or_default();
+    for (_, boost) in url::form_urlencoded::parse(raw.as_bytes()).filter(|(key, _)| key == "boost") {
+        let (domain, factor) = boost
+            .rsplit_once(':')
+            .and_then(|(domain, factor)| Some((domain, factor.parse::<f32>().ok()?)))
+            .filter(|(domain, factor)| !domain.is_empty() && factor.is_finite() && *factor >= 0.0)
+            .ok_or_else(|| ApiError::bad_request(format!("Invalid boost {:?}, expected domain:factor", boost)))?;
+        query = query.boost(domain, factor);
+    }
+    let facets = url::form_urlencoded::parse(raw.as_bytes())
+        .filter(|(key, _)| key == "facets[]" || key == "facets")
+        .map(|(_, value)| value.parse::<FacetField>())
+        .collect::<crate::common::error::Result<Vec<_>>>()
+        .map_err(|e| ApiError::bad_request(e.to_string()))?;
+
+    let to_api_error = |e: Error| match e {
+        Error::QueryError(_) => ApiError::bad_request(e.to_string()),
+        e => ApiError::internal(e.to_string()),
+    };
+    let results = searcher.search_with_facets(&query, &facets).map_err(to_api_error)?;
+    let did_you_mean = if results.total < SUGGEST_BELOW_HITS {
+        searcher.suggest(&params.q).map_err(to_api_error)?
+    } else {
+        Vec::new()
+    };
+    Ok(Json(SearchResponse {
+        results,
+        did_you_mean,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::create_router;
+    use crate::api::JobRegistry;
+    use crate::crawler::mock_server::MockServer;
+    use crate::indexer::{IndexDocument, Indexer};
+    use crate::search::Searcher;
+    use crate::storage::{InMemoryStore, Storage};
+    use axum::body::Body;
+    use std::sync::Arc;
+    use axum::http::Request;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    async fn send(app: &axum::Router, request: Request<Body>) -> (StatusCode, serde_json::Value) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn post_crawl(body: serde_json::Value) -> Request<Body> {
+        Request::post("/crawl")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_crawl_job_runs_to_completion() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/a">a</a>"#);
+        server.html("/a", "<title>A</title>");
+        let app = create_router(AppState::default());
+
+        let (status, body) = send(
+            &app,
+            post_crawl(serde_json::json!({
+                "seed_urls": [server.url("/").to_string()],
+                "max_pages": 10,
+                "max_depth": 2,
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let id = body["id"].as_str().unwrap().to_string();
+
+        let mut report = serde_json::Value::Null;
+        for _ in 0..100 {
+            let (status, body) = send(
+                &app,
+                Request::get(format!("/crawl/{}", id)).body(Body::empty()).unwrap(),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            report = body;
+            if report["status"] != "running" {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        assert_eq!(report["status"], "completed");
+        assert_eq!(report["stats"]["pages_crawled"], 2);
+    }
+
+    async fn wait_for_crawl(app: &axum::Router, id: &str) -> (StatusCode, serde_json::Value) {
+        for _ in 0..100 {
+            let (status, body) = send(app, Request::get(format!("/crawl/{}", id)).body(Body::empty()).unwrap()).await;
+            if body["status"] != "running" {
+                return (status, body);
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("crawl job {} never finished", id);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_crawl_job_uses_server_store_and_indexer() {
+        let server = MockServer::start();
+        server.html("/", "<title>Home</title><p>pangolin</p>");
+        let storage = Arc::new(InMemoryStore::new());
+        let indexer = Indexer::in_memory().unwrap();
+        let searcher = Searcher::for_indexer(&indexer).unwrap();
+        let app = create_router(AppState {
+            storage: Some(storage.clone()),
+            indexer: Some(indexer),
+            searcher: Some(searcher.clone()),
+            ..AppState::default()
+        });
+
+        let (_, body) = send(&app, post_crawl(serde_json::json!({ "seed_urls": [server.url("/").to_string()] }))).await;
+        let (_, report) = wait_for_crawl(&app, body["id"].as_str().unwrap()).await;
+        assert_eq!(report["status"], "completed");
+
+        assert!(storage.exists(server.url("/").as_str()).await.unwrap());
+        searcher.reload().unwrap();
+        let (status, body) = send(&app, Request::get("/search?q=pangolin").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_finished_jobs_are_evicted() {
+        let server = MockServer::start();
+        server.html("/", "<title>Home</title>");
+        let app = create_router(AppState {
+            jobs: JobRegistry::with_max_finished(1),
+            ..AppState::default()
+        });
+
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let (_, body) = send(&app, post_crawl(serde_json::json!({ "seed_urls": [server.url("/").to_string()] }))).await;
+            let id = body["id"].as_str().unwrap().to_string();
+            let (status, report) = wait_for_crawl(&app, &id).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(report["stats"]["pages_crawled"], 1);
+            ids.push(id);
+        }
+
+        let (status, _) = send(&app, Request::get(format!("/crawl/{}", ids[0])).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, report) = send(&app, Request::get(format!("/crawl/{}", ids[1])).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report["status"], "completed");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cancel_crawl_job() {
+        let server = MockServer::start();
+        server.html("/", "<title>Home</title>");
+        let app = create_router(AppState::default());
+
+        let (_, body) = send(
+            &app,
+            post_crawl(serde_json::json!({ "seed_urls": [server.url("/").to_string()] })),
+        )
+        .await;
+        let id = body["id"].as_str().unwrap().to_string();
+
+        let (status, body) = send(
+            &app,
+            Request::delete(format!("/crawl/{}", id)).body(Body::empty()).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "cancelled");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_job_and_bad_request() {
+        let app = create_router(AppState::default());
+
+        let (status, _) = send(
+            &app,
+            Request::get(format!("/crawl/{}", Uuid::new_v4())).body(Body::empty()).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, body) = send(&app, post_crawl(serde_json::json!({ "seed_urls": ["not a url"] }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("not a url"));
+    }
+
+    #[tokio::test]
+    async fn test_search_pagination_and_sort() {
+        let indexer = Indexer::in_memory().unwrap();
+        for (i, path) in ["c", "a", "b"].iter().enumerate() {
+            indexer
+                .add_document(&IndexDocument {
+                    url: format!("https://example.com/{}", path),
+                    title: None,
+                    body: "rust search".to_string(),
+                    content_type: None,
+                    language: None,
+                    description: None,
+                    image: None,
+                    crawled_at: chrono::Utc::now() + chrono::Duration::seconds(i as i64),
+                    published_at: None,
+                    tags: Default::default(),
+                })
+                .unwrap();
+        }
+        indexer.commit().unwrap();
+        let searcher = Searcher::for_indexer(&indexer).unwrap();
+        searcher.reload().unwrap();
+        let app = create_router(AppState {
+            searcher: Some(searcher),
+            ..AppState::default()
+        });
+
+        let (status, body) = send(
+            &app,
+            Request::get("/search?q=rust&sort=url&offset=1&limit=1").body(Body::empty()).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 3);
+        assert_eq!(body["hits"].as_array().unwrap().len(), 1);
+        assert_eq!(body["hits"][0]["url"], "https://example.com/b");
+
+        let (status, body) = send(
+            &app,
+            Request::get("/search?q=rust&facets[]=domain&facets%5B%5D=content_type")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["facets"]["domain"]["example.com"], 3);
+        assert!(body["facets"]["content_type"].as_object().unwrap().is_empty());
+        assert!(body.get("did_you_mean").is_none());
+
+        let (status, body) = send(&app, Request::get("/search?q=serch").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 0);
+        assert_eq!(body["did_you_mean"][0], "search");
+
+        let (status, body) = send(&app, Request::get("/search?q=nofield:x").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().starts_with("Invalid query"));
+
+        let (status, body) = send(
+            &app,
+            Request::get("/search?q=rust&boost=example.com:2.0").body(Body::empty()).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 3);
+        let (status, body) = send(&app, Request::get("/search?q=rust&boost=example.com").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("domain:factor"));
+
+        let response = app
+            .oneshot(Request::get("/search?q=rust&sort=bogus").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::crawler::{CrawlStats, Crawler, ReloadableConfig};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use tracing::info;
+use uuid::Uuid;
+
+/// Lifecycle state of a crawl job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Snapshot of a crawl job returned by the API
+#[derive(Debug, Clone, Serialize)]
+pub struct JobReport {
+    pub id: Uuid,
+    pub status: JobStatus,
+    pub stats: CrawlStats,
+    pub error: Option<String>,
+}
+
+/// Finished jobs kept for reporting by default; older ones are forgotten
+pub const DEFAULT_MAX_FINISHED_JOBS: usize = 100;
+
+struct JobState {
+    status: JobStatus,
+    error: Option<String>,
+    /// Dropped once the crawl finishes, leaving only its final stats
+    crawler: Option<Arc<Crawler>>,
+    stats: CrawlStats,
+}
+
+/// A crawl running in a background task
+type JobHandle = Arc<Mutex<JobState>>;
+
+/// Registry of crawl jobs launched through the API
+#[derive(Clone)]
+pub struct JobRegistry {
+    jobs: Arc<RwLock<HashMap<Uuid, JobHandle>>>,
+    /// Finished jobs, oldest first
+    finished: Arc<Mutex<VecDeque<Uuid>>>,
+    max_finished: usize,
+}
+
+impl Default for JobRegistry {
+    fn default() -> Self {
+        Self::with_max_finished(DEFAULT_MAX_FINISHED_JOBS)
+    }
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Registry that forgets the oldest finished job once more than
+    /// `max_finished` have finished
+    pub fn with_max_finished(max_finished: usize) -> Self {
+        Self {
+            jobs: Arc::default(),
+            finished: Arc::default(),
+            max_finished,
+        }
+    }
+    
+    /// Start crawling in a background task and return the job id
+    pub async fn launch(&self, crawler: Crawler) -> Uuid {
+        let id = Uuid::new_v4();
+        let crawler = Arc::new(crawler);
+        let handle = Arc::new(Mutex::new(JobState {
+            status: JobStatus::Running,
+            error: None,
+            crawler: Some(crawler.clone()),
+            stats: CrawlStats::default(),
+        }));
+        self.jobs.write().await.insert(id, handle.clone());
+        
+        let registry = self.clone();
+        tokio::spawn(async move {
+            let result = crawler.crawl().await;
+            let stats = crawler.get_stats().await;
+            drop(crawler);
+            let mut state = handle.lock().await;
+            if state.status == JobStatus::Running {
+                match result {
+                    Ok(_) => state.status = JobStatus::Completed,
+                    Err(e) => {
+                        state.status = JobStatus::Failed;
+                        state.error = Some(e.to_string());
+                    }
+                }
+            }
+            state.crawler = None;
+            state.stats = stats;
+            // Evict while the state is locked, so older jobs are gone by the
+            // time this one reports as finished
+            registry.finish(id).await;
+            info!("Crawl job {} finished: {:?}", id, state.status);
+        });
+        
+        id
+    }
+    
+    /// Record that a job finished, forgetting the oldest finished jobs past
+    /// the limit
+    async fn finish(&self, id: Uuid) {
+        let mut finished = self.finished.lock().await;
+        finished.push_back(id);
+        while finished.len() > self.max_finished {
+            if let Some(oldest) = finished.pop_front() {
+                self.jobs.write().await.remove(&oldest);
+            }
+        }
+    }
+    
+    /// Current status and statistics of a job
+    pub async fn report(&self, id: Uuid) -> Option<JobReport> {
+        let handle = self.jobs.read().await.get(&id).cloned()?;
+        let crawler = handle.lock().await.crawler.clone();
+        let live_stats = match crawler {
+            Some(crawler) => Some(crawler.get_stats().await),
+            None => None,
+        };
+        let state = handle.lock().await;
+        Some(JobReport {
+            id,
+            status: state.status,
+            stats: live_stats.unwrap_or_else(|| state.stats.clone()),
+            error: state.error.clone(),
+        })
+    }
+    
+    /// Change the hot-reloadable settings of a job. Returns `None` if the
+    /// job doesn't exist.
+    pub async fn reload(&self, id: Uuid, changes: &ReloadableConfig) -> Option<JobReport> {
+        let handle = self.jobs.read().await.get(&id).cloned()?;
+        let crawler = handle.lock().await.crawler.clone();
+        if let Some(crawler) = crawler {
+            crawler.reload(changes).await;
+        }
+        self.report(id).await
+    }
+    
+    /// Cancel a running job. Returns `None` if the job doesn't exist.
+    pub async fn cancel(&self, id: Uuid) -> Option<JobReport> {
+        let handle = self.jobs.read().await.get(&id).cloned()?;
+        {
+            let mut state = handle.lock().await;
+            if state.status == JobStatus::Running {
+                if let Some(crawler) = &state.crawler {
+                    crawler.cancel();
+                }
+                state.status = JobStatus::Cancelled;
+            }
+        }
+        self.report(id).await
+    }
+}
@@ -0,0 +1,23 @@
+pub mod handlers;
+pub mod jobs;
+pub mod routes;
+
+pub use jobs::{JobRegistry, JobReport, JobStatus};
+pub use routes::create_router;
+
+use crate::indexer::Indexer;
+use crate::search::Searcher;
+use crate::storage::Storage;
+use std::sync::Arc;
+
+/// Shared state for the API handlers
+#[derive(Clone, Default)]
+pub struct AppState {
+    pub jobs: JobRegistry,
+    /// Searcher backing `GET /search`; search is unavailable without one
+    pub searcher: Option<Searcher>,
+    /// Store that crawl jobs save their pages to
+    pub storage: Option<Arc<dyn Storage>>,
+    /// Index that crawl jobs add their pages to
+    pub indexer: Option<Indexer>,
+}
@@ -0,0 +1,17 @@
+use crate::api::handlers;
+use crate::api::AppState;
+use axum::routing::{get, post, put};
+use axum::Router;
+
+/// Build the API router
+pub fn create_router(state: AppState) -> Router {
+    Router::new()
+        .route("/crawl", post(handlers::start_crawl))
+        .route(
+            "/crawl/:id",
+            get(handlers::get_crawl).delete(handlers::cancel_crawl),
+        )
+        .route("/crawl/:id/config", put(handlers::reload_crawl_config))
+        .route("/search", get(handlers::search))
+        .with_state(state)
+}
@@ -1,14 +1,25 @@
 use clap::Parser as ClapParser;
 use web_crawler::prelude::*;
+use web_crawler::crawler::{OutputFormat, ReloadableConfig};
+use std::path::PathBuf;
+use std::sync::Arc;
 use url::Url;
 use tracing::Level;
 
 #[derive(ClapParser, Debug)]
 #[clap(author, version, about = "High-performance web crawler")]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
-    /// Starting URL to crawl
-    #[clap(value_parser)]
-    url: String,
+    #[clap(subcommand)]
+    command: Option<Command>,
+    
+    /// Starting URL to crawl; `-` reads seed URLs from standard input
+    #[clap(value_parser, required_unless_present = "stdin")]
+    url: Option<String>,
+    
+    /// Read newline-separated seed URLs from standard input
+    #[clap(long)]
+    stdin: bool,
     
     /// Maximum number of pages to crawl
     #[clap(short, long, default_value = "100")]
@@ -26,48 +37,108 @@ struct Args {
     #[clap(long, default_value = "1000")]
     delay: u64,
     
+    /// JSON file of hot-reloadable settings (delay_ms, delay_jitter_percent,
+    /// global_rate_limit, allowed_domains), applied at start and re-read on SIGHUP
+    #[clap(long)]
+    reload_config: Option<PathBuf>,
+    
+    /// Format of the final report: text, json or csv
+    #[clap(long, default_value = "text")]
+    format: OutputFormat,
+    
     /// Enable debug logging
     #[clap(short = 'v', long)]
     verbose: bool,
 }
 
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Self-test fetching, parsing, crawling, indexing and search against
+    /// a built-in local site; exits non-zero if any stage fails
+    Doctor,
+}
+
+/// Print progress to stdout in text mode and to stderr otherwise, so
+/// machine-readable reports can be piped
+macro_rules! progress {
+    ($text:expr, $($arg:tt)*) => {
+        if $text {
+            println!($($arg)*);
+        } else {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
     
+    if let Some(Command::Doctor) = args.command {
+        let report = web_crawler::doctor::run().await;
+        print!("{}", report);
+        if !report.is_healthy() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    
     // Initialize tracing
     let level = if args.verbose { Level::DEBUG } else { Level::INFO };
-    tracing_subscriber::fmt()
-        .with_max_level(level)
-        .init();
+    let subscriber = tracing_subscriber::fmt().with_max_level(level);
+    let text = args.format == OutputFormat::Text;
+    if text {
+        subscriber.init();
+    } else {
+        // Keep stdout for the machine-readable report
+        subscriber.with_writer(std::io::stderr).init();
+    }
     
-    println!("🕷️  Web Crawler v0.1.0");
-    println!("====================");
+    progress!(text, "🕷️  Web Crawler v0.1.0");
+    progress!(text, "====================");
     
-    // Parse the starting URL
-    let start_url = Url::parse(&args.url)
-        .map_err(|e| Error::UrlParseError(e))?;
+    // Parse the starting URL, unless seeds come from stdin
+    let read_stdin = args.stdin || args.url.as_deref() == Some("-");
+    let start_url = match (&args.url, read_stdin) {
+        (Some(url), false) => Some(Url::parse(url).map_err(Error::UrlParseError)?),
+        _ => None,
+    };
     
-    println!("\n📋 Configuration:");
-    println!("  Starting URL: {}", start_url);
-    println!("  Max pages: {}", args.max_pages);
-    println!("  Max depth: {}", args.max_depth);
-    println!("  Concurrent workers: {}", args.concurrent);
-    println!("  Delay: {}ms", args.delay);
+    progress!(text, "\n📋 Configuration:");
+    match &start_url {
+        Some(url) => progress!(text, "  Starting URL: {}", url),
+        None => progress!(text, "  Starting URLs: read from stdin"),
+    }
+    progress!(text, "  Max pages: {}", args.max_pages);
+    progress!(text, "  Max depth: {}", args.max_depth);
+    progress!(text, "  Concurrent workers: {}", args.concurrent);
+    progress!(text, "  Delay: {}ms", args.delay);
     
     // Create crawler
-    let crawler = CrawlerBuilder::new()
+    let crawler = Arc::new(CrawlerBuilder::new()
         .max_pages(args.max_pages)
         .max_depth(args.max_depth)
         .max_concurrent(args.concurrent)
         .delay_ms(args.delay)
         .user_agent("RustCrawler/0.1.0 (https://github.com/yourusername/crawler)".to_string())
-        .build();
+        .build());
+    
+    if let Some(path) = &args.reload_config {
+        crawler.reload(&ReloadableConfig::from_json_file(path)?).await;
+        #[cfg(unix)]
+        reload_on_sighup(crawler.clone(), path.clone())?;
+    }
     
-    // Add seed URL
-    crawler.add_seed(start_url).await?;
+    // Add seed URLs
+    match start_url {
+        Some(url) => crawler.add_seed(url).await?,
+        None => {
+            let added = crawler.add_seeds_from_reader(std::io::stdin().lock()).await?;
+            progress!(text, "  Seeds read: {}", added);
+        }
+    }
     
-    println!("\n🚀 Starting crawl...\n");
+    progress!(text, "\n🚀 Starting crawl...\n");
     
     // Start crawling
     let start_time = std::time::Instant::now();
@@ -76,6 +147,9 @@ async fn main() -> Result<()> {
     let result = crawler.crawl().await;
     
     match result {
+        Ok(_) if !text => {
+            println!("{}", crawler.report().await.render(args.format)?);
+        }
         Ok(stats) => {
             let duration = start_time.elapsed();
             
@@ -97,4 +171,21 @@ async fn main() -> Result<()> {
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Re-read the settings file into the crawler whenever the process gets SIGHUP
+#[cfg(unix)]
+fn reload_on_sighup(crawler: Arc<Crawler>, path: PathBuf) -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+    
+    let mut hangups = signal(SignalKind::hangup())?;
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            match ReloadableConfig::from_json_file(&path) {
+                Ok(changes) => crawler.reload(&changes).await,
+                Err(e) => eprintln!("⚠️  Not reloading {}: {}", path.display(), e),
+            }
+        }
+    });
+    Ok(())
+}
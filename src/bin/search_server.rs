@@ -1,3 +1,53 @@
-fn main() {
-    println!("Search server placeholder");
-}
\ No newline at end of file
+use web_crawler::api::{create_router, AppState};
+use web_crawler::prelude::*;
+use web_crawler::indexer::Indexer;
+use web_crawler::search::Searcher;
+use web_crawler::storage::{PageStore, Storage};
+use std::sync::Arc;
+
+#[tokio::main]
+async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt::init();
+    
+    let config = Config::load()?;
+    let addr = format!("{}:{}", config.api.host, config.api.port);
+    
+    // Crawls started through the API write to the index being searched
+    let indexer = match Indexer::open(&config.storage.index_path) {
+        Ok(indexer) => Some(indexer),
+        Err(e) => {
+            tracing::warn!("Crawl jobs won't be indexed, could not open index {}: {}", config.storage.index_path, e);
+            None
+        }
+    };
+    let searcher = match &indexer {
+        Some(indexer) => Searcher::for_indexer(indexer),
+        None => Searcher::open(&config.storage.index_path),
+    };
+    let searcher = match searcher {
+        Ok(searcher) => Some(searcher),
+        Err(e) => {
+            tracing::warn!("Search disabled, could not open index {}: {}", config.storage.index_path, e);
+            None
+        }
+    };
+    let storage = match PageStore::open(&config.storage.storage_path, config.storage.store_headers) {
+        Ok(store) => Some(Arc::new(store) as Arc<dyn Storage>),
+        Err(e) => {
+            tracing::warn!("Crawl jobs won't be stored, could not open {}: {}", config.storage.storage_path, e);
+            None
+        }
+    };
+    let app = create_router(AppState {
+        searcher,
+        storage,
+        indexer,
+        ..AppState::default()
+    });
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    
+    println!("🔎 Search server listening on http://{}", addr);
+    axum::serve(listener, app).await?;
+    
+    Ok(())
+}
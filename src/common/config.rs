@@ -33,6 +33,29 @@ pub struct CrawlerConfig {
     
     /// Number of worker threads
     pub num_workers: usize,
+    
+    /// Seed URLs, each with optional limits of its own
+    #[serde(default)]
+    pub seeds: Vec<SeedConfig>,
+}
+
+/// A seed URL with per-target overrides, which apply to every page on the
+/// seed's host and must stay within the crawl-wide limits
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SeedConfig {
+    pub url: String,
+    
+    /// Maximum crawl depth on this host, at most the crawl's `max_depth`
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+    
+    /// Maximum pages crawled on this host, at most the crawl's `max_pages`
+    #[serde(default)]
+    pub max_pages: Option<usize>,
+    
+    /// Delay between requests to this host, at least the crawl's delay
+    #[serde(default)]
+    pub delay_ms: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -42,6 +65,10 @@ pub struct StorageConfig {
     
     /// Index directory path
     pub index_path: String,
+    
+    /// Persist response headers alongside each stored page
+    #[serde(default)]
+    pub store_headers: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -86,10 +113,12 @@ impl Default for Config {
                 max_retries: 3,
                 max_page_size: 10 * 1024 * 1024, // 10MB
                 num_workers: 8,
+                seeds: Vec::new(),
             },
             storage: StorageConfig {
                 storage_path: "./data/storage".to_string(),
                 index_path: "./data/index".to_string(),
+                store_headers: false,
             },
             search: SearchConfig {
                 max_results: 1000,
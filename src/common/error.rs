@@ -5,6 +5,9 @@ pub enum Error {
     #[error("HTTP request failed: {0}")]
     HttpError(String),
     
+    #[error("HTTP {0} for {1}")]
+    HttpStatus(u16, String),
+    
     #[error("URL parse error: {0}")]
     UrlParseError(#[from] url::ParseError),
     
@@ -14,6 +17,16 @@ pub enum Error {
     #[error("Storage error: {0}")]
     StorageError(#[from] sled::Error),
     
+    #[error("Index error: {0}")]
+    IndexError(#[from] tantivy::TantivyError),
+    
+    #[cfg(feature = "redis")]
+    #[error("Redis error: {0}")]
+    RedisError(#[from] redis::RedisError),
+    
+    #[error("Invalid query: {0}")]
+    QueryError(String),
+    
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
     
@@ -29,6 +42,21 @@ pub enum Error {
     #[error("Robots.txt forbids crawling: {0}")]
     RobotsForbidden(String),
     
+    #[error("Redirect leaves the crawl scope: {0}")]
+    RedirectOutOfScope(String),
+    
+    #[error("Response is a download attachment: {0}")]
+    Attachment(String),
+    
+    #[error("Response body looks binary: {0}")]
+    BinaryContent(String),
+    
+    #[error("Response body of {0} sniffs as {1}, not its declared content type")]
+    MimeMismatch(String, String),
+    
+    #[error("Parsing took too long: {0}")]
+    ParseTimeout(String),
+    
     #[error("Invalid response: {0}")]
     InvalidResponse(String),
     
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Parameters of the per-domain adaptive delay
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdaptiveDelayConfig {
+    /// Delay added to a domain on each 429 or 503 response
+    pub increase: Duration,
+    /// Most delay a domain can build up, on top of the crawl's delay
+    pub max_delay: Duration,
+    /// Successful responses in a row needed to shrink the delay
+    pub successes_per_decrease: usize,
+    /// Factor the delay is multiplied by after a run of successes (0.0 - 1.0)
+    pub decrease_factor: f64,
+}
+
+impl Default for AdaptiveDelayConfig {
+    fn default() -> Self {
+        Self {
+            increase: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            successes_per_decrease: 5,
+            decrease_factor: 0.5,
+        }
+    }
+}
+
+/// Extra per-domain delay driven by how often a domain says it is
+/// overloaded: each 429 or 503 adds `increase`, up to `max_delay`, and every
+/// `successes_per_decrease` successful responses in a row multiply it by
+/// `decrease_factor`. Other statuses leave it alone.
+#[derive(Debug)]
+pub struct AdaptiveDelay {
+    config: AdaptiveDelayConfig,
+    domains: HashMap<String, DomainDelay>,
+}
+
+#[derive(Debug, Default)]
+struct DomainDelay {
+    delay: Duration,
+    /// Successes since the delay last changed
+    successes: usize,
+}
+
+impl AdaptiveDelay {
+    pub fn new(config: AdaptiveDelayConfig) -> Self {
+        Self {
+            config,
+            domains: HashMap::new(),
+        }
+    }
+
+    /// Extra delay currently applied between requests to a domain
+    pub fn delay(&self, domain: &str) -> Duration {
+        self.domains.get(domain).map_or(Duration::ZERO, |domain| domain.delay)
+    }
+
+    /// Adjust a domain's delay by the status of a response from it
+    pub fn record(&mut self, domain: &str, status: u16) {
+        match status {
+            429 | 503 => {
+                let entry = self.domains.entry(domain.to_string()).or_default();
+                entry.delay = (entry.delay + self.config.increase).min(self.config.max_delay);
+                entry.successes = 0;
+            }
+            200..=299 => {
+                // Domains that were never throttled have nothing to recover
+                let Some(entry) = self.domains.get_mut(domain) else {
+                    return;
+                };
+                entry.successes += 1;
+                if entry.successes >= self.config.successes_per_decrease.max(1) {
+                    entry.delay = entry.delay.mul_f64(self.config.decrease_factor.clamp(0.0, 1.0));
+                    entry.successes = 0;
+                    if entry.delay < Duration::from_millis(1) {
+                        self.domains.remove(domain);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttling_burst_then_recovery() {
+        let mut adaptive = AdaptiveDelay::new(AdaptiveDelayConfig {
+            increase: Duration::from_millis(500),
+            max_delay: Duration::from_secs(2),
+            successes_per_decrease: 2,
+            decrease_factor: 0.5,
+        });
+
+        // Each 429 or 503 adds to the delay, up to the cap
+        let burst: Vec<u128> = [429, 429, 503, 429, 429]
+            .into_iter()
+            .map(|status| {
+                adaptive.record("example.com", status);
+                adaptive.delay("example.com").as_millis()
+            })
+            .collect();
+        assert_eq!(burst, vec![500, 1000, 1500, 2000, 2000]);
+        assert_eq!(adaptive.delay("other.com"), Duration::ZERO);
+
+        // Errors that aren't throttling don't count either way
+        adaptive.record("example.com", 404);
+        assert_eq!(adaptive.delay("example.com").as_millis(), 2000);
+
+        // Every second success in a row halves it
+        let recovery: Vec<u128> = (0..6)
+            .map(|_| {
+                adaptive.record("example.com", 200);
+                adaptive.delay("example.com").as_millis()
+            })
+            .collect();
+        assert_eq!(recovery, vec![2000, 1000, 1000, 500, 500, 250]);
+
+        // A throttled response restarts the streak
+        adaptive.record("example.com", 200);
+        adaptive.record("example.com", 503);
+        adaptive.record("example.com", 200);
+        assert_eq!(adaptive.delay("example.com").as_millis(), 750);
+    }
+}
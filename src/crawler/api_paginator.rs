@@ -0,0 +1,157 @@
+use crate::common::error::{Error, Result};
+use serde_json::Value;
+use url::Url;
+
+/// Placeholder for the page number in an [`ApiPaginator`] template
+pub const PAGE_PLACEHOLDER: &str = "{n}";
+
+/// Placeholder for the cursor in an [`ApiPaginator`] template
+pub const CURSOR_PLACEHOLDER: &str = "{cursor}";
+
+/// How to walk a JSON API that pages through a list of items.
+///
+/// Page URLs come from a template such as
+/// `https://api.example.com/items?page={n}`, with `{n}` counting up from
+/// `start`. Templates with `{cursor}` instead take the cursor from each
+/// response (at `cursor_at`) to build the next URL; the first page gets an
+/// empty cursor. Paging stops at the first page without items, when a
+/// cursor template gets no cursor back, or after `max_pages` pages.
+#[derive(Debug, Clone)]
+pub struct ApiPaginator {
+    pub template: String,
+    /// First page number
+    pub start: u64,
+    /// JSON pointer to the array of items in a page
+    pub items_at: String,
+    /// JSON pointer to the link inside an item. Items that are strings are
+    /// links themselves.
+    pub link_at: String,
+    /// JSON pointer to the next page's cursor in a page
+    pub cursor_at: Option<String>,
+    /// Most pages fetched, in case the API never returns an empty page
+    pub max_pages: usize,
+}
+
+/// Links and cursor read from one API page
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ApiPage {
+    pub links: Vec<Url>,
+    pub next_cursor: Option<String>,
+}
+
+impl ApiPaginator {
+    /// Walk the pages of `template`, reading `/items/*/url` links
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+            start: 1,
+            items_at: "/items".to_string(),
+            link_at: "/url".to_string(),
+            cursor_at: None,
+            max_pages: 1000,
+        }
+    }
+
+    pub fn start(mut self, start: u64) -> Self {
+        self.start = start;
+        self
+    }
+
+    pub fn items_at(mut self, pointer: impl Into<String>) -> Self {
+        self.items_at = pointer.into();
+        self
+    }
+
+    pub fn link_at(mut self, pointer: impl Into<String>) -> Self {
+        self.link_at = pointer.into();
+        self
+    }
+
+    pub fn cursor_at(mut self, pointer: impl Into<String>) -> Self {
+        self.cursor_at = Some(pointer.into());
+        self
+    }
+
+    pub fn max_pages(mut self, max: usize) -> Self {
+        self.max_pages = max;
+        self
+    }
+
+    /// Whether page URLs are built from the previous page's cursor
+    pub fn uses_cursor(&self) -> bool {
+        self.template.contains(CURSOR_PLACEHOLDER)
+    }
+
+    /// URL of page `n`, with `cursor` (percent-encoded) for cursor templates
+    pub fn page_url(&self, n: u64, cursor: Option<&str>) -> Result<Url> {
+        let cursor: String = url::form_urlencoded::byte_serialize(cursor.unwrap_or_default().as_bytes()).collect();
+        let url = self
+            .template
+            .replace(PAGE_PLACEHOLDER, &n.to_string())
+            .replace(CURSOR_PLACEHOLDER, &cursor);
+        Ok(Url::parse(&url)?)
+    }
+
+    /// Read the item links of a page, resolved against its URL, and the
+    /// cursor to the next one. A page without the items array has no links.
+    pub fn parse_page(&self, body: &str, page_url: &Url) -> Result<ApiPage> {
+        let json: Value = serde_json::from_str(body)?;
+        let links = match json.pointer(&self.items_at) {
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(|item| match item {
+                    Value::String(link) => Some(link.as_str()),
+                    item => item.pointer(&self.link_at)?.as_str(),
+                })
+                .filter_map(|link| page_url.join(link.trim()).ok())
+                .collect(),
+            Some(other) => {
+                return Err(Error::InvalidResponse(format!(
+                    "{} of {} is not an array: {}",
+                    self.items_at, page_url, other
+                )))
+            }
+            None => Vec::new(),
+        };
+        let next_cursor = self
+            .cursor_at
+            .as_ref()
+            .and_then(|pointer| match json.pointer(pointer)? {
+                Value::String(cursor) => Some(cursor.clone()),
+                Value::Number(cursor) => Some(cursor.to_string()),
+                _ => None,
+            })
+            .filter(|cursor| !cursor.is_empty());
+        Ok(ApiPage { links, next_cursor })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cursor_page() {
+        let paginator = ApiPaginator::new("https://api.example.com/v1/posts?after={cursor}")
+            .items_at("/data/children")
+            .link_at("/data/permalink")
+            .cursor_at("/data/after");
+        let body = r#"{"data": {"after": "t3 abc", "children": [
+            {"data": {"permalink": "/r/rust/1"}},
+            {"data": {"title": "no link"}},
+            {"data": {"permalink": "https://example.com/2"}}
+        ]}}"#;
+
+        let url = paginator.page_url(1, None).unwrap();
+        assert_eq!(url.as_str(), "https://api.example.com/v1/posts?after=");
+        let page = paginator.parse_page(body, &url).unwrap();
+
+        let links: Vec<&str> = page.links.iter().map(Url::as_str).collect();
+        assert_eq!(links, ["https://api.example.com/r/rust/1", "https://example.com/2"]);
+        assert_eq!(page.next_cursor.as_deref(), Some("t3 abc"));
+        assert_eq!(
+            paginator.page_url(2, page.next_cursor.as_deref()).unwrap().as_str(),
+            "https://api.example.com/v1/posts?after=t3+abc"
+        );
+    }
+}
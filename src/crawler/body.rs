@@ -0,0 +1,121 @@
+use bytes::Bytes;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::ops::Deref;
+
+/// Text of a response body, held in the buffer it was read into.
+///
+/// Reads from a [`BufferPool`](crate::crawler::BufferPool) hand their buffer
+/// over without copying it, and clones share it, so a body is copied neither
+/// when it's read nor when it's passed to the parser. The pool gets the
+/// buffer back once every clone is dropped.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Body(Bytes);
+
+impl Body {
+    /// Body of `bytes`, if they are valid UTF-8
+    pub fn from_utf8(bytes: Bytes) -> Result<Self, std::str::Utf8Error> {
+        std::str::from_utf8(&bytes)?;
+        Ok(Self(bytes))
+    }
+
+    pub fn as_str(&self) -> &str {
+        // SAFETY: every constructor checks the bytes are UTF-8
+        unsafe { std::str::from_utf8_unchecked(&self.0) }
+    }
+
+    /// The first `len` bytes, sharing the buffer. Panics unless `len` falls
+    /// on a character boundary.
+    pub fn prefix(&self, len: usize) -> Body {
+        assert!(self.as_str().is_char_boundary(len), "{} is not a character boundary", len);
+        Self(self.0.slice(..len))
+    }
+
+    /// The raw bytes, sharing the buffer
+    pub fn bytes(&self) -> Bytes {
+        self.0.clone()
+    }
+}
+
+impl Deref for Body {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Body {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<String> for Body {
+    fn from(text: String) -> Self {
+        Self(Bytes::from(text))
+    }
+}
+
+impl From<&str> for Body {
+    fn from(text: &str) -> Self {
+        Self(Bytes::copy_from_slice(text.as_bytes()))
+    }
+}
+
+impl PartialEq<str> for Body {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Body {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for Body {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Body {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for Body {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Body {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Body::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_the_buffer() {
+        let bytes = Bytes::from(b"<p>caf\xc3\xa9</p>".to_vec());
+        let body = Body::from_utf8(bytes.clone()).unwrap();
+        assert_eq!(body, "<p>café</p>");
+        assert_eq!(body.clone().as_ptr(), bytes.as_ptr());
+        assert!(Body::from_utf8(Bytes::from_static(b"\xff")).is_err());
+        assert_eq!(serde_json::to_string(&body).unwrap(), r#""<p>café</p>""#);
+    }
+}
@@ -0,0 +1,88 @@
+use bytes::{Bytes, BytesMut};
+use std::sync::{Arc, Mutex};
+
+/// Largest buffer kept for reuse; bigger ones are freed after use so one
+/// huge page doesn't pin its memory for the rest of the crawl
+pub const MAX_POOLED_CAPACITY: usize = 4 * 1024 * 1024;
+
+/// Body read buffers shared by the workers of a crawl, so reading a page
+/// reuses memory already grown by earlier pages instead of reallocating.
+///
+/// A body read into a pooled buffer is handed out as [`Bytes`] sharing the
+/// buffer's memory, without a copy. The pool keeps the buffer, which takes
+/// its memory back on the next [`take`](Self::take) once that body is dropped.
+#[derive(Debug, Clone)]
+pub struct BufferPool {
+    buffers: Arc<Mutex<Vec<BytesMut>>>,
+    max_buffers: usize,
+}
+
+impl BufferPool {
+    /// Pool keeping up to `max_buffers` idle buffers, typically one per worker
+    pub fn new(max_buffers: usize) -> Self {
+        Self {
+            buffers: Arc::new(Mutex::new(Vec::with_capacity(max_buffers))),
+            max_buffers,
+        }
+    }
+
+    /// An empty buffer with room for at least `capacity` bytes
+    pub fn take(&self, capacity: usize) -> BytesMut {
+        let mut buffer = self.buffers.lock().unwrap().pop().unwrap_or_default();
+        buffer.reserve(capacity);
+        buffer
+    }
+
+    /// Hand out the bytes read into `buffer` without copying them, and keep
+    /// the buffer for reuse
+    pub fn finish(&self, mut buffer: BytesMut) -> Bytes {
+        let oversized = buffer.capacity() > MAX_POOLED_CAPACITY;
+        let bytes = buffer.split().freeze();
+        if !oversized {
+            self.give(buffer);
+        }
+        bytes
+    }
+
+    /// Hand a buffer back for reuse
+    pub fn give(&self, mut buffer: BytesMut) {
+        if buffer.capacity() > MAX_POOLED_CAPACITY {
+            return;
+        }
+        buffer.clear();
+        let mut buffers = self.buffers.lock().unwrap();
+        if buffers.len() < self.max_buffers {
+            buffers.push(buffer);
+        }
+    }
+
+    /// Number of idle buffers in the pool
+    pub fn idle(&self) -> usize {
+        self.buffers.lock().unwrap().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffers_are_reused() {
+        let pool = BufferPool::new(1);
+        let mut buffer = pool.take(1024);
+        buffer.extend_from_slice(b"page");
+        let ptr = buffer.as_ptr();
+        let body = pool.finish(buffer);
+        assert_eq!((&body[..], body.as_ptr()), (&b"page"[..], ptr));
+        pool.give(BytesMut::with_capacity(16));
+        assert_eq!(pool.idle(), 1);
+
+        // The memory comes back once the body handed out is dropped
+        drop(body);
+        let buffer = pool.take(1024);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.as_ptr(), ptr);
+        pool.give(BytesMut::with_capacity(MAX_POOLED_CAPACITY + 1));
+        assert_eq!(pool.idle(), 0);
+    }
+}
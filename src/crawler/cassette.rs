@@ -0,0 +1,170 @@
+use crate::common::error::{Error, Result};
+use crate::crawler::fetcher::{FetchResponse, HttpFetcher};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use crate::storage::{FlushPolicy, RecordWriter};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+use url::Url;
+
+/// How a recorded fetch ended
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum RecordedOutcome {
+    Response { response: FetchResponse },
+    HttpStatus { status: u16 },
+    RedirectOutOfScope { target: String },
+    Attachment,
+    Binary,
+    MimeMismatch { sniffed: String },
+    Failed { message: String },
+}
+
+impl RecordedOutcome {
+    fn from_result(result: &Result<FetchResponse>) -> Self {
+        match result {
+            Ok(response) => RecordedOutcome::Response {
+                response: response.clone(),
+            },
+            Err(Error::HttpStatus(status, _)) => RecordedOutcome::HttpStatus { status: *status },
+            Err(Error::RedirectOutOfScope(target)) => RecordedOutcome::RedirectOutOfScope {
+                target: target.clone(),
+            },
+            Err(Error::Attachment(_)) => RecordedOutcome::Attachment,
+            Err(Error::BinaryContent(_)) => RecordedOutcome::Binary,
+            Err(Error::MimeMismatch(_, sniffed)) => RecordedOutcome::MimeMismatch {
+                sniffed: sniffed.clone(),
+            },
+            Err(e) => RecordedOutcome::Failed { message: e.to_string() },
+        }
+    }
+
+    fn into_result(self, url: &Url) -> Result<FetchResponse> {
+        match self {
+            RecordedOutcome::Response { response } => Ok(response),
+            RecordedOutcome::HttpStatus { status } => Err(Error::HttpStatus(status, url.to_string())),
+            RecordedOutcome::RedirectOutOfScope { target } => Err(Error::RedirectOutOfScope(target)),
+            RecordedOutcome::Attachment => Err(Error::Attachment(url.to_string())),
+            RecordedOutcome::Binary => Err(Error::BinaryContent(url.to_string())),
+            RecordedOutcome::MimeMismatch { sniffed } => Err(Error::MimeMismatch(url.to_string(), sniffed)),
+            RecordedOutcome::Failed { message } => Err(Error::HttpError(message)),
+        }
+    }
+}
+
+/// One fetch in a cassette file, stored as a line of JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CassetteEntry {
+    pub url: Url,
+    #[serde(flatten)]
+    pub outcome: RecordedOutcome,
+}
+
+/// [`HttpFetcher`] that passes fetches to another fetcher and appends every
+/// request and its outcome to a cassette file, for [`ReplayFetcher`]
+pub struct RecordingFetcher {
+    inner: Arc<dyn HttpFetcher>,
+    file: Mutex<RecordWriter>,
+}
+
+impl RecordingFetcher {
+    /// Record the fetches of `inner` to `path`, replacing any existing file
+    pub fn new(inner: Arc<dyn HttpFetcher>, path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_flush_policy(inner, path, FlushPolicy::default())
+    }
+
+    /// Record to `path`, flushing entries to disk as `policy` says
+    pub fn with_flush_policy(
+        inner: Arc<dyn HttpFetcher>,
+        path: impl AsRef<Path>,
+        policy: FlushPolicy,
+    ) -> Result<Self> {
+        let file = RecordWriter::create(path, policy)?;
+        Ok(Self {
+            inner,
+            file: Mutex::new(file),
+        })
+    }
+
+    fn record(&self, url: &Url, result: &Result<FetchResponse>) {
+        let entry = CassetteEntry {
+            url: url.clone(),
+            outcome: RecordedOutcome::from_result(result),
+        };
+        let written = serde_json::to_string(&entry).map_err(Error::from).and_then(|line| {
+            self.file.lock().unwrap().write_record(&line)
+        });
+        if let Err(e) = written {
+            warn!("Failed to record fetch of {}: {}", url, e);
+        }
+    }
+}
+
+#[async_trait]
+impl HttpFetcher for RecordingFetcher {
+    async fn fetch_accepting(&self, url: &Url, content_types: &[&str]) -> Result<FetchResponse> {
+        self.fetch_with_referer(url, content_types, None).await
+    }
+
+    async fn fetch_with_referer(
+        &self,
+        url: &Url,
+        content_types: &[&str],
+        referer: Option<&Url>,
+    ) -> Result<FetchResponse> {
+        let result = self.inner.fetch_with_referer(url, content_types, referer).await;
+        self.record(url, &result);
+        result
+    }
+}
+
+/// [`HttpFetcher`] answering from a cassette written by
+/// [`RecordingFetcher`], without touching the network.
+///
+/// A URL fetched several times while recording replays its outcomes in the
+/// same order, repeating the last one once they run out. URLs not in the
+/// cassette fail.
+#[derive(Debug, Default)]
+pub struct ReplayFetcher {
+    outcomes: Mutex<HashMap<Url, VecDeque<RecordedOutcome>>>,
+}
+
+impl ReplayFetcher {
+    /// Load the cassette at `path`
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let mut outcomes: HashMap<Url, VecDeque<RecordedOutcome>> = HashMap::new();
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: CassetteEntry = serde_json::from_str(&line)?;
+            outcomes.entry(entry.url).or_default().push_back(entry.outcome);
+        }
+        Ok(Self {
+            outcomes: Mutex::new(outcomes),
+        })
+    }
+}
+
+#[async_trait]
+impl HttpFetcher for ReplayFetcher {
+    async fn fetch_accepting(&self, url: &Url, _content_types: &[&str]) -> Result<FetchResponse> {
+        let outcome = {
+            let mut outcomes = self.outcomes.lock().unwrap();
+            let queue = outcomes
+                .get_mut(url)
+                .ok_or_else(|| Error::HttpError(format!("{} is not in the cassette", url)))?;
+            if queue.len() > 1 {
+                queue.pop_front().unwrap()
+            } else {
+                queue[0].clone()
+            }
+        };
+        outcome.into_result(url)
+    }
+}
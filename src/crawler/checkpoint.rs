@@ -0,0 +1,49 @@
+use crate::common::error::Result;
+use crate::crawler::frontier::FrontierSnapshot;
+use crate::crawler::CrawlStats;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Name of the checkpoint file inside the checkpoint directory
+pub const CHECKPOINT_FILE: &str = "checkpoint.json";
+
+/// Everything needed to pick a crawl up where it left off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub written_at: DateTime<Utc>,
+    pub frontier: FrontierSnapshot,
+    pub stats: CrawlStats,
+    /// Milliseconds since each domain was last requested, so per-domain
+    /// delays still hold after resuming
+    pub domain_last_access_ms: HashMap<String, u64>,
+}
+
+impl Checkpoint {
+    /// Path of the checkpoint file in `dir`
+    pub fn path(dir: &Path) -> PathBuf {
+        dir.join(CHECKPOINT_FILE)
+    }
+
+    /// Write the checkpoint into `dir`. The file is written under a
+    /// temporary name and renamed into place, so a crash mid-write leaves
+    /// the previous checkpoint intact.
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)?;
+        let temp_path = dir.join(format!("{}.tmp", CHECKPOINT_FILE));
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(&serde_json::to_vec(self)?)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, Self::path(dir))?;
+        Ok(())
+    }
+
+    /// Read the checkpoint in `dir`
+    pub fn read(dir: &Path) -> Result<Self> {
+        let contents = fs::read(Self::path(dir))?;
+        Ok(serde_json::from_slice(&contents)?)
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Per-domain circuit breaker that stops crawling a domain whose
+/// error rate over a rolling window exceeds a threshold
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    /// Error rate (0.0 - 1.0) above which the breaker trips
+    error_rate_threshold: f64,
+    /// Rolling window over which the error rate is computed
+    error_window: Duration,
+    /// How long a tripped domain stays blocked
+    cooldown: Duration,
+    /// Minimum outcomes in the window before the breaker may trip
+    min_samples: usize,
+    domains: HashMap<String, DomainState>,
+}
+
+#[derive(Debug, Default)]
+struct DomainState {
+    /// Recent outcomes as (time, was_error)
+    outcomes: VecDeque<(Instant, bool)>,
+    /// Set while the breaker is open
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(
+        error_rate_threshold: f64,
+        error_window: Duration,
+        cooldown: Duration,
+        min_samples: usize,
+    ) -> Self {
+        Self {
+            error_rate_threshold,
+            error_window,
+            cooldown,
+            min_samples,
+            domains: HashMap::new(),
+        }
+    }
+
+    /// Check whether requests to a domain are currently blocked
+    pub fn is_open(&mut self, domain: &str) -> bool {
+        self.is_open_at(domain, Instant::now())
+    }
+
+    /// Record the outcome of a request. Returns true if this outcome tripped the breaker.
+    pub fn record(&mut self, domain: &str, success: bool) -> bool {
+        self.record_at(domain, success, Instant::now())
+    }
+
+    fn is_open_at(&mut self, domain: &str, now: Instant) -> bool {
+        let Some(state) = self.domains.get_mut(domain) else {
+            return false;
+        };
+
+        match state.open_until {
+            Some(until) if now < until => true,
+            Some(_) => {
+                // Cooldown elapsed, start over with a clean window
+                state.open_until = None;
+                state.outcomes.clear();
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn record_at(&mut self, domain: &str, success: bool, now: Instant) -> bool {
+        let state = self.domains.entry(domain.to_string()).or_default();
+        if state.open_until.is_some() {
+            return false;
+        }
+
+        state.outcomes.push_back((now, !success));
+        while let Some(&(at, _)) = state.outcomes.front() {
+            if now.duration_since(at) > self.error_window {
+                state.outcomes.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        let total = state.outcomes.len();
+        if total < self.min_samples {
+            return false;
+        }
+
+        let errors = state.outcomes.iter().filter(|(_, is_error)| *is_error).count();
+        let rate = errors as f64 / total as f64;
+        if rate > self.error_rate_threshold {
+            warn!(
+                "Circuit breaker tripped for {} ({:.0}% errors), pausing for {:?}",
+                domain,
+                rate * 100.0,
+                self.cooldown
+            );
+            state.open_until = Some(now + self.cooldown);
+            return true;
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trips_on_error_rate() {
+        let mut breaker = CircuitBreaker::new(0.5, Duration::from_secs(60), Duration::from_secs(30), 4);
+        let now = Instant::now();
+
+        assert!(!breaker.record_at("example.com", false, now));
+        assert!(!breaker.record_at("example.com", true, now));
+        assert!(!breaker.record_at("example.com", false, now));
+        assert!(!breaker.is_open_at("example.com", now));
+
+        // 3 errors out of 4 exceeds 50%
+        assert!(breaker.record_at("example.com", false, now));
+        assert!(breaker.is_open_at("example.com", now));
+        assert!(!breaker.is_open_at("other.com", now));
+
+        // Closed again after the cooldown
+        assert!(breaker.is_open_at("example.com", now + Duration::from_secs(29)));
+        assert!(!breaker.is_open_at("example.com", now + Duration::from_secs(31)));
+    }
+
+    #[test]
+    fn test_old_errors_leave_window() {
+        let mut breaker = CircuitBreaker::new(0.5, Duration::from_secs(10), Duration::from_secs(30), 2);
+        let start = Instant::now();
+
+        breaker.record_at("example.com", false, start);
+
+        // The first error has aged out, so one error in two is not above 50%
+        let later = start + Duration::from_secs(20);
+        assert!(!breaker.record_at("example.com", true, later));
+        assert!(!breaker.record_at("example.com", false, later));
+        assert!(!breaker.is_open_at("example.com", later));
+    }
+}
@@ -1,30 +1,180 @@
 use crate::common::error::{Error, Result};
-use crate::crawler::{Fetcher, Parser, UrlFrontier, CrawlTask, RobotsChecker};
+use crate::indexer::{IndexDocument, Indexer};
+use crate::storage::{FlushPolicy, NoopStore, Storage, StoredPage};
+use crate::crawler::{parse_date, truncate_html, Fetcher, FetchResponse, FetcherConfig, HttpFetcher, ReferrerPolicy, UserAgentRotation, Parser, ParsedPage, UrlFrontier, CrawlTask, CrawlStrategy, TrailingSlash, RobotsChecker, DnsResolver, RobotsOverride, CircuitBreaker, SitemapParser, ApiPaginator, Link};
+use crate::crawler::domain_budget::DomainBudget;
+use crate::crawler::dns::DEFAULT_DNS_CACHE_TTL;
+use crate::crawler::domain_stats::{DomainStats, DomainStatsTracker, DEFAULT_MAX_TRACKED_DOMAINS};
+use crate::crawler::slow_start::{DomainConcurrency, SlowStartConfig};
+use crate::crawler::adaptive_delay::{AdaptiveDelay, AdaptiveDelayConfig};
+use crate::crawler::rate_limiter::TokenBucket;
+use crate::crawler::same_site::SameSitePolicy;
+use crate::crawler::reload::{LiveSettings, ReloadableConfig};
+use crate::crawler::politeness::PolitenessProfile;
+use crate::crawler::report::CrawlReport;
+use crate::crawler::retry::{DefaultRetryPolicy, RetryPolicy};
+use crate::crawler::checkpoint::Checkpoint;
+use crate::crawler::seed_limits::SeedLimits;
+use crate::crawler::cassette::{RecordingFetcher, ReplayFetcher};
+use crate::common::config::SeedConfig;
+use crate::crawler::events::{CrawlEvent, SkipReason, EVENT_CHANNEL_CAPACITY};
+use crate::crawler::response_cache::{ResponseCache, DEFAULT_RESPONSE_CACHE_ENTRIES};
+use crate::crawler::extractor::{ContentExtractor, ExtractorRegistry};
+use crate::crawler::fetcher::{DEFAULT_BINARY_THRESHOLD, DEFAULT_MAX_REDIRECTS, HTML_CONTENT_TYPES, JSON_CONTENT_TYPES, XML_CONTENT_TYPES};
+use crate::crawler::frontier::{Frontier, DEFAULT_PRIORITY, NEXT_PAGE_PRIORITY};
+use crate::crawler::sitemap::MAX_SITEMAP_INDEX_DEPTH;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::time::{Duration, Instant};
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, mpsc, Mutex, Semaphore, SemaphorePermit};
 use tokio::time::sleep;
-use tracing::{info, warn, error};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use tracing::{debug, field, info, info_span, warn, error, Instrument, Span};
 use url::Url;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::io::BufRead;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+/// Default number of failed URLs kept in `CrawlStats::failed_urls`
+pub const DEFAULT_MAX_FAILED_URLS: usize = 100;
 
 /// Statistics about the crawl
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct CrawlStats {
     pub pages_crawled: usize,
     pub pages_failed: usize,
     pub total_links_found: usize,
+    /// Pages whose body length fell outside the configured content-length range
+    pub pages_out_of_range: usize,
+    /// Number of times a domain's circuit breaker tripped
+    pub breaker_trips: usize,
+    /// Tasks skipped because their domain's circuit breaker was open
+    pub breaker_skipped: usize,
+    /// Tasks skipped because their domain had used up its weighted share of pages
+    pub pages_over_budget: usize,
+    /// URLs not fetched because robots.txt disallows them
+    pub robots_blocked: usize,
+    /// Pages cut to `max_parse_size` before parsing
+    pub pages_parse_truncated: usize,
+    /// Links dropped for introducing a host past `max_unique_hosts`
+    pub new_hosts_dropped: usize,
+    /// Failed fetches queued again by the retry policy
+    pub pages_retried: usize,
+    /// Links dropped for exceeding `max_query_variants` on their path
+    pub query_variants_dropped: usize,
+    /// Responses not crawled for being `Content-Disposition: attachment` downloads
+    pub attachments_skipped: usize,
+    /// Responses not crawled because their body looked binary
+    pub binary_skipped: usize,
+    /// Responses not crawled because their body sniffed as another format
+    pub mime_mismatch_skipped: usize,
+    /// Pages not crawled because parsing them exceeded `parse_timeout`
+    pub parse_timeouts: usize,
+    /// URLs not fetched because the store has a copy crawled after `crawl_since`
+    pub fresh_skipped: usize,
+    /// 3xx responses recorded without following them, with `audit_redirects`
+    pub redirects_audited: usize,
+    /// Responses received per HTTP status code
+    pub status_codes: BTreeMap<u16, usize>,
+    /// URLs that failed for good, after any retries, with their last error;
+    /// at most `max_failed_urls` per crawl
+    #[serde(default)]
+    pub failed_urls: Vec<(Url, String)>,
+    #[serde(skip)]
     pub start_time: Option<Instant>,
+    #[serde(skip)]
     pub end_time: Option<Instant>,
+    /// Wall-clock counterparts of `start_time` and `end_time`, which survive
+    /// serialization so stats from other processes can be merged
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl CrawlStats {
+    /// Time from start to end, by the wall clock when known and the
+    /// monotonic clock otherwise
     pub fn duration(&self) -> Option<Duration> {
+        if let (Some(start), Some(end)) = (self.started_at, self.finished_at) {
+            return (end - start).to_std().ok();
+        }
         match (self.start_time, self.end_time) {
             (Some(start), Some(end)) => Some(end - start),
             _ => None,
         }
     }
+    
+    /// Fold in the stats of another part of the same crawl, such as another
+    /// shard. Counts and per-status counts are summed. The merged span runs
+    /// from the earliest start to the latest end; a time missing on one side,
+    /// like the `Instant`s of stats read from another process, is taken from
+    /// the other side.
+    pub fn merge(&mut self, other: &CrawlStats) {
+        let CrawlStats {
+            pages_crawled,
+            pages_failed,
+            total_links_found,
+            pages_out_of_range,
+            breaker_trips,
+            breaker_skipped,
+            pages_over_budget,
+            robots_blocked,
+            pages_parse_truncated,
+            new_hosts_dropped,
+            pages_retried,
+            query_variants_dropped,
+            attachments_skipped,
+            binary_skipped,
+            mime_mismatch_skipped,
+            parse_timeouts,
+            fresh_skipped,
+            redirects_audited,
+            status_codes,
+            failed_urls,
+            start_time,
+            end_time,
+            started_at,
+            finished_at,
+        } = other;
+        self.pages_crawled += pages_crawled;
+        self.pages_failed += pages_failed;
+        self.total_links_found += total_links_found;
+        self.pages_out_of_range += pages_out_of_range;
+        self.breaker_trips += breaker_trips;
+        self.breaker_skipped += breaker_skipped;
+        self.pages_over_budget += pages_over_budget;
+        self.robots_blocked += robots_blocked;
+        self.pages_parse_truncated += pages_parse_truncated;
+        self.new_hosts_dropped += new_hosts_dropped;
+        self.pages_retried += pages_retried;
+        self.query_variants_dropped += query_variants_dropped;
+        self.attachments_skipped += attachments_skipped;
+        self.binary_skipped += binary_skipped;
+        self.mime_mismatch_skipped += mime_mismatch_skipped;
+        self.parse_timeouts += parse_timeouts;
+        self.fresh_skipped += fresh_skipped;
+        self.redirects_audited += redirects_audited;
+        for (status, count) in status_codes {
+            *self.status_codes.entry(*status).or_default() += count;
+        }
+        self.failed_urls.extend(failed_urls.iter().cloned());
+        self.start_time = merge_times(self.start_time, *start_time, Ord::min);
+        self.end_time = merge_times(self.end_time, *end_time, Ord::max);
+        self.started_at = merge_times(self.started_at, *started_at, Ord::min);
+        self.finished_at = merge_times(self.finished_at, *finished_at, Ord::max);
+    }
+}
+
+/// Pick between two optional times, keeping whichever is present
+fn merge_times<T: Ord>(a: Option<T>, b: Option<T>, pick: fn(T, T) -> T) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(pick(a, b)),
+        (a, b) => a.or(b),
+    }
 }
 
 /// Configuration for the crawler
@@ -34,9 +184,225 @@ pub struct CrawlerConfig {
     pub max_depth: usize,
     pub max_concurrent: usize,
     pub delay_ms: u64,
+    /// Primary user agent. Sent with requests unless `user_agents` is set,
+    /// and always the agent robots.txt groups are matched against.
     pub user_agent: String,
+    /// Agents rotated through per request, overriding `user_agent` when non-empty
+    pub user_agents: Vec<String>,
+    pub user_agent_rotation: UserAgentRotation,
     pub timeout_seconds: u64,
     pub max_page_size: usize,
+    /// Longest HTML string handed to the parser; longer pages are cut at a
+    /// tag boundary first. `None` parses whatever was downloaded.
+    pub max_parse_size: Option<usize>,
+    /// Longest a page may take to parse before it's skipped, so adversarial
+    /// HTML can't hold a worker indefinitely. `None` waits for any parse.
+    pub parse_timeout: Option<Duration>,
+    /// Attributes besides `href` to take links from, such as `data-href`
+    /// or `onclick`. Empty (the default) since the results are a guess.
+    pub link_attributes: Vec<String>,
+    /// Text and links nested deeper than this many elements are ignored
+    pub max_dom_depth: Option<usize>,
+    /// Minimum body length (in bytes) for a page to be indexed
+    pub min_content_length: Option<usize>,
+    /// Maximum body length (in bytes) for a page to be indexed
+    pub max_content_length: Option<usize>,
+    /// Per-domain error rate (0.0 - 1.0) that trips the circuit breaker; `None` disables it
+    pub error_rate_threshold: Option<f64>,
+    /// Rolling window over which the error rate is measured
+    pub error_window: Duration,
+    /// How long a domain is skipped after its breaker trips
+    pub cooldown: Duration,
+    /// Minimum requests in the window before the breaker may trip
+    pub error_min_samples: usize,
+    /// Maximum simultaneous in-flight HTTP requests across all workers; `None` means no cap
+    pub max_inflight_requests: Option<usize>,
+    /// Host names resolved to fixed socket addresses instead of using DNS
+    pub resolve_overrides: HashMap<String, SocketAddr>,
+    /// Random jitter applied to each per-domain delay, as a percentage of `delay_ms` (e.g. 20.0 = ±20%)
+    pub delay_jitter_percent: f64,
+    /// Seed for the jitter RNG; `None` seeds from entropy
+    pub jitter_seed: Option<u64>,
+    /// Order in which the frontier hands out tasks
+    pub strategy: CrawlStrategy,
+    /// Relative weights splitting `max_pages` between domains (and their
+    /// subdomains); domains not listed are not limited
+    pub domain_weights: HashMap<String, f32>,
+    /// How long fetched responses are reused for repeat requests of the same
+    /// URL; `None` disables the response cache
+    pub response_cache_ttl: Option<Duration>,
+    /// Maximum number of responses kept in the response cache
+    pub response_cache_entries: usize,
+    /// Ramp each domain's concurrent requests up from a low start, backing
+    /// off on errors; `None` leaves domains unlimited
+    pub slow_start: Option<SlowStartConfig>,
+    /// Slow down each domain that answers 429 or 503, recovering after
+    /// sustained success; `None` keeps the configured delay
+    pub adaptive_delay: Option<AdaptiveDelayConfig>,
+    /// Space requests per resolved IP address instead of per host name, so
+    /// hosts sharing a CDN or shared-hosting server share one delay. Hosts
+    /// that don't resolve fall back to their name.
+    pub rate_limit_by_ip: bool,
+    /// Queue the `hreflang` language variants of crawled pages
+    pub follow_alternates: bool,
+    /// Links carrying any of these `rel` tokens are not queued
+    pub skip_link_rels: Vec<String>,
+    /// Maximum requests per second across the whole crawl, on top of the
+    /// per-domain delays; `None` means no cap
+    pub global_rate_limit: Option<f64>,
+    /// Non-2xx statuses whose pages are still parsed for links. They are
+    /// counted as failures and not stored or indexed.
+    pub parse_status_codes: Vec<u16>,
+    /// Keep the crawl on the seeds' sites; `None` follows links anywhere
+    pub same_site: Option<SameSitePolicy>,
+    /// Keep the crawl on the registrable domains of the seeds, including
+    /// seeds added mid-crawl. Shorthand for `same_site` set to
+    /// [`SameSitePolicy::RegistrableDomain`]; an explicit `same_site` wins.
+    pub auto_restrict_to_seeds: bool,
+    /// Domains given their own entry in the per-domain stats; the rest are
+    /// added up under "other"
+    pub max_tracked_domains: usize,
+    /// Send the page a link was found on as the `Referer` when fetching it
+    pub send_referer: bool,
+    /// Which requests get a `Referer` when `send_referer` is on
+    pub referrer_policy: ReferrerPolicy,
+    /// Only crawl these domains and their subdomains; empty allows any
+    pub allowed_domains: Vec<String>,
+    /// Times a failed fetch is queued again when the retry policy deems
+    /// the failure retryable. 0 disables retries.
+    pub max_retries: u32,
+    /// Wait before a retry, doubled for each further attempt; `None` retries
+    /// as soon as a worker picks the task up again
+    pub retry_backoff: Option<Duration>,
+    /// Longest wait before a retry, however many attempts came before
+    pub retry_backoff_max: Duration,
+    /// Wait a random time between zero and the backoff ("full jitter"), so
+    /// workers that failed together don't retry together. Uses the
+    /// `jitter_seed` RNG.
+    pub retry_jitter: bool,
+    /// Most failed URLs kept in `CrawlStats::failed_urls`; later failures
+    /// are still counted in `pages_failed`
+    pub max_failed_urls: usize,
+    /// Most URLs differing only in their query string queued per path;
+    /// `None` means no cap
+    pub max_query_variants: Option<usize>,
+    /// How trailing slashes on URL paths are normalized before dedup
+    pub trailing_slash: TrailingSlash,
+    /// Most distinct hosts the crawl visits; links to further hosts are
+    /// dropped. `None` means no cap.
+    pub max_unique_hosts: Option<usize>,
+    /// Most URLs ever queued, fetched or not, to bound memory and scope;
+    /// unlike `max_pages` this counts failures and unfetched URLs too.
+    /// `None` means no cap.
+    pub max_urls_discovered: Option<usize>,
+    /// sled database keeping the URLs already seen, so later crawls with the
+    /// same database skip them, seeds included; `None` keeps them in memory
+    pub dedup_db_path: Option<PathBuf>,
+    /// JSON file fetched robots.txt rules are kept in between crawls, so
+    /// they aren't fetched again within the hour; `None` keeps them in memory
+    pub robots_cache_path: Option<PathBuf>,
+    /// Longest chain of redirects followed for one page. Each hop must stay
+    /// within `same_site` and `allowed_domains`.
+    pub max_redirects: u32,
+    /// Record redirects instead of following them: each 3xx response is
+    /// stored with its status and `Location` header, reported as a
+    /// [`CrawlEvent::Redirected`], and its target queued as a link of the
+    /// page, so broken targets and chains show up in the crawl
+    pub audit_redirects: bool,
+    /// Crawl `file://` URLs from disk, following links between local files
+    pub allow_file_scheme: bool,
+    /// Directory local files must be inside; `None` allows any file
+    pub file_root: Option<PathBuf>,
+    /// Directory the crawl state is checkpointed to; `None` disables
+    /// checkpoints
+    pub checkpoint_dir: Option<PathBuf>,
+    /// Time between checkpoints while the crawl runs
+    pub checkpoint_interval: Duration,
+    /// Reuse body read buffers between fetches, one per worker
+    pub pool_body_buffers: bool,
+    /// Skip responses sent as downloads with `Content-Disposition: attachment`,
+    /// even when their content type is HTML
+    pub skip_attachments: bool,
+    /// Fraction of control bytes in a body's first KB above which it's
+    /// skipped as binary, even when labeled as HTML; `None` disables the check
+    pub binary_threshold: Option<f64>,
+    /// Skip responses whose first bytes sniff as an image, PDF, archive or
+    /// other binary format their `Content-Type` doesn't name
+    pub sniff_content: bool,
+    /// Most DNS lookups in flight at once; `None` means no cap
+    pub max_concurrent_dns: Option<usize>,
+    /// How long resolved host addresses are reused; `None` disables the
+    /// cache, unless `rate_limit_by_ip` is on
+    pub dns_cache_ttl: Option<Duration>,
+    /// Resolver used instead of one built from the DNS settings above
+    pub dns_resolver: Option<DnsResolver>,
+    /// Rules used instead of robots.txt on these hosts, such as sites the
+    /// crawl has permission to crawl in full
+    pub robots_overrides: HashMap<String, RobotsOverride>,
+    /// Obey robots.txt disallow rules. Only turn this off for sites you
+    /// own or in testing.
+    pub respect_robots: bool,
+    /// Wait out robots.txt crawl delays, even when `respect_robots` is off
+    pub honor_robots_crawl_delay: bool,
+    /// Crawl without storing or indexing pages when the storage backend or
+    /// index can't be written, instead of failing at startup
+    pub optional_outputs: bool,
+    /// Only fetch URLs the storage backend has no page for, or whose page
+    /// was crawled before this time, for refreshing an earlier crawl
+    pub crawl_since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Cassette file every fetch and its outcome is recorded to
+    pub record_path: Option<PathBuf>,
+    /// When records written to output files like the cassette are flushed
+    /// to disk
+    pub output_flush: FlushPolicy,
+    /// Queue the URLs of the sitemaps a site's robots.txt lists, once per
+    /// site, as far as the page budget allows
+    pub follow_sitemaps: bool,
+    /// Seeds with depth, page and delay overrides for their hosts, queued
+    /// by [`Crawler::add_configured_seeds`]
+    pub seeds: Vec<SeedConfig>,
+}
+
+impl CrawlerConfig {
+    /// Build the fetcher configuration for this crawl
+    pub fn fetcher_config(&self) -> FetcherConfig {
+        FetcherConfig {
+            user_agent: self.user_agent.clone(),
+            timeout_seconds: self.timeout_seconds,
+            max_size: self.max_page_size,
+            resolve_overrides: self.resolve_overrides.clone(),
+            user_agents: self.user_agents.clone(),
+            user_agent_rotation: self.user_agent_rotation,
+            parse_status_codes: self.parse_status_codes.clone(),
+            max_redirects: self.max_redirects,
+            allow_file_scheme: self.allow_file_scheme,
+            file_root: self.file_root.clone(),
+            body_buffer_pool_size: if self.pool_body_buffers { self.max_concurrent } else { 0 },
+            skip_attachments: self.skip_attachments,
+            binary_threshold: self.binary_threshold,
+            sniff_content: self.sniff_content,
+            max_concurrent_dns: self.max_concurrent_dns,
+            // Rate limiting by IP resolves every task's host, so cache answers
+            dns_cache_ttl: self.dns_cache_ttl.or(self.rate_limit_by_ip.then_some(DEFAULT_DNS_CACHE_TTL)),
+            audit_redirects: self.audit_redirects,
+        }
+    }
+    
+    /// Check whether a body length falls within the configured content-length range.
+    /// Both bounds are inclusive.
+    pub fn content_length_in_range(&self, len: usize) -> bool {
+        if let Some(min) = self.min_content_length {
+            if len < min {
+                return false;
+            }
+        }
+        if let Some(max) = self.max_content_length {
+            if len > max {
+                return false;
+            }
+        }
+        true
+    }
 }
 
 impl Default for CrawlerConfig {
@@ -47,64 +413,592 @@ impl Default for CrawlerConfig {
             max_concurrent: 10,
             delay_ms: 1000,
             user_agent: "RustCrawler/0.1.0".to_string(),
+            user_agents: Vec::new(),
+            user_agent_rotation: UserAgentRotation::default(),
             timeout_seconds: 30,
             max_page_size: 10 * 1024 * 1024, // 10MB
+            max_parse_size: None,
+            parse_timeout: None,
+            link_attributes: Vec::new(),
+            max_dom_depth: None,
+            min_content_length: None,
+            max_content_length: None,
+            error_rate_threshold: None,
+            error_window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(300),
+            error_min_samples: 10,
+            max_inflight_requests: None,
+            resolve_overrides: HashMap::new(),
+            delay_jitter_percent: 0.0,
+            jitter_seed: None,
+            strategy: CrawlStrategy::default(),
+            domain_weights: HashMap::new(),
+            response_cache_ttl: None,
+            response_cache_entries: DEFAULT_RESPONSE_CACHE_ENTRIES,
+            slow_start: None,
+            adaptive_delay: None,
+            rate_limit_by_ip: false,
+            follow_alternates: false,
+            skip_link_rels: vec!["nofollow".to_string()],
+            global_rate_limit: None,
+            parse_status_codes: Vec::new(),
+            same_site: None,
+            auto_restrict_to_seeds: false,
+            max_tracked_domains: DEFAULT_MAX_TRACKED_DOMAINS,
+            send_referer: false,
+            referrer_policy: ReferrerPolicy::default(),
+            allowed_domains: Vec::new(),
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            audit_redirects: false,
+            dedup_db_path: None,
+            robots_cache_path: None,
+            max_unique_hosts: None,
+            max_urls_discovered: None,
+            max_query_variants: None,
+            trailing_slash: TrailingSlash::default(),
+            max_retries: 0,
+            retry_backoff: None,
+            retry_backoff_max: Duration::from_secs(60),
+            retry_jitter: true,
+            max_failed_urls: DEFAULT_MAX_FAILED_URLS,
+            allow_file_scheme: false,
+            file_root: None,
+            checkpoint_dir: None,
+            checkpoint_interval: Duration::from_secs(60),
+            pool_body_buffers: false,
+            seeds: Vec::new(),
+            follow_sitemaps: false,
+            record_path: None,
+            output_flush: FlushPolicy::default(),
+            skip_attachments: true,
+            binary_threshold: Some(DEFAULT_BINARY_THRESHOLD),
+            sniff_content: false,
+            max_concurrent_dns: None,
+            dns_cache_ttl: None,
+            dns_resolver: None,
+            robots_overrides: HashMap::new(),
+            respect_robots: true,
+            honor_robots_crawl_delay: true,
+            optional_outputs: false,
+            crawl_since: None,
         }
     }
 }
 
+/// HTML parser for the config's extraction settings, or `None` when the
+/// defaults apply
+fn html_parser(config: &CrawlerConfig) -> Option<Parser> {
+    if config.link_attributes.is_empty() && config.max_dom_depth.is_none() {
+        return None;
+    }
+    let mut parser = Parser::new().link_attributes(&config.link_attributes);
+    if let Some(depth) = config.max_dom_depth {
+        parser = parser.max_dom_depth(depth);
+    }
+    Some(parser)
+}
+
+/// Date of the response's `Last-Modified` header, if it parses
+fn last_modified(response: &FetchResponse) -> Option<chrono::DateTime<chrono::Utc>> {
+    response
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("last-modified"))
+        .and_then(|(_, value)| parse_date(value))
+}
+
+/// Apply a random ±`percent` jitter to a delay in milliseconds
+pub(crate) fn jittered_delay<R: Rng + ?Sized>(delay_ms: u64, percent: f64, rng: &mut R) -> Duration {
+    if percent <= 0.0 || delay_ms == 0 {
+        return Duration::from_millis(delay_ms);
+    }
+    
+    let fraction = percent.min(100.0) / 100.0;
+    let factor = rng.gen_range(1.0 - fraction..=1.0 + fraction);
+    Duration::from_secs_f64(delay_ms as f64 * factor / 1000.0)
+}
+
+/// Wait before retry number `attempt` (0 for the first retry): `base`
+/// doubled per attempt and capped at `max`, or with `full_jitter` a random
+/// time up to that
+pub(crate) fn retry_delay<R: Rng + ?Sized>(
+    base: Duration,
+    max: Duration,
+    attempt: u32,
+    full_jitter: bool,
+    rng: &mut R,
+) -> Duration {
+    let backoff = base.saturating_mul(2u32.saturating_pow(attempt)).min(max);
+    if !full_jitter || backoff.is_zero() {
+        return backoff;
+    }
+    Duration::from_secs_f64(rng.gen_range(0.0..=backoff.as_secs_f64()))
+}
+
 /// Web crawler that coordinates fetching, parsing, and URL management
 pub struct Crawler {
     config: CrawlerConfig,
-    frontier: UrlFrontier,
-    fetcher: Fetcher,
+    frontier: Arc<dyn Frontier>,
+    fetcher: Arc<dyn HttpFetcher>,
     parser: Parser,
+    extractors: ExtractorRegistry,
     robots_checker: RobotsChecker,
     stats: Arc<Mutex<CrawlStats>>,
+    domain_stats: Arc<std::sync::Mutex<DomainStatsTracker>>,
     domain_last_access: Arc<Mutex<HashMap<String, Instant>>>,
+    finished: Arc<AtomicBool>,
+    /// Outputs failed validation and are optional, so pages are only crawled
+    crawl_only: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+    circuit_breaker: Option<Arc<Mutex<CircuitBreaker>>>,
+    inflight: Option<Arc<Semaphore>>,
+    domain_budget: Option<Arc<Mutex<DomainBudget>>>,
+    seed_limits: Arc<Mutex<SeedLimits>>,
+    /// Origins whose robots.txt sitemaps were already followed
+    sitemaps_followed: Arc<Mutex<HashSet<String>>>,
+    domain_concurrency: Option<Arc<Mutex<DomainConcurrency>>>,
+    /// Extra delay per domain from throttled responses, if adaptive
+    adaptive_delay: Option<Arc<Mutex<AdaptiveDelay>>>,
+    /// Resolves hosts to the IP addresses keying the delay, if
+    /// `rate_limit_by_ip`
+    ip_resolver: Option<DnsResolver>,
+    global_rate_limiter: Arc<Mutex<Option<TokenBucket>>>,
+    /// Settings that can be changed while the crawl runs
+    live: Arc<LiveSettings>,
+    storage: Arc<dyn Storage>,
+    indexer: Option<Indexer>,
+    rng: Arc<std::sync::Mutex<StdRng>>,
+    events: broadcast::Sender<CrawlEvent>,
+    retry_policy: Arc<dyn RetryPolicy>,
+    /// Receives every crawled page, for [`Crawler::crawl_stream`]
+    page_sink: Option<mpsc::Sender<Result<(Url, ParsedPage)>>>,
 }
 
 impl Crawler {
     /// Create a new crawler with the given configuration
     pub fn new(config: CrawlerConfig) -> Self {
-        let frontier = UrlFrontier::new(config.max_pages * 2);
-        let fetcher = Fetcher::new(
-            config.user_agent.clone(),
-            config.timeout_seconds,
-            config.max_page_size,
-        );
+        Self::with_storage(config, Arc::new(NoopStore))
+    }
+    
+    /// Create a new crawler that persists crawled pages to a storage backend
+    pub fn with_storage(config: CrawlerConfig, storage: Arc<dyn Storage>) -> Self {
+        let frontier = Self::default_frontier(&config);
+        Self::with_frontier(config, storage, Arc::new(frontier))
+    }
+    
+    /// In-memory frontier set up from the crawl configuration
+    fn default_frontier(config: &CrawlerConfig) -> UrlFrontier {
+        let mut frontier = UrlFrontier::with_strategy(config.max_pages * 2, config.strategy);
+        let same_site = config
+            .same_site
+            .or(config.auto_restrict_to_seeds.then_some(SameSitePolicy::RegistrableDomain));
+        if let Some(policy) = same_site {
+            frontier = frontier.with_same_site(policy);
+        }
+        if let Some(max_hosts) = config.max_unique_hosts {
+            frontier = frontier.with_max_hosts(max_hosts);
+        }
+        if let Some(max_variants) = config.max_query_variants {
+            frontier = frontier.with_max_query_variants(max_variants);
+        }
+        if let Some(max_urls) = config.max_urls_discovered {
+            frontier = frontier.with_max_urls_discovered(max_urls);
+        }
+        frontier = frontier.with_trailing_slash(config.trailing_slash);
+        if let Some(path) = &config.dedup_db_path {
+            match frontier.clone().with_dedup_db(path) {
+                Ok(persistent) => frontier = persistent,
+                Err(e) => error!(
+                    "Failed to open dedup database {}, keeping seen URLs in memory: {}",
+                    path.display(),
+                    e
+                ),
+            }
+        }
+        frontier
+    }
+    
+    /// Create a crawler taking its URLs from `frontier`, which may be shared
+    /// with other crawler processes. The frontier settings of `config`, such
+    /// as `same_site` or `dedup_db_path`, only shape the default in-memory
+    /// frontier and are up to `frontier` here.
+    pub fn with_frontier(config: CrawlerConfig, storage: Arc<dyn Storage>, frontier: Arc<dyn Frontier>) -> Self {
+        // One resolver serves the fetcher and `rate_limit_by_ip`, so they
+        // share its cache and lookup cap
+        let fetcher_config = config.fetcher_config();
+        let resolver = config.dns_resolver.clone().or_else(|| fetcher_config.dns_resolver());
+        let ip_resolver = resolver.clone().filter(|_| config.rate_limit_by_ip);
+        let mut fetcher = Fetcher::with_resolver(fetcher_config, resolver);
+        if let Some(ttl) = config.response_cache_ttl {
+            fetcher = fetcher.with_cache(ResponseCache::new(ttl, config.response_cache_entries));
+        }
+        let live = Arc::new(LiveSettings::new(
+            config.delay_ms,
+            config.delay_jitter_percent,
+            config.allowed_domains.clone(),
+        ));
+        fetcher = fetcher.with_redirect_filter({
+            let frontier = frontier.clone();
+            let live = live.clone();
+            Arc::new(move |url: &Url| {
+                frontier.in_scope(url) && url.host_str().is_some_and(|host| live.is_host_allowed(host))
+            })
+        });
         let parser = Parser::new();
-        let robots_checker = RobotsChecker::new(config.user_agent.clone());
+        let mut extractors = ExtractorRegistry::new();
+        if let Some(html_parser) = html_parser(&config) {
+            extractors.set_html(Arc::new(html_parser));
+        }
+        let mut robots_checker = RobotsChecker::new(config.user_agent.clone())
+            .with_resolve_overrides(config.resolve_overrides.clone());
+        if let Some(path) = &config.robots_cache_path {
+            robots_checker = robots_checker.with_persistence(path);
+        }
+        for (domain, policy) in &config.robots_overrides {
+            robots_checker.set_override(domain, policy.clone());
+        }
+        let circuit_breaker = config.error_rate_threshold.map(|threshold| {
+            Arc::new(Mutex::new(CircuitBreaker::new(
+                threshold,
+                config.error_window,
+                config.cooldown,
+                config.error_min_samples,
+            )))
+        });
+        let rng = match config.jitter_seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let inflight = config
+            .max_inflight_requests
+            .map(|permits| Arc::new(Semaphore::new(permits)));
+        let domain_budget = (!config.domain_weights.is_empty()).then(|| {
+            Arc::new(Mutex::new(DomainBudget::new(config.max_pages, &config.domain_weights)))
+        });
+        let global_rate_limiter = config.global_rate_limit.map(TokenBucket::new);
+        let domain_stats = DomainStatsTracker::new(config.max_tracked_domains);
+        let domain_concurrency = config
+            .slow_start
+            .clone()
+            .map(|slow_start| Arc::new(Mutex::new(DomainConcurrency::new(slow_start))));
+        let adaptive_delay = config
+            .adaptive_delay
+            .clone()
+            .map(|adaptive_delay| Arc::new(Mutex::new(AdaptiveDelay::new(adaptive_delay))));
         
         Self {
             config,
             frontier,
-            fetcher,
+            fetcher: Arc::new(fetcher),
             parser,
+            extractors,
             robots_checker,
             stats: Arc::new(Mutex::new(CrawlStats::default())),
+            domain_stats: Arc::new(std::sync::Mutex::new(domain_stats)),
             domain_last_access: Arc::new(Mutex::new(HashMap::new())),
+            finished: Arc::new(AtomicBool::new(false)),
+            crawl_only: Arc::new(AtomicBool::new(false)),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            circuit_breaker,
+            inflight,
+            domain_budget,
+            seed_limits: Arc::new(Mutex::new(SeedLimits::default())),
+            sitemaps_followed: Arc::new(Mutex::new(HashSet::new())),
+            domain_concurrency,
+            adaptive_delay,
+            ip_resolver,
+            global_rate_limiter: Arc::new(Mutex::new(global_rate_limiter)),
+            live,
+            storage,
+            indexer: None,
+            rng: Arc::new(std::sync::Mutex::new(rng)),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            retry_policy: Arc::new(DefaultRetryPolicy),
+            page_sink: None,
         }
     }
     
     /// Add a seed URL to start crawling from
     pub async fn add_seed(&self, url: Url) -> Result<()> {
-        if !Fetcher::should_fetch(&url) {
+        self.add_seed_tagged(url, HashMap::new()).await
+    }
+    
+    /// Add a seed URL whose `tags` are carried to every page crawled from
+    /// it, and stored and indexed with them
+    pub async fn add_seed_tagged(&self, url: Url, tags: HashMap<String, String>) -> Result<()> {
+        if !self.should_fetch(&url) {
             return Err(Error::InvalidResponse("Invalid seed URL".to_string()));
         }
         
-        self.frontier.add(url, 0).await;
+        self.frontier.add_seed_site(&url);
+        self.frontier.push(CrawlTask::new(url, 0, DEFAULT_PRIORITY, None).with_tags(tags)).await;
         Ok(())
     }
     
-    /// Start crawling
+    /// Add a seed for every line of `reader`, such as standard input. Blank
+    /// lines are ignored and invalid URLs are logged and skipped. Returns the
+    /// number of valid seed URLs read.
+    pub async fn add_seeds_from_reader<R: BufRead>(&self, reader: R) -> Result<usize> {
+        let mut added = 0;
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let seeded = match Url::parse(line) {
+                Ok(url) => self.add_seed(url).await,
+                Err(e) => Err(e.into()),
+            };
+            match seeded {
+                Ok(()) => added += 1,
+                Err(e) => warn!("Skipping seed {:?}: {}", line, e),
+            }
+        }
+        Ok(added)
+    }
+    
+    /// Fetch a sitemap and add its URLs as seeds, prioritized by their
+    /// `<priority>` values. Returns the number of URLs added.
+    pub async fn add_sitemap(&self, sitemap_url: &Url) -> Result<usize> {
+        self.add_sitemap_limited(sitemap_url, usize::MAX).await
+    }
+    
+    /// Add up to `limit` URLs of a sitemap. Sitemap indexes are followed
+    /// into their child sitemaps, gzipped or not, up to
+    /// `MAX_SITEMAP_INDEX_DEPTH` levels down.
+    async fn add_sitemap_limited(&self, sitemap_url: &Url, limit: usize) -> Result<usize> {
+        let parser = SitemapParser::new();
+        let mut pending = VecDeque::from([(sitemap_url.clone(), 0)]);
+        let mut visited = HashSet::new();
+        let mut added = 0;
+        while let Some((url, depth)) = pending.pop_front() {
+            if added >= limit {
+                break;
+            }
+            if !visited.insert(url.to_string()) {
+                continue;
+            }
+            let response = match self.fetcher.fetch_accepting(&url, XML_CONTENT_TYPES).await {
+                Ok(response) => response,
+                Err(e) if depth == 0 => return Err(e),
+                Err(e) => {
+                    warn!("Failed to fetch sitemap {}: {}", url, e);
+                    continue;
+                }
+            };
+            
+            let children = parser.parse_index(&response.body);
+            if !children.is_empty() {
+                if depth < MAX_SITEMAP_INDEX_DEPTH {
+                    pending.extend(children.into_iter().map(|child| (child, depth + 1)));
+                } else {
+                    warn!("Not following sitemap index {} - nested too deep", url);
+                }
+            }
+            
+            for entry in parser.parse(&response.body) {
+                if added >= limit {
+                    break;
+                }
+                if !Fetcher::should_fetch(&entry.loc) {
+                    continue;
+                }
+                let priority = entry.crawl_priority();
+                if self.frontier.add_with_priority(entry.loc, 0, priority).await {
+                    added += 1;
+                }
+            }
+        }
+        
+        info!("Added {} URLs from sitemap {}", added, sitemap_url);
+        Ok(added)
+    }
+    
+    /// Walk the pages of a JSON API and add the item links they list as
+    /// seeds. A failure on the first page is returned; on later pages it
+    /// ends the walk. Returns the number of URLs added.
+    pub async fn add_api_pages(&self, paginator: &ApiPaginator) -> Result<usize> {
+        let mut cursor: Option<String> = None;
+        let mut added = 0;
+        for page in 0..paginator.max_pages {
+            let url = paginator.page_url(paginator.start + page as u64, cursor.as_deref())?;
+            let parsed = match self.fetcher.fetch_accepting(&url, JSON_CONTENT_TYPES).await {
+                Ok(response) => paginator.parse_page(&response.body, &response.url),
+                Err(e) => Err(e),
+            };
+            let parsed = match parsed {
+                Ok(parsed) => parsed,
+                Err(e) if page == 0 => return Err(e),
+                Err(e) => {
+                    warn!("Stopping at API page {}: {}", url, e);
+                    break;
+                }
+            };
+            if parsed.links.is_empty() {
+                debug!("API page {} has no items, stopping", url);
+                break;
+            }
+            for link in parsed.links {
+                if Fetcher::should_fetch(&link) && self.frontier.add(link, 0).await {
+                    added += 1;
+                }
+            }
+            if paginator.uses_cursor() {
+                match parsed.next_cursor {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+        }
+        
+        info!("Added {} URLs from API {}", added, paginator.template);
+        Ok(added)
+    }
+    
+    /// On first contact with a site, queue the URLs of the sitemap its
+    /// robots.txt lists, up to the pages left in the crawl's budget
+    async fn follow_robots_sitemap(&self, url: &Url) {
+        if !self.sitemaps_followed.lock().await.insert(url.origin().ascii_serialization()) {
+            return;
+        }
+        let sitemap = match self.robots_checker.get_sitemap(url).await {
+            Ok(Some(sitemap)) => sitemap,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Failed to look up the sitemap for {}: {}", url, e);
+                return;
+            }
+        };
+        let used = self.stats.lock().await.pages_crawled + self.frontier.size().await;
+        let remaining = self.config.max_pages.saturating_sub(used);
+        if let Err(e) = self.add_sitemap_limited(&sitemap, remaining).await {
+            warn!("Failed to follow sitemap {}: {}", sitemap, e);
+        }
+    }
+    
+    /// Continue from an earlier crawl: queue the URLs the storage backend
+    /// saw discovered but never crawled, and mark stored pages as seen so
+    /// they aren't fetched again. Returns the number of URLs queued.
+    pub async fn extend_frontier_from_store(&self) -> Result<usize> {
+        for url in self.storage.all_urls().await? {
+            if let Ok(url) = Url::parse(&url) {
+                self.frontier.mark_seen(&url).await;
+            }
+        }
+        
+        let mut added = 0;
+        for (url, depth) in self.storage.uncrawled_discovered().await? {
+            let Ok(url) = Url::parse(&url) else {
+                continue;
+            };
+            if self.should_fetch(&url) && self.frontier.add(url, depth).await {
+                added += 1;
+            }
+        }
+        
+        info!("Added {} discovered URLs from storage", added);
+        Ok(added)
+    }
+    
+    /// Queue the configured seed entries and apply their per-host
+    /// overrides. Fails without queueing anything if an override goes
+    /// beyond the crawl-wide `max_depth`, `max_pages` or delay.
+    pub async fn add_configured_seeds(&self) -> Result<usize> {
+        let limits = SeedLimits::new(
+            &self.config.seeds,
+            self.config.max_depth,
+            self.config.max_pages,
+            self.live.delay_ms(),
+        )?;
+        *self.seed_limits.lock().await = limits;
+        for seed in &self.config.seeds {
+            self.add_seed(Url::parse(&seed.url)?).await?;
+        }
+        Ok(self.config.seeds.len())
+    }
+    
+    /// Check whether a URL may be crawled, including local files when the
+    /// file scheme is allowed
+    fn should_fetch(&self, url: &Url) -> bool {
+        Fetcher::should_fetch(url) || (self.config.allow_file_scheme && Fetcher::should_fetch_file(url))
+    }
+    
+    /// Start crawling.
+    ///
+    /// However the crawl stops, including cancellation and errors, a final
+    /// [`CrawlEvent::Finished`] is sent to subscribers as the last event.
     pub async fn crawl(&self) -> Result<CrawlStats> {
+        let checkpoints = self.config.checkpoint_dir.is_some().then(|| {
+            let crawler = self.clone_for_worker();
+            tokio::spawn(async move {
+                loop {
+                    sleep(crawler.config.checkpoint_interval).await;
+                    if let Err(e) = crawler.write_checkpoint().await {
+                        warn!("Failed to write checkpoint: {}", e);
+                    }
+                }
+            })
+        });
+        let result = self.run_crawl().await;
+        if let Some(checkpoints) = checkpoints {
+            checkpoints.abort();
+        }
+        let stats = {
+            let mut stats = self.stats.lock().await;
+            stats.end_time = Some(Instant::now());
+            stats.finished_at = Some(chrono::Utc::now());
+            stats.clone()
+        };
+        self.finished.store(true, Ordering::SeqCst);
+        if let Err(e) = self.write_checkpoint().await {
+            warn!("Failed to write checkpoint: {}", e);
+        }
+        if let Err(e) = self.robots_checker.save().await {
+            warn!("Failed to save the robots.txt cache: {}", e);
+        }
+        self.emit(CrawlEvent::Finished { stats: stats.clone() });
+        result.map(|_| stats)
+    }
+    
+    /// Check that the storage backend and index can be written, so a crawl
+    /// with unusable outputs fails before fetching anything instead of
+    /// losing its pages to a late write error. Run by [`Crawler::crawl`].
+    pub async fn validate_outputs(&self) -> Result<()> {
+        self.storage.check_writable().await?;
+        if let Some(indexer) = &self.indexer {
+            indexer.check_writable()?;
+        }
+        Ok(())
+    }
+    
+    /// Whether pages are only crawled because the outputs are unusable
+    pub fn is_crawl_only(&self) -> bool {
+        self.crawl_only.load(Ordering::SeqCst)
+    }
+    
+    /// Run the workers until the crawl stops
+    async fn run_crawl(&self) -> Result<()> {
         info!("Starting crawl with max {} pages", self.config.max_pages);
+        if !self.config.respect_robots {
+            warn!("IGNORING robots.txt: disallow rules will not be obeyed on any site. Only do this for sites you own or in testing.");
+        }
+        self.finished.store(false, Ordering::SeqCst);
+        
+        if let Err(e) = self.validate_outputs().await {
+            if !self.config.optional_outputs {
+                return Err(e);
+            }
+            warn!("Outputs unavailable, crawling without storing or indexing pages: {}", e);
+            self.crawl_only.store(true, Ordering::SeqCst);
+        }
         
         // Set start time
         {
             let mut stats = self.stats.lock().await;
             stats.start_time = Some(Instant::now());
+            stats.started_at = Some(chrono::Utc::now());
+            stats.finished_at = None;
         }
         
         // Create concurrent workers
@@ -122,10 +1016,175 @@ impl Crawler {
             let _ = handle.await;
         }
         
-        // Set end time and return stats
-        let mut stats = self.stats.lock().await;
-        stats.end_time = Some(Instant::now());
-        Ok(stats.clone())
+        // Make everything indexed during the crawl searchable
+        if let Some(indexer) = self.indexer.as_ref().filter(|_| !self.is_crawl_only()) {
+            indexer.commit()?;
+        }
+        Ok(())
+    }
+    
+    /// Crawl like [`crawl`](Self::crawl), writing every stored page into
+    /// `store` instead of the crawler's own storage backend. Limits, the
+    /// frontier and statistics are shared with this crawler.
+    pub async fn crawl_into<S: Storage + 'static>(&self, store: S) -> Result<CrawlStats> {
+        let mut crawler = self.clone_for_worker();
+        crawler.storage = Arc::new(store);
+        crawler.crawl().await
+    }
+    
+    /// Crawl like [`crawl`](Self::crawl), yielding each crawled page with its
+    /// URL as it completes. The stream holds one page per worker; while it's
+    /// full, workers wait for the consumer, so a slow consumer slows the
+    /// crawl. Failed pages are left out. If the crawl itself fails, its error
+    /// is the last item. Dropping the stream cancels the crawl.
+    pub fn crawl_stream(&self) -> impl Stream<Item = Result<(Url, ParsedPage)>> {
+        let (tx, rx) = mpsc::channel(self.config.max_concurrent.max(1));
+        let mut crawler = self.clone_for_worker();
+        crawler.page_sink = Some(tx.clone());
+        
+        tokio::spawn(async move {
+            if let Err(e) = crawler.crawl().await {
+                let _ = tx.send(Err(e)).await;
+            }
+        });
+        
+        ReceiverStream::new(rx)
+    }
+    
+    /// Checkpoint the crawl state to `dir` every `interval` while crawling,
+    /// and once more when the crawl stops
+    pub fn with_checkpointing(mut self, dir: impl Into<PathBuf>, interval: Duration) -> Self {
+        self.config.checkpoint_dir = Some(dir.into());
+        self.config.checkpoint_interval = interval;
+        self
+    }
+    
+    /// Snapshot of the frontier, statistics and per-domain timers
+    pub async fn checkpoint(&self) -> Checkpoint {
+        let domain_last_access_ms = self
+            .domain_last_access
+            .lock()
+            .await
+            .iter()
+            .map(|(domain, last)| (domain.clone(), last.elapsed().as_millis() as u64))
+            .collect();
+        Checkpoint {
+            written_at: chrono::Utc::now(),
+            frontier: self.frontier.snapshot().await,
+            stats: self.get_stats().await,
+            domain_last_access_ms,
+        }
+    }
+    
+    /// Write a checkpoint to the checkpoint directory, if one is set
+    pub async fn write_checkpoint(&self) -> Result<()> {
+        let Some(dir) = &self.config.checkpoint_dir else {
+            return Ok(());
+        };
+        let checkpoint = self.checkpoint().await;
+        let dir = dir.clone();
+        tokio::task::spawn_blocking(move || checkpoint.write(&dir))
+            .await
+            .map_err(|e| Error::Unknown(format!("Task error: {}", e)))??;
+        debug!("Wrote checkpoint with {} queued URLs", self.frontier.size().await);
+        Ok(())
+    }
+    
+    /// Load the checkpoint in `dir`, so the next [`crawl`](Self::crawl)
+    /// continues from its frontier and statistics. Seeds added again are
+    /// not recrawled, since they were already seen, but restore the
+    /// same-site scope.
+    pub async fn resume_from_checkpoint(&self, dir: impl AsRef<Path>) -> Result<()> {
+        let checkpoint = Checkpoint::read(dir.as_ref())?;
+        info!(
+            "Resuming from checkpoint written at {} with {} queued URLs",
+            checkpoint.written_at,
+            checkpoint.frontier.tasks.len()
+        );
+        self.frontier.restore(checkpoint.frontier).await;
+        *self.stats.lock().await = CrawlStats {
+            start_time: None,
+            end_time: None,
+            started_at: None,
+            finished_at: None,
+            ..checkpoint.stats
+        };
+        let now = Instant::now();
+        let mut last_access = self.domain_last_access.lock().await;
+        for (domain, elapsed_ms) in checkpoint.domain_last_access_ms {
+            if let Some(last) = now.checked_sub(Duration::from_millis(elapsed_ms)) {
+                last_access.insert(domain, last);
+            }
+        }
+        Ok(())
+    }
+    
+    /// Stream periodic snapshots of the crawl statistics.
+    ///
+    /// A snapshot is emitted every `interval` while the crawl runs. Once the
+    /// crawl finishes a final snapshot is emitted and the stream ends. The
+    /// background task also stops as soon as the stream is dropped.
+    pub fn stats_stream(&self, interval: Duration) -> impl Stream<Item = CrawlStats> {
+        let (tx, rx) = mpsc::channel(1);
+        let stats = self.stats.clone();
+        let finished = self.finished.clone();
+        
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                // Read the flag before the snapshot so the last snapshot
+                // always reflects the completed crawl
+                let done = finished.load(Ordering::SeqCst);
+                let snapshot = stats.lock().await.clone();
+                if tx.send(snapshot).await.is_err() || done {
+                    break;
+                }
+            }
+        });
+        
+        ReceiverStream::new(rx)
+    }
+    
+    /// Pages, failures, bytes and latency per crawled domain
+    pub fn domain_stats(&self) -> HashMap<String, DomainStats> {
+        self.domain_stats.lock().unwrap().snapshot()
+    }
+    
+    /// Receive the events of the crawl from now on. A subscriber that falls
+    /// more than 1024 events behind loses the oldest ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<CrawlEvent> {
+        self.events.subscribe()
+    }
+    
+    /// Send an event to every subscriber
+    fn emit(&self, event: CrawlEvent) {
+        // Failing only means nobody is subscribed
+        let _ = self.events.send(event);
+    }
+    
+    /// Change the hot-reloadable settings of this crawler, running or not.
+    /// See [`ReloadableConfig`] for which settings those are.
+    pub async fn reload(&self, changes: &ReloadableConfig) {
+        self.live.apply(changes);
+        if let Some(rate) = changes.global_rate_limit {
+            let mut limiter = self.global_rate_limiter.lock().await;
+            match limiter.as_mut() {
+                Some(bucket) => bucket.set_rate(rate),
+                None => *limiter = Some(TokenBucket::new(rate)),
+            }
+        }
+        info!("Reloaded crawler settings: {:?}", changes);
+    }
+    
+    /// Ask a running crawl to stop. Workers finish their current page and exit.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+    
+    /// Check whether the crawl has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
     }
     
     /// Clone necessary components for a worker
@@ -133,15 +1192,32 @@ impl Crawler {
         Self {
             config: self.config.clone(),
             frontier: self.frontier.clone(),
-            fetcher: Fetcher::new(
-                self.config.user_agent.clone(),
-                self.config.timeout_seconds,
-                self.config.max_page_size,
-            ),
+            fetcher: self.fetcher.clone(),
             parser: Parser::new(),
+            extractors: self.extractors.clone(),
             robots_checker: self.robots_checker.clone(),
             stats: self.stats.clone(),
+            domain_stats: self.domain_stats.clone(),
             domain_last_access: self.domain_last_access.clone(),
+            finished: self.finished.clone(),
+            crawl_only: self.crawl_only.clone(),
+            cancelled: self.cancelled.clone(),
+            circuit_breaker: self.circuit_breaker.clone(),
+            inflight: self.inflight.clone(),
+            domain_budget: self.domain_budget.clone(),
+            seed_limits: self.seed_limits.clone(),
+            sitemaps_followed: self.sitemaps_followed.clone(),
+            domain_concurrency: self.domain_concurrency.clone(),
+            adaptive_delay: self.adaptive_delay.clone(),
+            ip_resolver: self.ip_resolver.clone(),
+            global_rate_limiter: self.global_rate_limiter.clone(),
+            live: self.live.clone(),
+            storage: self.storage.clone(),
+            indexer: self.indexer.clone(),
+            rng: self.rng.clone(),
+            events: self.events.clone(),
+            retry_policy: self.retry_policy.clone(),
+            page_sink: self.page_sink.clone(),
         }
     }
     
@@ -150,6 +1226,11 @@ impl Crawler {
         info!("Worker {} started", worker_id);
         
         loop {
+            if self.is_cancelled() {
+                info!("Worker {} stopping - crawl cancelled", worker_id);
+                break;
+            }
+            
             // Check if we've reached the page limit
             {
                 let stats = self.stats.lock().await;
@@ -176,7 +1257,43 @@ impl Crawler {
             };
             
             // Check depth limit
-            if task.depth > self.config.max_depth {
+            let max_depth = self.seed_limits.lock().await.max_depth(&task.url);
+            if task.depth > max_depth.unwrap_or(self.config.max_depth) {
+                continue;
+            }
+            
+            // Skip hosts outside the allowed domains
+            let allowed = match task.url.host_str() {
+                Some(host) => self.live.is_host_allowed(host),
+                None => task.url.scheme() == "file",
+            };
+            if !allowed {
+                debug!("Worker {} skipping {} - domain not allowed", worker_id, task.url);
+                self.emit(CrawlEvent::Skipped {
+                    url: task.url,
+                    reason: SkipReason::DomainNotAllowed,
+                });
+                continue;
+            }
+            
+            // Skip domains whose circuit breaker is open
+            if self.is_circuit_open(&task.url).await {
+                info!("Worker {} skipping {} - circuit breaker open", worker_id, task.url);
+                self.stats.lock().await.breaker_skipped += 1;
+                continue;
+            }
+            
+            // Skip domains that have used up their share of the page budget,
+            // and seeds that have used up their pages. A domain's page taken
+            // for a task its seed refuses is given back.
+            let mut within_budget = self.acquire_domain_budget(&task.url).await;
+            if within_budget && !self.seed_limits.lock().await.try_acquire(&task.url) {
+                self.release_domain_budget(&task.url).await;
+                within_budget = false;
+            }
+            if !within_budget {
+                debug!("Worker {} skipping {} - domain budget used up", worker_id, task.url);
+                self.stats.lock().await.pages_over_budget += 1;
                 continue;
             }
             
@@ -186,11 +1303,24 @@ impl Crawler {
                 continue;
             }
             
-            // Process the URL
-            info!("Worker {} crawling: {} (depth: {})", worker_id, task.url, task.depth);
-            if let Err(e) = self.process_url(task).await {
-                error!("Error processing URL: {}", e);
+            // Process the URL inside a span carrying the task's fields
+            let span = info_span!(
+                "crawl_task",
+                url = %task.url,
+                depth = task.depth,
+                worker_id,
+                domain = task.url.host_str().unwrap_or_default(),
+                fetch_ms = field::Empty,
+                body_bytes = field::Empty,
+            );
+            async {
+                info!("Worker {} crawling: {} (depth: {})", worker_id, task.url, task.depth);
+                if let Err(e) = self.process_url(task).await {
+                    error!("Error processing URL: {}", e);
+                }
             }
+            .instrument(span)
+            .await;
         }
         
         info!("Worker {} finished", worker_id);
@@ -198,14 +1328,27 @@ impl Crawler {
     
     /// Apply rate limiting for a domain
     async fn apply_rate_limit(&self, url: &Url) -> Result<()> {
+        // Local files are read without delay
+        if url.scheme() == "file" {
+            return Ok(());
+        }
         let domain = url.domain()
             .ok_or_else(|| Error::InvalidResponse("No domain in URL".to_string()))?;
         
+        let delay_ms = self.seed_limits.lock().await.delay_ms(url).unwrap_or_else(|| self.live.delay_ms());
+        let extra = match &self.adaptive_delay {
+            Some(adaptive) => adaptive.lock().await.delay(domain),
+            None => Duration::ZERO,
+        };
+        let key = self.rate_limit_key(url, domain).await;
         let mut last_access = self.domain_last_access.lock().await;
         
-        if let Some(last_time) = last_access.get(domain) {
+        if let Some(last_time) = last_access.get(&key) {
             let elapsed = last_time.elapsed();
-            let required_delay = Duration::from_millis(self.config.delay_ms);
+            let required_delay = {
+                let mut rng = self.rng.lock().unwrap();
+                jittered_delay(delay_ms, self.live.delay_jitter_percent(), &mut *rng) + extra
+            };
             
             if elapsed < required_delay {
                 let wait_time = required_delay - elapsed;
@@ -213,65 +1356,535 @@ impl Crawler {
             }
         }
         
-        last_access.insert(domain.to_string(), Instant::now());
+        last_access.insert(key, Instant::now());
         Ok(())
     }
     
+    /// Key of the URL's delay: its resolved IP address with
+    /// `rate_limit_by_ip`, otherwise its domain
+    async fn rate_limit_key(&self, url: &Url, domain: &str) -> String {
+        let (Some(resolver), Some(port)) = (&self.ip_resolver, url.port_or_known_default()) else {
+            return domain.to_string();
+        };
+        let resolver = resolver.clone();
+        let netloc = format!("{}:{}", domain, port);
+        let resolved = tokio::task::spawn_blocking(move || resolver.resolve(&netloc)).await;
+        match resolved {
+            Ok(Ok(addrs)) if !addrs.is_empty() => addrs[0].ip().to_string(),
+            _ => domain.to_string(),
+        }
+    }
+    
+    /// Take a page from the budget of the URL's domain, if domains are weighted
+    async fn acquire_domain_budget(&self, url: &Url) -> bool {
+        match (&self.domain_budget, url.host_str()) {
+            (Some(budget), Some(host)) => budget.lock().await.try_acquire(host),
+            _ => true,
+        }
+    }
+    
+    /// Return a page taken with `acquire_domain_budget` for a task that
+    /// wasn't crawled
+    async fn release_domain_budget(&self, url: &Url) {
+        if let (Some(budget), Some(host)) = (&self.domain_budget, url.host_str()) {
+            budget.lock().await.release(host);
+        }
+    }
+    
+    /// Check whether the circuit breaker is open for a URL's domain
+    async fn is_circuit_open(&self, url: &Url) -> bool {
+        match (&self.circuit_breaker, url.host_str()) {
+            (Some(breaker), Some(host)) => breaker.lock().await.is_open(host),
+            _ => false,
+        }
+    }
+    
+    /// Wait until the URL's domain is below its slow-start concurrency limit
+    /// and claim a slot
+    async fn acquire_domain_slot(&self, url: &Url) {
+        let (Some(concurrency), Some(host)) = (&self.domain_concurrency, url.host_str()) else {
+            return;
+        };
+        while !concurrency.lock().await.try_acquire(host) {
+            sleep(Duration::from_millis(10)).await;
+        }
+    }
+    
+    /// Wait for a token from the crawl-wide rate limiter, if one is set
+    async fn acquire_global_token(&self) {
+        loop {
+            let wait = match self.global_rate_limiter.lock().await.as_mut().map(TokenBucket::try_take) {
+                None | Some(Ok(())) => return,
+                Some(Err(wait)) => wait,
+            };
+            sleep(wait).await;
+        }
+    }
+    
+    /// Give back a domain slot, growing or shrinking the limit by the outcome
+    async fn release_domain_slot(&self, url: &Url, success: bool) {
+        if let (Some(concurrency), Some(host)) = (&self.domain_concurrency, url.host_str()) {
+            concurrency.lock().await.release(host, success);
+        }
+    }
+    
+    /// Record a fetch outcome with the circuit breaker
+    async fn record_outcome(&self, url: &Url, success: bool) {
+        if let (Some(breaker), Some(host)) = (&self.circuit_breaker, url.host_str()) {
+            if breaker.lock().await.record(host, success) {
+                self.stats.lock().await.breaker_trips += 1;
+            }
+        }
+    }
+    
+    /// Wait for a permit under the in-flight request cap, if there is one
+    async fn acquire_inflight_permit(&self) -> Result<Option<SemaphorePermit<'_>>> {
+        match &self.inflight {
+            Some(semaphore) => semaphore
+                .acquire()
+                .await
+                .map(Some)
+                .map_err(|e| Error::Unknown(format!("In-flight semaphore closed: {}", e))),
+            None => Ok(None),
+        }
+    }
+    
+    /// Whether the store has a copy of the URL crawled at or after
+    /// `crawl_since`. Lookup failures count as stale, so the URL is fetched.
+    async fn is_fresh(&self, url: &Url) -> bool {
+        let Some(cutoff) = self.config.crawl_since else {
+            return false;
+        };
+        match self.storage.get_page(url.as_str()).await {
+            Ok(page) => page.is_some_and(|page| page.crawled_at >= cutoff),
+            Err(e) => {
+                warn!("Failed to look up {} in storage: {}", url, e);
+                false
+            }
+        }
+    }
+    
     /// Process a single URL
     async fn process_url(&self, task: CrawlTask) -> Result<()> {
-        // Check robots.txt first
-        if !self.robots_checker.is_allowed(&task.url).await? {
-            warn!("Skipping {} - blocked by robots.txt", task.url);
+        if self.is_fresh(&task.url).await {
+            debug!("Skipping {} - stored copy is newer than the cutoff", task.url);
+            self.stats.lock().await.fresh_skipped += 1;
+            self.emit(CrawlEvent::Skipped {
+                url: task.url,
+                reason: SkipReason::Fresh,
+            });
             return Ok(());
         }
         
-        // Check if we should also apply crawl delay from robots.txt
-        if let Some(delay) = self.robots_checker.get_crawl_delay(&task.url).await? {
-            let delay_ms = delay.as_millis() as u64;
-            if delay_ms > self.config.delay_ms {
-                // Use the longer delay specified in robots.txt
-                sleep(Duration::from_millis(delay_ms - self.config.delay_ms)).await;
+        // Check robots.txt first. Fetching it counts as an in-flight request.
+        if self.config.respect_robots {
+            let permit = self.acquire_inflight_permit().await?;
+            let allowed = self.robots_checker.is_allowed(&task.url).await;
+            drop(permit);
+            if !allowed? {
+                warn!("Skipping {} - blocked by robots.txt", task.url);
+                self.stats.lock().await.robots_blocked += 1;
+                self.emit(CrawlEvent::Skipped {
+                    url: task.url,
+                    reason: SkipReason::RobotsDisallowed,
+                });
+                return Ok(());
             }
         }
         
-        // Fetch the page
-        let response = match self.fetcher.fetch(&task.url) {
-            Ok(resp) => resp,
-            Err(e) => {
-                self.update_stats_failed().await;
-                return Err(e);
-            }
-        };
+        if self.config.follow_sitemaps {
+            self.follow_robots_sitemap(&task.url).await;
+        }
         
-        // Parse the page
-        let parsed = self.parser.parse(&response.body, &response.url)?;
+        // Check if we should also apply crawl delay from robots.txt
+        if self.config.honor_robots_crawl_delay {
+            if let Some(delay) = self.robots_checker.get_crawl_delay(&task.url).await? {
+                let delay_ms = delay.as_millis() as u64;
+                let configured_ms = self.live.delay_ms();
+                if delay_ms > configured_ms {
+                    // Use the longer delay specified in robots.txt
+                    sleep(Duration::from_millis(delay_ms - configured_ms)).await;
+                }
+            }
+        }
         
-        // Extract and filter links
-        let filtered_links = self.parser.filter_links(parsed.links);
-        
-        // Add new links to frontier
-        let new_depth = task.depth + 1;
-        let new_links: Vec<(Url, usize)> = filtered_links
-            .into_iter()
-            .map(|url| (url, new_depth))
+        // Fetch the page, holding a domain slot and an in-flight permit for
+        // the duration of the request
+        self.acquire_domain_slot(&task.url).await;
+        let permit = self.acquire_inflight_permit().await?;
+        self.acquire_global_token().await;
+        let fetch_start = Instant::now();
+        let content_types: Vec<&str> = HTML_CONTENT_TYPES
+            .iter()
+            .copied()
+            .chain(self.extractors.mime_types())
             .collect();
+        let referer = task
+            .referer
+            .as_ref()
+            .filter(|_| self.config.send_referer)
+            .and_then(|referer| self.config.referrer_policy.referer(referer, &task.url));
+        let result = self
+            .fetcher
+            .fetch_with_referer(&task.url, &content_types, referer.as_ref())
+            .await;
+        drop(permit);
+        if let Err(Error::RedirectOutOfScope(target)) = &result {
+            info!("Skipping {} - redirects out of scope to {}", task.url, target);
+            self.release_domain_slot(&task.url, true).await;
+            self.emit(CrawlEvent::Skipped {
+                url: task.url,
+                reason: SkipReason::OutOfScope,
+            });
+            return Ok(());
+        }
+        if let Err(Error::Attachment(_)) = &result {
+            info!("Skipping {} - served as an attachment", task.url);
+            self.release_domain_slot(&task.url, true).await;
+            self.stats.lock().await.attachments_skipped += 1;
+            self.emit(CrawlEvent::Skipped {
+                url: task.url,
+                reason: SkipReason::Attachment,
+            });
+            return Ok(());
+        }
+        if let Err(Error::BinaryContent(_)) = &result {
+            info!("Skipping {} - body looks binary", task.url);
+            self.release_domain_slot(&task.url, true).await;
+            self.stats.lock().await.binary_skipped += 1;
+            self.emit(CrawlEvent::Skipped {
+                url: task.url,
+                reason: SkipReason::Binary,
+            });
+            return Ok(());
+        }
+        if let Err(Error::MimeMismatch(_, sniffed)) = &result {
+            info!("Skipping {} - body sniffs as {}", task.url, sniffed);
+            self.release_domain_slot(&task.url, true).await;
+            self.stats.lock().await.mime_mismatch_skipped += 1;
+            self.emit(CrawlEvent::Skipped {
+                url: task.url,
+                reason: SkipReason::MimeMismatch,
+            });
+            return Ok(());
+        }
+        let status = match &result {
+            Ok(response) => Some(response.status_code),
+            Err(Error::HttpStatus(status, _)) => Some(*status),
+            Err(_) => None,
+        };
+        if let Some(status) = status {
+            *self.stats.lock().await.status_codes.entry(status).or_default() += 1;
+            if let (Some(adaptive), Some(host)) = (&self.adaptive_delay, task.url.domain()) {
+                adaptive.lock().await.record(host, status);
+            }
+        }
+        let success = result
+            .as_ref()
+            .is_ok_and(|response| (200..300).contains(&response.status_code) || response.redirect_target().is_some());
+        self.release_domain_slot(&task.url, success).await;
+        let fetch_time = fetch_start.elapsed();
+        Span::current().record("fetch_ms", fetch_time.as_millis() as u64);
+        if let Some(host) = task.url.host_str() {
+            let bytes = result.as_ref().map_or(0, |response| response.body_bytes);
+            self.domain_stats.lock().unwrap().record(host, success, bytes, fetch_time);
+        }
         
-        let links_count = new_links.len();
-        self.frontier.add_many(new_links).await;
+        let response = match result {
+            Ok(resp) => resp,
+            Err(e) => {
+                self.record_outcome(&task.url, false).await;
+                if task.retry_count < self.config.max_retries && self.retry_policy.is_retryable(&e, status) {
+                    info!("Retrying {} after: {}", task.url, e);
+                    if let Some(base) = self.config.retry_backoff {
+                        let delay = {
+                            let mut rng = self.rng.lock().unwrap();
+                            retry_delay(
+                                base,
+                                self.config.retry_backoff_max,
+                                task.retry_count,
+                                self.config.retry_jitter,
+                                &mut *rng,
+                            )
+                        };
+                        sleep(delay).await;
+                    }
+                    let url = task.url.clone();
+                    if self.frontier.retry(task).await {
+                        self.stats.lock().await.pages_retried += 1;
+                        return Ok(());
+                    }
+                    self.update_stats_failed(&url, &e).await;
+                    return Err(e);
+                }
+                self.update_stats_failed(&task.url, &e).await;
+                if let Error::HttpStatus(status, _) = &e {
+                    self.remove_if_gone(&task.url, *status);
+                }
+                return Err(e);
+            }
+        };
+        self.record_outcome(&task.url, success).await;
+        Span::current().record("body_bytes", response.body_bytes);
+        
+        if self.config.audit_redirects && (300..400).contains(&response.status_code) {
+            return self.record_redirect(&task, &response).await;
+        }
+        
+        // An error page listed in `parse_status_codes`: follow its links,
+        // but count it as a failure
+        if !success {
+            let error = Error::HttpStatus(response.status_code, task.url.to_string());
+            self.update_stats_failed(&task.url, &error).await;
+            self.remove_if_gone(&task.url, response.status_code);
+            let parsed = self.extract_page(&response).await?;
+            let links_count = self.enqueue_links(&task, &parsed).await;
+            info!("Followed {} links on {} error page {}", links_count, response.status_code, task.url);
+            return Err(error);
+        }
+        
+        // Pages outside the content-length range are still used for link
+        // discovery, but are not indexed or stored
+        let in_range = self.config.content_length_in_range(response.body.len());
+        if !in_range {
+            info!(
+                "Not indexing {} - body length {} outside content-length range",
+                task.url,
+                response.body.len()
+            );
+            self.update_stats_out_of_range().await;
+        }
+        
+        // Parse the page with the extractor for its content type
+        let parsed = match self.extract_page(&response).await {
+            Err(Error::ParseTimeout(_)) => {
+                warn!("Skipping {} - parsing took longer than {:?}", task.url, self.config.parse_timeout);
+                self.emit(CrawlEvent::Skipped {
+                    url: task.url,
+                    reason: SkipReason::ParseTimeout,
+                });
+                return Ok(());
+            }
+            parsed => parsed?,
+        };
+        
+        let links_count = self.enqueue_links(&task, &parsed).await;
+        
+        // Persist the page
+        if in_range && !self.is_crawl_only() {
+            let page = StoredPage {
+                url: task.url.to_string(),
+                status_code: response.status_code,
+                content_type: response.content_type.clone(),
+                title: parsed.title.clone(),
+                text_content: parsed.text_content.clone(),
+                depth: task.depth,
+                crawled_at: chrono::Utc::now(),
+                headers: Some(StoredPage::normalize_headers(&response.headers)),
+                open_graph: parsed.open_graph.clone().into_iter().collect(),
+                canonical_url: parsed.canonical_url.as_ref().map(Url::to_string),
+                tags: task.tags.clone().into_iter().collect(),
+            };
+            if let Some(indexer) = &self.indexer {
+                let mut document = IndexDocument::from(&page);
+                document.language = parsed.language.clone();
+                document.published_at = parsed.published_at.or_else(|| last_modified(&response));
+                if let Some(article) = &parsed.article_text {
+                    // Boilerplate-free text makes for better matches and snippets
+                    document.body = article.clone();
+                }
+                // Image captions carry meaning the text may not
+                for alt in &parsed.image_alts {
+                    document.body.push('\n');
+                    document.body.push_str(alt);
+                }
+                // Pages sharing a canonical URL replace each other's document
+                if page.canonical_url.is_some() {
+                    if let Err(e) = indexer.delete_by_url(&document.url) {
+                        warn!("Failed to replace {} in the index: {}", document.url, e);
+                    }
+                }
+                if let Err(e) = indexer.add_document(&document) {
+                    warn!("Failed to index {}: {}", task.url, e);
+                }
+            }
+            if let Err(e) = self.storage.insert_page(page).await {
+                warn!("Failed to store {}: {}", task.url, e);
+            }
+        }
         
         // Update statistics
         self.update_stats_success(links_count).await;
         
         // Log progress
-        if let Some(title) = parsed.title {
+        if let Some(title) = &parsed.title {
             info!("Crawled: {} - {}", task.url, title);
         } else {
             info!("Crawled: {}", task.url);
         }
         
+        if let Some(sink) = &self.page_sink {
+            if sink.send(Ok((task.url, parsed))).await.is_err() {
+                // The stream was dropped, so nobody wants further pages
+                self.cancel();
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Store an audited 3xx response and queue its target like a link on
+    /// the page, so the target is checked in turn
+    async fn record_redirect(&self, task: &CrawlTask, response: &FetchResponse) -> Result<()> {
+        let target = response.redirect_target();
+        match &target {
+            Some(target) => info!("Redirect: {} -> {} ({})", task.url, target, response.status_code),
+            None => warn!("Redirect without a valid Location: {} ({})", task.url, response.status_code),
+        }
+        self.stats.lock().await.redirects_audited += 1;
+        self.emit(CrawlEvent::Redirected {
+            url: task.url.clone(),
+            status: response.status_code,
+            target: target.clone(),
+        });
+        
+        if !self.is_crawl_only() {
+            let page = StoredPage {
+                url: task.url.to_string(),
+                status_code: response.status_code,
+                content_type: response.content_type.clone(),
+                title: None,
+                text_content: String::new(),
+                depth: task.depth,
+                crawled_at: chrono::Utc::now(),
+                headers: Some(StoredPage::normalize_headers(&response.headers)),
+                open_graph: BTreeMap::new(),
+                canonical_url: None,
+                tags: task.tags.clone().into_iter().collect(),
+            };
+            if let Err(e) = self.storage.insert_page(page).await {
+                warn!("Failed to store {}: {}", task.url, e);
+            }
+        }
+        
+        if let Some(target) = target {
+            let parsed = ParsedPage {
+                links: vec![Link { url: target, rel: Vec::new() }],
+                ..ParsedPage::default()
+            };
+            self.enqueue_links(task, &parsed).await;
+        }
         Ok(())
     }
     
+    /// Parse a response with the extractor for its content type, cutting
+    /// the body to `max_parse_size` first. With a `parse_timeout` the parse
+    /// runs on a blocking thread, which is abandoned if it takes too long.
+    async fn extract_page(&self, response: &FetchResponse) -> Result<ParsedPage> {
+        let mut body = response.body.as_str();
+        if let Some(max) = self.config.max_parse_size {
+            body = truncate_html(body, max);
+            if body.len() < response.body.len() {
+                debug!("Parsing only the first {} bytes of {}", body.len(), response.url);
+                self.stats.lock().await.pages_parse_truncated += 1;
+            }
+        }
+        let Some(timeout) = self.config.parse_timeout else {
+            return self
+                .extractors
+                .extract(response.content_type.as_deref(), body, &response.url);
+        };
+        
+        let extractors = self.extractors.clone();
+        let (content_type, body, url) = (response.content_type.clone(), response.body.prefix(body.len()), response.url.clone());
+        let parse = tokio::task::spawn_blocking(move || extractors.extract(content_type.as_deref(), &body, &url));
+        match tokio::time::timeout(timeout, parse).await {
+            Ok(parsed) => parsed.map_err(|e| Error::Unknown(format!("Task error: {}", e)))?,
+            Err(_) => {
+                self.stats.lock().await.parse_timeouts += 1;
+                Err(Error::ParseTimeout(response.url.to_string()))
+            }
+        }
+    }
+    
+    /// Queue the links of a parsed page one level deeper, returning how many
+    /// were queued
+    async fn enqueue_links(&self, task: &CrawlTask, parsed: &ParsedPage) -> usize {
+        // Extract and filter links, along with language variants if wanted
+        let mut links: Vec<Url> = parsed
+            .links
+            .iter()
+            .filter(|link| !self.config.skip_link_rels.iter().any(|rel| link.has_rel(rel)))
+            .map(|link| link.url.clone())
+            .collect();
+        if self.config.follow_alternates {
+            links.extend(parsed.alternates.iter().map(|(_, url)| url.clone()));
+        }
+        let filtered_links = if self.config.allow_file_scheme {
+            self.parser.filter_links_with_schemes(links, &["http", "https", "file"])
+        } else {
+            self.parser.filter_links(links)
+        };
+        let new_depth = task.depth + 1;
+        
+        // Follow the next page of a paginated set ahead of other links
+        if let Some(next_url) = &parsed.next_url {
+            for url in self.parser.filter_links(vec![next_url.clone()]) {
+                self.frontier.push(task.link(url, NEXT_PAGE_PRIORITY)).await;
+            }
+        }
+        
+        // Add new links to frontier
+        let new_links: Vec<(Url, usize)> = filtered_links
+            .into_iter()
+            .map(|url| (url, new_depth))
+            .collect();
+        
+        let links_count = new_links.len();
+        let discovered: Vec<(String, usize)> = new_links
+            .iter()
+            .map(|(url, depth)| (url.to_string(), *depth))
+            .collect();
+        for (url, _) in new_links {
+            if !self.frontier.admits_host(&url) {
+                debug!("Dropping {} - crawl reached its host limit", url);
+                self.stats.lock().await.new_hosts_dropped += 1;
+                self.emit(CrawlEvent::Skipped {
+                    url,
+                    reason: SkipReason::HostLimit,
+                });
+                continue;
+            }
+            if !self.frontier.admits_query_variant(&url) {
+                debug!("Dropping {} - too many query variants of its path", url);
+                self.stats.lock().await.query_variants_dropped += 1;
+                self.emit(CrawlEvent::Skipped {
+                    url,
+                    reason: SkipReason::QueryVariantLimit,
+                });
+                continue;
+            }
+            self.frontier.push(task.link(url, DEFAULT_PRIORITY)).await;
+        }
+        if self.is_crawl_only() {
+            return links_count;
+        }
+        if let Err(e) = self.storage.insert_discovered(&discovered).await {
+            warn!("Failed to record links found on {}: {}", task.url, e);
+        }
+        links_count
+    }
+    
+    /// Drop a page that answered 404 or 410 from the index, removing any
+    /// copy indexed by an earlier crawl
+    fn remove_if_gone(&self, url: &Url, status: u16) {
+        if let (404 | 410, Some(indexer)) = (status, &self.indexer) {
+            if let Err(e) = indexer.delete_by_url(url.as_str()) {
+                warn!("Failed to remove {} from the index: {}", url, e);
+            }
+        }
+    }
+    
     /// Update statistics for successful crawl
     async fn update_stats_success(&self, links_found: usize) {
         let mut stats = self.stats.lock().await;
@@ -279,27 +1892,54 @@ impl Crawler {
         stats.total_links_found += links_found;
     }
     
-    /// Update statistics for failed crawl
-    async fn update_stats_failed(&self) {
+    /// Update statistics for a page outside the content-length range
+    async fn update_stats_out_of_range(&self) {
+        let mut stats = self.stats.lock().await;
+        stats.pages_out_of_range += 1;
+    }
+    
+    /// Update statistics for failed crawl, keeping `url` and its error while
+    /// `max_failed_urls` allows
+    async fn update_stats_failed(&self, url: &Url, error: &Error) {
         let mut stats = self.stats.lock().await;
         stats.pages_failed += 1;
+        if stats.failed_urls.len() < self.config.max_failed_urls {
+            stats.failed_urls.push((url.clone(), error.to_string()));
+        }
     }
     
     /// Get current statistics
     pub async fn get_stats(&self) -> CrawlStats {
         self.stats.lock().await.clone()
     }
+    
+    /// Current statistics together with the frontier's queue and seen counts
+    pub async fn report(&self) -> CrawlReport {
+        CrawlReport::new(self.get_stats().await, self.frontier.stats().await)
+    }
 }
 
 /// Builder for creating a crawler with custom configuration
 pub struct CrawlerBuilder {
     config: CrawlerConfig,
+    storage: Arc<dyn Storage>,
+    indexer: Option<Indexer>,
+    extractors: ExtractorRegistry,
+    fetcher: Option<Arc<dyn HttpFetcher>>,
+    retry_policy: Option<Arc<dyn RetryPolicy>>,
+    frontier: Option<Arc<dyn Frontier>>,
 }
 
 impl CrawlerBuilder {
     pub fn new() -> Self {
         Self {
             config: CrawlerConfig::default(),
+            storage: Arc::new(NoopStore),
+            indexer: None,
+            extractors: ExtractorRegistry::new(),
+            fetcher: None,
+            retry_policy: None,
+            frontier: None,
         }
     }
     
@@ -313,6 +1953,13 @@ impl CrawlerBuilder {
         self
     }
     
+    /// Apply a politeness preset. Setters called after this override the
+    /// values it sets.
+    pub fn politeness(mut self, profile: PolitenessProfile) -> Self {
+        profile.apply(&mut self.config);
+        self
+    }
+    
     pub fn max_concurrent(mut self, concurrent: usize) -> Self {
         self.config.max_concurrent = concurrent;
         self
@@ -323,18 +1970,2348 @@ impl CrawlerBuilder {
         self
     }
     
+    /// Cut pages longer than `max` bytes before parsing them
+    pub fn max_parse_size(mut self, max: usize) -> Self {
+        self.config.max_parse_size = Some(max);
+        self
+    }
+    
+    /// Skip pages whose parse takes longer than `timeout`
+    pub fn parse_timeout(mut self, timeout: Duration) -> Self {
+        self.config.parse_timeout = Some(timeout);
+        self
+    }
+    
+    /// Ignore text and links nested more than `depth` elements deep
+    pub fn max_dom_depth(mut self, depth: usize) -> Self {
+        self.config.max_dom_depth = Some(depth);
+        self
+    }
+    
+    /// Also follow URLs found in these attributes (`data-href`, `onclick`)
+    /// of any element; see [`Parser::link_attributes`]
+    pub fn link_attributes(mut self, attributes: Vec<String>) -> Self {
+        self.config.link_attributes = attributes;
+        self
+    }
+    
     pub fn user_agent(mut self, agent: String) -> Self {
         self.config.user_agent = agent;
         self
     }
     
-    pub fn build(self) -> Crawler {
-        Crawler::new(self.config)
+    /// Rotate through these agents per request. robots.txt is still matched
+    /// against the primary `user_agent` only, so set that to the agent whose
+    /// rules the crawl should obey.
+    pub fn user_agents(mut self, agents: Vec<String>, rotation: UserAgentRotation) -> Self {
+        self.config.user_agents = agents;
+        self.config.user_agent_rotation = rotation;
+        self
     }
-}
-
-impl Default for CrawlerBuilder {
-    fn default() -> Self {
-        Self::new()
+    
+    pub fn min_content_length(mut self, min: usize) -> Self {
+        self.config.min_content_length = Some(min);
+        self
+    }
+    
+    pub fn max_content_length(mut self, max: usize) -> Self {
+        self.config.max_content_length = Some(max);
+        self
+    }
+    
+    pub fn resolve_override(mut self, host: &str, addr: SocketAddr) -> Self {
+        self.config.resolve_overrides.insert(host.to_lowercase(), addr);
+        self
+    }
+    
+    pub fn strategy(mut self, strategy: CrawlStrategy) -> Self {
+        self.config.strategy = strategy;
+        self
+    }
+    
+    pub fn delay_jitter_percent(mut self, percent: f64) -> Self {
+        self.config.delay_jitter_percent = percent;
+        self
+    }
+    
+    pub fn jitter_seed(mut self, seed: u64) -> Self {
+        self.config.jitter_seed = Some(seed);
+        self
+    }
+    
+    pub fn max_inflight_requests(mut self, max: usize) -> Self {
+        self.config.max_inflight_requests = Some(max);
+        self
+    }
+    
+    pub fn circuit_breaker(mut self, error_rate_threshold: f64, error_window: Duration, cooldown: Duration) -> Self {
+        self.config.error_rate_threshold = Some(error_rate_threshold);
+        self.config.error_window = error_window;
+        self.config.cooldown = cooldown;
+        self
+    }
+    
+    pub fn error_min_samples(mut self, samples: usize) -> Self {
+        self.config.error_min_samples = samples;
+        self
+    }
+    
+    /// Split the page budget between domains by relative weight
+    pub fn domain_weights(mut self, weights: HashMap<String, f32>) -> Self {
+        self.config.domain_weights = weights;
+        self
+    }
+    
+    /// Reuse fetched responses for repeat requests of a URL within `ttl`,
+    /// keeping at most `entries` responses
+    pub fn response_cache(mut self, ttl: Duration, entries: usize) -> Self {
+        self.config.response_cache_ttl = Some(ttl);
+        self.config.response_cache_entries = entries;
+        self
+    }
+    
+    /// Ramp up each domain's concurrent requests gradually, backing off on errors
+    pub fn slow_start(mut self, slow_start: SlowStartConfig) -> Self {
+        self.config.slow_start = Some(slow_start);
+        self
+    }
+    
+    /// Lengthen the delay of domains that answer 429 or 503, shortening it
+    /// again as they recover
+    pub fn adaptive_delay(mut self, adaptive_delay: AdaptiveDelayConfig) -> Self {
+        self.config.adaptive_delay = Some(adaptive_delay);
+        self
+    }
+    
+    /// Space requests per resolved IP address rather than per host name
+    pub fn rate_limit_by_ip(mut self, by_ip: bool) -> Self {
+        self.config.rate_limit_by_ip = by_ip;
+        self
+    }
+    
+    /// Queue the `hreflang` language variants of crawled pages
+    pub fn follow_alternates(mut self, follow: bool) -> Self {
+        self.config.follow_alternates = follow;
+        self
+    }
+    
+    /// Don't queue links with any of these `rel` tokens; defaults to
+    /// `nofollow`, an empty list follows every link
+    pub fn skip_link_rels(mut self, rels: Vec<String>) -> Self {
+        self.config.skip_link_rels = rels;
+        self
+    }
+    
+    /// Only follow links to the seeds' sites, as decided by `policy`
+    pub fn same_site(mut self, policy: SameSitePolicy) -> Self {
+        self.config.same_site = Some(policy);
+        self
+    }
+    
+    /// Only crawl the registrable domains of the seed URLs
+    pub fn auto_restrict_to_seeds(mut self, restrict: bool) -> Self {
+        self.config.auto_restrict_to_seeds = restrict;
+        self
+    }
+    
+    /// Still parse pages answering with these non-2xx statuses for links
+    pub fn parse_status_codes(mut self, codes: Vec<u16>) -> Self {
+        self.config.parse_status_codes = codes;
+        self
+    }
+    
+    /// Cap requests per second across the whole crawl
+    pub fn global_rate_limit(mut self, requests_per_second: f64) -> Self {
+        self.config.global_rate_limit = Some(requests_per_second);
+        self
+    }
+    
+    /// Queue at most `max` URLs over the whole crawl
+    pub fn max_urls_discovered(mut self, max: usize) -> Self {
+        self.config.max_urls_discovered = Some(max);
+        self
+    }
+    
+    /// Visit at most `max` distinct hosts
+    pub fn max_unique_hosts(mut self, max: usize) -> Self {
+        self.config.max_unique_hosts = Some(max);
+        self
+    }
+    
+    /// Queue at most `max` URLs per path that differ only in their query
+    /// string, guarding against endless `?page=N` style links
+    pub fn max_query_variants(mut self, max: usize) -> Self {
+        self.config.max_query_variants = Some(max);
+        self
+    }
+    
+    /// Treat `/path` and `/path/` as one page, normalized as `mode` says
+    pub fn trailing_slash(mut self, mode: TrailingSlash) -> Self {
+        self.config.trailing_slash = mode;
+        self
+    }
+    
+    /// Remember seen URLs across crawls in a sled database at `path`
+    pub fn dedup_db(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.dedup_db_path = Some(path.into());
+        self
+    }
+    
+    /// Keep fetched robots.txt rules in a JSON file at `path` across crawls
+    pub fn robots_cache(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.robots_cache_path = Some(path.into());
+        self
+    }
+    
+    /// Follow at most `max` redirects for one page
+    pub fn max_redirects(mut self, max: u32) -> Self {
+        self.config.max_redirects = max;
+        self
+    }
+    
+    /// Record redirects and queue their targets instead of following them
+    pub fn audit_redirects(mut self, audit: bool) -> Self {
+        self.config.audit_redirects = audit;
+        self
+    }
+    
+    /// Only crawl these domains and their subdomains
+    pub fn allowed_domains(mut self, domains: Vec<String>) -> Self {
+        self.config.allowed_domains = domains;
+        self
+    }
+    
+    /// Send the page a link was found on as the `Referer` when fetching it,
+    /// to the requests allowed by `policy`
+    pub fn send_referer(mut self, policy: ReferrerPolicy) -> Self {
+        self.config.send_referer = true;
+        self.config.referrer_policy = policy;
+        self
+    }
+    
+    /// Cap the domains given their own per-domain stats entry
+    pub fn max_tracked_domains(mut self, max: usize) -> Self {
+        self.config.max_tracked_domains = max;
+        self
     }
-}
\ No newline at end of file
+    
+    pub fn storage(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.storage = storage;
+        self
+    }
+    
+    /// Extract responses of `mime_type` with a custom extractor instead of
+    /// the HTML parser. Registering a type also makes it fetchable.
+    pub fn extractor(mut self, mime_type: &str, extractor: Arc<dyn ContentExtractor>) -> Self {
+        self.extractors.register(mime_type, extractor);
+        self
+    }
+    
+    /// Fetch pages and robots.txt through `fetcher` instead of over HTTP
+    pub fn fetcher(mut self, fetcher: Arc<dyn HttpFetcher>) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+    
+    /// Use `policy` instead of robots.txt on `domain`
+    pub fn robots_override(mut self, domain: &str, policy: RobotsOverride) -> Self {
+        self.config.robots_overrides.insert(domain.to_lowercase(), policy);
+        self
+    }
+    
+    /// Ignore robots.txt disallow rules on every site. Only for sites you
+    /// own or in testing.
+    pub fn respect_robots(mut self, respect: bool) -> Self {
+        self.config.respect_robots = respect;
+        self
+    }
+    
+    /// Whether to wait out robots.txt crawl delays
+    pub fn honor_robots_crawl_delay(mut self, honor: bool) -> Self {
+        self.config.honor_robots_crawl_delay = honor;
+        self
+    }
+    
+    /// Run at most `max` DNS lookups at once
+    pub fn max_concurrent_dns(mut self, max: usize) -> Self {
+        self.config.max_concurrent_dns = Some(max);
+        self
+    }
+    
+    /// Reuse resolved host addresses for `ttl`
+    pub fn dns_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.config.dns_cache_ttl = Some(ttl);
+        self
+    }
+    
+    /// Resolve host names with `resolver`, ignoring the other DNS settings
+    pub fn dns_resolver(mut self, resolver: DnsResolver) -> Self {
+        self.config.dns_resolver = Some(resolver);
+        self
+    }
+    
+    /// Skip bodies with more than `threshold` control bytes in their first
+    /// KB, or read every body as text with `None`
+    pub fn binary_threshold(mut self, threshold: Option<f64>) -> Self {
+        self.config.binary_threshold = threshold;
+        self
+    }
+    
+    /// Skip responses whose magic bytes contradict their content type
+    pub fn sniff_content(mut self, sniff: bool) -> Self {
+        self.config.sniff_content = sniff;
+        self
+    }
+    
+    /// Only fetch URLs that aren't stored or were last crawled before `cutoff`
+    pub fn crawl_since(mut self, cutoff: chrono::DateTime<chrono::Utc>) -> Self {
+        self.config.crawl_since = Some(cutoff);
+        self
+    }
+    
+    /// Crawl without storing or indexing when the outputs can't be written
+    pub fn optional_outputs(mut self, optional: bool) -> Self {
+        self.config.optional_outputs = optional;
+        self
+    }
+    
+    /// Whether to skip `Content-Disposition: attachment` responses (the default)
+    pub fn skip_attachments(mut self, skip: bool) -> Self {
+        self.config.skip_attachments = skip;
+        self
+    }
+    
+    /// Record every fetch and its outcome to a cassette file at `path`
+    pub fn record_to(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.record_path = Some(path.into());
+        self
+    }
+    
+    /// Flush output files every `records` records
+    pub fn flush_every(mut self, records: usize) -> Self {
+        self.config.output_flush.flush_every = records;
+        self
+    }
+    
+    /// Fsync output files on every flush, trading throughput for surviving
+    /// a crash with all flushed records
+    pub fn fsync(mut self, fsync: bool) -> Self {
+        self.config.output_flush.fsync = fsync;
+        self
+    }
+    
+    /// Answer fetches from the cassette file at `path` instead of the
+    /// network, reproducing a recorded crawl. The cassette is loaded now,
+    /// so a missing or malformed one fails here rather than every fetch.
+    pub fn replay_from(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        self.fetcher = Some(Arc::new(ReplayFetcher::from_file(path)?));
+        Ok(self)
+    }
+    
+    /// Queue the URLs of sitemaps listed in robots.txt
+    pub fn follow_sitemaps(mut self, follow: bool) -> Self {
+        self.config.follow_sitemaps = follow;
+        self
+    }
+    
+    /// Seed entries with per-host overrides, queued by
+    /// [`Crawler::add_configured_seeds`]
+    pub fn seeds(mut self, seeds: Vec<SeedConfig>) -> Self {
+        self.config.seeds = seeds;
+        self
+    }
+    
+    /// Queue a failed fetch again, up to `max` times, when the retry policy
+    /// deems the failure retryable
+    pub fn max_retries(mut self, max: u32) -> Self {
+        self.config.max_retries = max;
+        self
+    }
+    
+    /// Back off exponentially between retries, starting at `base` and
+    /// waiting at most `max`
+    pub fn retry_backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.config.retry_backoff = Some(base);
+        self.config.retry_backoff_max = max;
+        self
+    }
+    
+    /// Whether to wait a random part of each retry backoff (the default)
+    pub fn retry_jitter(mut self, jitter: bool) -> Self {
+        self.config.retry_jitter = jitter;
+        self
+    }
+    
+    /// Keep at most `max` failed URLs with their errors in the final stats
+    pub fn max_failed_urls(mut self, max: usize) -> Self {
+        self.config.max_failed_urls = max;
+        self
+    }
+    
+    /// Take URLs from `frontier` instead of an in-memory queue, for example
+    /// to share one queue and seen set between crawler processes
+    pub fn frontier(mut self, frontier: Arc<dyn Frontier>) -> Self {
+        self.frontier = Some(frontier);
+        self
+    }
+    
+    /// Decide which failures are retried with `policy` instead of
+    /// [`is_retryable`](crate::crawler::is_retryable)
+    pub fn retry_policy(mut self, policy: impl RetryPolicy + 'static) -> Self {
+        self.retry_policy = Some(Arc::new(policy));
+        self
+    }
+    
+    /// Crawl `file://` URLs inside `root`, refusing paths that resolve
+    /// outside it
+    pub fn allow_file_scheme(mut self, root: impl Into<PathBuf>) -> Self {
+        self.config.allow_file_scheme = true;
+        self.config.file_root = Some(root.into());
+        self
+    }
+    
+    /// Read response bodies into buffers reused across fetches, cutting
+    /// allocations on large crawls
+    pub fn pool_body_buffers(mut self, pool: bool) -> Self {
+        self.config.pool_body_buffers = pool;
+        self
+    }
+    
+    /// Add crawled pages to a full-text index, committed when the crawl ends
+    pub fn indexer(mut self, indexer: Indexer) -> Self {
+        self.indexer = Some(indexer);
+        self
+    }
+    
+    pub fn build(self) -> Crawler {
+        let mut crawler = match self.frontier {
+            Some(frontier) => Crawler::with_frontier(self.config, self.storage, frontier),
+            None => Crawler::with_storage(self.config, self.storage),
+        };
+        crawler.indexer = self.indexer;
+        crawler.extractors = self.extractors;
+        if let Some(html_parser) = html_parser(&crawler.config) {
+            // The builder's registry replaced the one made for the config
+            crawler.extractors.set_html(Arc::new(html_parser));
+        }
+        let mut fetcher = self.fetcher;
+        if let Some(path) = &crawler.config.record_path {
+            let inner = fetcher.clone().unwrap_or_else(|| crawler.fetcher.clone());
+            match RecordingFetcher::with_flush_policy(inner, path, crawler.config.output_flush) {
+                Ok(recording) => fetcher = Some(Arc::new(recording)),
+                Err(e) => error!("Not recording to {}: {}", path.display(), e),
+            }
+        }
+        if let Some(fetcher) = fetcher {
+            crawler.robots_checker = crawler.robots_checker.with_fetcher(fetcher.clone());
+            crawler.fetcher = fetcher;
+        }
+        if let Some(policy) = self.retry_policy {
+            crawler.retry_policy = policy;
+        }
+        crawler
+    }
+}
+
+impl Default for CrawlerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crawler::mock_server::{MockResponse, MockServer};
+    use crate::crawler::RobotsRules;
+    use crate::search::{SearchQuery, Searcher, SortOrder};
+    use crate::storage::{InMemoryStore, PageStore};
+    use tokio_stream::StreamExt;
+    
+    #[test]
+    fn test_content_length_range_unbounded() {
+        let config = CrawlerConfig::default();
+        assert!(config.content_length_in_range(0));
+        assert!(config.content_length_in_range(usize::MAX));
+    }
+    
+    #[test]
+    fn test_content_length_range_boundaries() {
+        let config = CrawlerConfig {
+            min_content_length: Some(100),
+            max_content_length: Some(1000),
+            ..CrawlerConfig::default()
+        };
+        
+        // Lower boundary
+        assert!(!config.content_length_in_range(99));
+        assert!(config.content_length_in_range(100));
+        
+        // Upper boundary
+        assert!(config.content_length_in_range(1000));
+        assert!(!config.content_length_in_range(1001));
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_out_of_range_pages_followed_not_stored() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/big">big</a>"#);
+        server.html("/big", &format!(r#"<title>Big</title><a href="/ok">ok</a><p>{}</p>"#, "giant ".repeat(100)));
+        server.html("/ok", &format!("<title>Ok</title><p>{}</p>", "normal text ".repeat(5)));
+        
+        let store = InMemoryStore::new();
+        let indexer = Indexer::in_memory().unwrap();
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .min_content_length(40)
+            .max_content_length(400)
+            .storage(Arc::new(store.clone()))
+            .indexer(indexer.clone())
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        // Both out-of-range pages lead on to the page that is kept
+        assert_eq!(stats.pages_crawled, 3);
+        assert_eq!(stats.pages_out_of_range, 2);
+        assert_eq!(store.all_urls().await.unwrap(), vec![server.url("/ok").to_string()]);
+        let searcher = Searcher::for_indexer(&indexer).unwrap();
+        searcher.reload().unwrap();
+        assert_eq!(searcher.search(&SearchQuery::new("giant")).unwrap().total, 0);
+        assert_eq!(searcher.search(&SearchQuery::new("normal")).unwrap().total, 1);
+    }
+    
+    #[test]
+    fn test_retry_delay_full_jitter() {
+        let (base, max) = (Duration::from_millis(100), Duration::from_secs(1));
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut delays = Vec::new();
+        for attempt in 0..6 {
+            let backoff = (base * 2u32.pow(attempt)).min(max);
+            assert_eq!(retry_delay(base, max, attempt, false, &mut rng), backoff);
+            for _ in 0..20 {
+                let delay = retry_delay(base, max, attempt, true, &mut rng);
+                assert!(delay <= backoff);
+                delays.push(delay);
+            }
+        }
+        assert!(delays.iter().any(|d| *d != delays[0]));
+        
+        // The same seed produces the same delays
+        let mut rng = StdRng::seed_from_u64(7);
+        let replay: Vec<Duration> = (0..6)
+            .flat_map(|attempt| {
+                retry_delay(base, max, attempt, false, &mut rng);
+                (0..20).map(|_| retry_delay(base, max, attempt, true, &mut rng)).collect::<Vec<_>>()
+            })
+            .collect();
+        assert_eq!(delays, replay);
+        
+        // Large attempt numbers saturate at the cap
+        assert_eq!(retry_delay(base, max, 100, false, &mut rng), max);
+    }
+    
+    #[test]
+    fn test_jittered_delay_range() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let delays: Vec<Duration> = (0..100)
+            .map(|_| jittered_delay(1000, 20.0, &mut rng))
+            .collect();
+        
+        for delay in &delays {
+            assert!(*delay >= Duration::from_millis(800));
+            assert!(*delay <= Duration::from_millis(1200));
+        }
+        assert!(delays.iter().any(|d| *d != delays[0]));
+        
+        // The same seed produces the same delays
+        let mut rng = StdRng::seed_from_u64(42);
+        let replay: Vec<Duration> = (0..100)
+            .map(|_| jittered_delay(1000, 20.0, &mut rng))
+            .collect();
+        assert_eq!(delays, replay);
+        
+        // No jitter configured
+        assert_eq!(jittered_delay(1000, 0.0, &mut rng), Duration::from_millis(1000));
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_stats_stream_snapshots() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/a">a</a><a href="/b">b</a><a href="/c">c</a>"#);
+        server.html("/a", "<title>A</title>");
+        server.html("/b", "<title>B</title>");
+        server.html("/c", "<title>C</title>");
+        
+        let crawler = CrawlerBuilder::new()
+            .max_concurrent(1)
+            .delay_ms(0)
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        
+        let stream = crawler.stats_stream(Duration::from_millis(5));
+        let collector = tokio::spawn(async move { stream.collect::<Vec<_>>().await });
+        
+        let final_stats = crawler.crawl().await.unwrap();
+        let snapshots = collector.await.unwrap();
+        
+        assert!(!snapshots.is_empty());
+        for pair in snapshots.windows(2) {
+            assert!(pair[0].pages_crawled <= pair[1].pages_crawled);
+        }
+        assert_eq!(snapshots.last().unwrap().pages_crawled, final_stats.pages_crawled);
+        assert_eq!(final_stats.pages_crawled, 4);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_crawl_stream_yields_each_page() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/a">a</a><a href="/b">b</a><a href="/c">c</a>"#);
+        server.html("/a", "<title>A</title>");
+        server.html("/b", "<title>B</title>");
+        server.html("/c", "<title>C</title>");
+        server.route("/missing", MockResponse::html("").status(404));
+        
+        let crawler = CrawlerBuilder::new().max_concurrent(2).delay_ms(0).build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        crawler.add_seed(server.url("/missing")).await.unwrap();
+        
+        // A slow consumer holds the crawl back rather than losing pages
+        let mut stream = Box::pin(crawler.crawl_stream());
+        let mut urls = Vec::new();
+        while let Some(item) = stream.next().await {
+            let (url, page) = item.unwrap();
+            assert_eq!(page.title.is_some(), url.path() != "/");
+            urls.push(url);
+            sleep(Duration::from_millis(20)).await;
+        }
+        
+        urls.sort();
+        let mut expected: Vec<Url> = ["/", "/a", "/b", "/c"].iter().map(|path| server.url(path)).collect();
+        expected.sort();
+        assert_eq!(urls, expected);
+        assert_eq!(crawler.get_stats().await.pages_failed, 1);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_circuit_breaker_skips_failing_domain() {
+        let server = MockServer::start();
+        for i in 0..10 {
+            server.route(&format!("/{}", i), MockResponse::html("down").status(500));
+        }
+        
+        let crawler = CrawlerBuilder::new()
+            .max_concurrent(1)
+            .delay_ms(0)
+            .circuit_breaker(0.5, Duration::from_secs(60), Duration::from_secs(60))
+            .error_min_samples(3)
+            .build();
+        for i in 0..10 {
+            crawler.add_seed(server.url(&format!("/{}", i))).await.unwrap();
+        }
+        
+        let stats = crawler.crawl().await.unwrap();
+        
+        // The breaker trips after three failures and the rest are skipped during cooldown
+        let page_hits = server.requests().iter().filter(|r| r.path != "/robots.txt").count();
+        assert_eq!(page_hits, 3);
+        assert_eq!(stats.pages_failed, 3);
+        assert_eq!(stats.breaker_trips, 1);
+        assert_eq!(stats.breaker_skipped, 7);
+    }
+    
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn test_max_inflight_requests() {
+        let server = MockServer::start();
+        for i in 0..8 {
+            server.route(
+                &format!("/{}", i),
+                MockResponse::html("<title>slow</title>").delay(Duration::from_millis(50)),
+            );
+        }
+        
+        let crawler = CrawlerBuilder::new()
+            .max_concurrent(8)
+            .max_inflight_requests(2)
+            .delay_ms(0)
+            .build();
+        for i in 0..8 {
+            crawler.add_seed(server.url(&format!("/{}", i))).await.unwrap();
+        }
+        
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.pages_crawled, 8);
+        assert!(server.max_concurrent() <= 2);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_stores_response_headers() {
+        let server = MockServer::start();
+        server.route(
+            "/",
+            MockResponse::html("<title>Home</title>")
+                .header("X-Trace", "one")
+                .header("X-Trace", "two"),
+        );
+        
+        let dir = tempfile::tempdir().unwrap();
+        let store = PageStore::open(dir.path(), true).unwrap();
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .storage(Arc::new(store.clone()))
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        crawler.crawl().await.unwrap();
+        
+        let page = store.get(server.url("/").as_str()).unwrap().unwrap();
+        assert_eq!(page.title.as_deref(), Some("Home"));
+        let headers = page.headers.unwrap();
+        assert_eq!(headers["x-trace"], vec!["one", "two"]);
+        assert_eq!(headers["content-type"], vec!["text/html"]);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_crawl_with_resolve_override() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/next">next</a>"#);
+        server.html("/next", "<title>Next</title>");
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .resolve_override("example.test", server.addr())
+            .build();
+        crawler.add_seed(Url::parse("http://example.test/").unwrap()).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.pages_crawled, 2);
+        assert_eq!(server.hits("/next"), 1);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_add_sitemap_prioritizes_entries() {
+        let server = MockServer::start();
+        let sitemap = format!(
+            "<urlset><url><loc>{}</loc><priority>0.1</priority></url>\
+             <url><loc>{}</loc><priority>1.0</priority></url></urlset>",
+            server.url("/low"),
+            server.url("/high"),
+        );
+        server.route(
+            "/sitemap.xml",
+            MockResponse::typed("application/xml", sitemap.as_bytes()),
+        );
+        
+        let crawler = CrawlerBuilder::new().delay_ms(0).build();
+        assert_eq!(crawler.add_sitemap(&server.url("/sitemap.xml")).await.unwrap(), 2);
+        
+        assert_eq!(crawler.frontier.pop().await.unwrap().url, server.url("/high"));
+        assert_eq!(crawler.frontier.pop().await.unwrap().url, server.url("/low"));
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_crawl_since_skips_fresh_pages() {
+        let server = MockServer::start();
+        for path in ["/fresh", "/stale", "/new"] {
+            server.html(path, "<title>Page</title>");
+        }
+        let store = InMemoryStore::new();
+        let now = chrono::Utc::now();
+        for (path, age) in [("/fresh", chrono::Duration::hours(1)), ("/stale", chrono::Duration::days(10))] {
+            store
+                .insert_page(StoredPage {
+                    url: server.url(path).to_string(),
+                    status_code: 200,
+                    content_type: Some("text/html".to_string()),
+                    title: None,
+                    text_content: String::new(),
+                    depth: 0,
+                    crawled_at: now - age,
+                    headers: None,
+                    open_graph: BTreeMap::new(),
+                    canonical_url: None,
+                    tags: BTreeMap::new(),
+                })
+                .await
+                .unwrap();
+        }
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .storage(Arc::new(store.clone()))
+            .crawl_since(now - chrono::Duration::days(1))
+            .build();
+        let mut events = crawler.subscribe();
+        for path in ["/fresh", "/stale", "/new"] {
+            crawler.add_seed(server.url(path)).await.unwrap();
+        }
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.fresh_skipped, 1);
+        assert_eq!(stats.pages_crawled, 2);
+        assert_eq!(server.hits("/fresh"), 0);
+        assert_eq!(server.hits("/stale"), 1);
+        assert_eq!(server.hits("/new"), 1);
+        assert!(store.get_page(server.url("/stale").as_str()).await.unwrap().unwrap().crawled_at > now);
+        assert_eq!(
+            events.try_recv().unwrap(),
+            CrawlEvent::Skipped {
+                url: server.url("/fresh"),
+                reason: SkipReason::Fresh,
+            }
+        );
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_audit_redirects() {
+        let server = MockServer::start();
+        server.route("/old", MockResponse::html("").status(302).header("Location", "/new"));
+        server.html("/new", "<title>New</title>");
+        let store = InMemoryStore::new();
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .storage(Arc::new(store.clone()))
+            .audit_redirects(true)
+            .build();
+        let mut events = crawler.subscribe();
+        crawler.add_seed(server.url("/old")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.redirects_audited, 1);
+        assert_eq!(stats.pages_crawled, 1);
+        assert_eq!(stats.status_codes.get(&302), Some(&1));
+        assert_eq!(server.hits("/new"), 1);
+        assert_eq!(
+            events.try_recv().unwrap(),
+            CrawlEvent::Redirected {
+                url: server.url("/old"),
+                status: 302,
+                target: Some(server.url("/new")),
+            }
+        );
+        let redirect = store.get_page(server.url("/old").as_str()).await.unwrap().unwrap();
+        assert_eq!(redirect.status_code, 302);
+        assert_eq!(redirect.headers.unwrap()["location"], vec!["/new"]);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_gzipped_sitemap_index() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+        
+        let gzip = |xml: String| {
+            let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(xml.as_bytes()).unwrap();
+            encoder.finish().unwrap()
+        };
+        let server = MockServer::start();
+        let index = |children: &[&str]| {
+            let children: String = children
+                .iter()
+                .map(|path| format!("<sitemap><loc>{}</loc></sitemap>", server.url(path)))
+                .collect();
+            format!("<sitemapindex>{}</sitemapindex>", children)
+        };
+        let urlset = |paths: &[&str]| {
+            let urls: String = paths.iter().map(|path| format!("<url><loc>{}</loc></url>", server.url(path))).collect();
+            format!("<urlset>{}</urlset>", urls)
+        };
+        server.route("/sitemap.xml.gz", MockResponse::typed("application/x-gzip", &gzip(index(&["/nested.xml.gz", "/pages.xml"]))));
+        server.route("/nested.xml.gz", MockResponse::typed("application/gzip", &gzip(index(&["/posts.xml.gz"]))));
+        server.route("/posts.xml.gz", MockResponse::typed("application/gzip", &gzip(urlset(&["/post/1", "/post/2"]))));
+        server.route("/pages.xml", MockResponse::typed("application/xml", urlset(&["/about"]).as_bytes()));
+        
+        let crawler = CrawlerBuilder::new().delay_ms(0).build();
+        assert_eq!(crawler.add_sitemap(&server.url("/sitemap.xml.gz")).await.unwrap(), 3);
+        let mut queued = Vec::new();
+        while let Some(task) = crawler.frontier.pop().await {
+            queued.push(task.url.path().to_string());
+        }
+        queued.sort();
+        assert_eq!(queued, ["/about", "/post/1", "/post/2"]);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_api_pages_stop_at_empty_page() {
+        let server = MockServer::start();
+        let page = |paths: &[&str]| {
+            let items: Vec<String> = paths.iter().map(|path| format!(r#"{{"id": 1, "url": "{}"}}"#, path)).collect();
+            format!(r#"{{"items": [{}]}}"#, items.join(","))
+        };
+        server.route("/items?page=1", MockResponse::typed("application/json", page(&["/item/1", "/item/2"]).as_bytes()));
+        server.route("/items?page=2", MockResponse::typed("application/json", page(&["/item/3"]).as_bytes()));
+        server.route("/items?page=3", MockResponse::typed("application/json", page(&[]).as_bytes()));
+        server.route("/items?page=4", MockResponse::typed("application/json", page(&["/item/4"]).as_bytes()));
+        
+        let crawler = CrawlerBuilder::new().delay_ms(0).build();
+        let paginator = ApiPaginator::new(format!("{}?page={{n}}", server.url("/items")));
+        assert_eq!(crawler.add_api_pages(&paginator).await.unwrap(), 3);
+        assert_eq!(server.hits("/items?page=3"), 1);
+        assert_eq!(server.hits("/items?page=4"), 0);
+        let mut queued = Vec::new();
+        while let Some(task) = crawler.frontier.pop().await {
+            queued.push(task.url.path().to_string());
+        }
+        queued.sort();
+        assert_eq!(queued, ["/item/1", "/item/2", "/item/3"]);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_follow_robots_sitemaps() {
+        let server = MockServer::start();
+        let robots = format!("User-agent: *\nSitemap: {}\n", server.url("/sitemap.xml"));
+        server.route("/robots.txt", MockResponse::typed("text/plain", robots.as_bytes()));
+        let sitemap: String = ["/a", "/b", "/c", "/d"]
+            .iter()
+            .map(|path| format!("<url><loc>{}</loc></url>", server.url(path)))
+            .collect();
+        server.route(
+            "/sitemap.xml",
+            MockResponse::typed("application/xml", format!("<urlset>{}</urlset>", sitemap).as_bytes()),
+        );
+        server.html("/", "<title>Home</title>");
+        for path in ["/a", "/b", "/c", "/d"] {
+            server.html(path, "<title>Page</title>");
+        }
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .max_pages(4)
+            .follow_sitemaps(true)
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.pages_crawled, 4);
+        assert_eq!(server.hits("/sitemap.xml"), 1);
+        assert_eq!(server.hits("/a") + server.hits("/b") + server.hits("/c"), 3);
+        assert_eq!(server.hits("/d"), 0);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_next_page_prioritized() {
+        let server = MockServer::start();
+        server.html(
+            "/",
+            r#"<a href="/x">x</a><a href="/y">y</a><a href="/z">z</a><a rel="next" href="/page/2">more</a>"#,
+        );
+        server.html("/page/2", "<title>Page 2</title>");
+        
+        let crawler = CrawlerBuilder::new()
+            .max_concurrent(1)
+            .delay_ms(0)
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        crawler.crawl().await.unwrap();
+        
+        let paths: Vec<String> = server
+            .requests()
+            .into_iter()
+            .map(|r| r.path)
+            .filter(|path| path != "/robots.txt")
+            .collect();
+        assert_eq!(&paths[..2], &["/", "/page/2"]);
+        assert_eq!(paths.len(), 5);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_crawl_into_memory_storage() {
+        let server = MockServer::start();
+        server.html("/", r#"<title>Home</title><a href="/about">about</a>"#);
+        server.html("/about", "<title>About</title>");
+        
+        let store = InMemoryStore::new();
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .storage(Arc::new(store.clone()))
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        crawler.crawl().await.unwrap();
+        
+        let mut urls = store.all_urls().await.unwrap();
+        urls.sort();
+        assert_eq!(urls, vec![server.url("/").to_string(), server.url("/about").to_string()]);
+        assert!(store.exists(server.url("/about").as_str()).await.unwrap());
+        let about = store.get_page(server.url("/about").as_str()).await.unwrap().unwrap();
+        assert_eq!(about.title.as_deref(), Some("About"));
+        assert_eq!(about.depth, 1);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_crawled_pages_are_searchable() {
+        let server = MockServer::start();
+        server.html("/", r#"<title>Home</title><a href="/rust">rust</a>"#);
+        server.html(
+            "/rust",
+            r#"<title>Rust</title><meta property="og:image" content="/rust.png"><p>ownership and borrowing</p><img src="/ferris.png" alt="Ferris the crab">"#,
+        );
+        
+        let indexer = Indexer::in_memory().unwrap();
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .indexer(indexer.clone())
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        crawler.crawl().await.unwrap();
+        
+        let searcher = Searcher::for_indexer(&indexer).unwrap();
+        searcher.reload().unwrap();
+        let results = searcher.search(&SearchQuery::new("borrowing")).unwrap();
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].url, server.url("/rust").to_string());
+        assert_eq!(results.hits[0].title.as_deref(), Some("Rust"));
+        assert_eq!(results.hits[0].image, Some(server.url("/rust.png").to_string()));
+        assert_eq!(searcher.search(&SearchQuery::new("ferris")).unwrap().total, 1);
+        assert_eq!(results.hits[0].published_at, None);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_published_at_falls_back_to_last_modified() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/dated">dated</a><a href="/header">header</a>"#);
+        server.route(
+            "/dated",
+            MockResponse::html(r#"<time datetime="2024-05-01">May</time><p>crab news</p>"#)
+                .header("Last-Modified", "Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        server.route(
+            "/header",
+            MockResponse::html("<p>crab archive</p>").header("Last-Modified", "Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        
+        let indexer = Indexer::in_memory().unwrap();
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .indexer(indexer.clone())
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        crawler.crawl().await.unwrap();
+        
+        let searcher = Searcher::for_indexer(&indexer).unwrap();
+        searcher.reload().unwrap();
+        let results = searcher.search(&SearchQuery::new("crab").sort(SortOrder::Published)).unwrap();
+        let dates: Vec<(String, Option<String>)> = results
+            .hits
+            .iter()
+            .map(|hit| (hit.url.clone(), hit.published_at.map(|date| date.to_rfc3339())))
+            .collect();
+        assert_eq!(
+            dates,
+            vec![
+                (server.url("/dated").to_string(), Some("2024-05-01T00:00:00+00:00".to_string())),
+                (server.url("/header").to_string(), Some("2015-10-21T07:28:00+00:00".to_string())),
+            ]
+        );
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_seed_tags_are_inherited() {
+        let server = MockServer::start();
+        server.html("/news", r#"<a href="/news/story">story</a>"#);
+        server.html("/news/story", "<p>crab story</p>");
+        server.html("/blog", "<p>crab blog</p>");
+        
+        let store = InMemoryStore::new();
+        let indexer = Indexer::in_memory().unwrap();
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .storage(Arc::new(store.clone()))
+            .indexer(indexer.clone())
+            .build();
+        let tags = |category: &str| HashMap::from([("category".to_string(), category.to_string())]);
+        crawler.add_seed_tagged(server.url("/news"), tags("news")).await.unwrap();
+        crawler.add_seed_tagged(server.url("/blog"), tags("blog")).await.unwrap();
+        crawler.crawl().await.unwrap();
+        
+        let story = store.get_page(server.url("/news/story").as_str()).await.unwrap().unwrap();
+        assert_eq!(story.tags.get("category").map(String::as_str), Some("news"));
+        let blog = store.get_page(server.url("/blog").as_str()).await.unwrap().unwrap();
+        assert_eq!(blog.tags.get("category").map(String::as_str), Some("blog"));
+        
+        let searcher = Searcher::for_indexer(&indexer).unwrap();
+        searcher.reload().unwrap();
+        let results = searcher.search(&SearchQuery::new("crab AND tags:/category/news")).unwrap();
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].url, server.url("/news/story").as_str());
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_domain_weights_split_page_budget() {
+        let server = MockServer::start();
+        let links: String = (0..15).map(|i| format!(r#"<a href="/{}">{}</a>"#, i, i)).collect();
+        server.html("/", &links);
+        for i in 0..15 {
+            server.html(&format!("/{}", i), "<title>Page</title>");
+        }
+        
+        let store = InMemoryStore::new();
+        let weights = HashMap::from([("a.test".to_string(), 0.7), ("b.test".to_string(), 0.3)]);
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_pages(20)
+            .max_concurrent(1)
+            .resolve_override("a.test", server.addr())
+            .resolve_override("b.test", server.addr())
+            .domain_weights(weights)
+            .storage(Arc::new(store.clone()))
+            .build();
+        let port = server.addr().port();
+        crawler.add_seed(Url::parse(&format!("http://a.test:{}/", port)).unwrap()).await.unwrap();
+        crawler.add_seed(Url::parse(&format!("http://b.test:{}/", port)).unwrap()).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        let urls = store.all_urls().await.unwrap();
+        let on_host = |host: &str| urls.iter().filter(|url| url.contains(host)).count();
+        assert_eq!(on_host("a.test"), 14);
+        assert_eq!(on_host("b.test"), 6);
+        assert!(stats.pages_over_budget > 0);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rate_limit_by_ip_shares_delay() {
+        let server = MockServer::start();
+        server.html("/", "<title>Shared</title>");
+        let port = server.addr().port();
+        
+        for by_ip in [true, false] {
+            let crawler = CrawlerBuilder::new()
+                .delay_ms(400)
+                .max_concurrent(2)
+                .resolve_override("a.test", server.addr())
+                .resolve_override("b.test", server.addr())
+                .rate_limit_by_ip(by_ip)
+                .build();
+            crawler.add_seed(Url::parse(&format!("http://a.test:{}/", port)).unwrap()).await.unwrap();
+            crawler.add_seed(Url::parse(&format!("http://b.test:{}/", port)).unwrap()).await.unwrap();
+            let started = Instant::now();
+            let stats = crawler.crawl().await.unwrap();
+            assert_eq!(stats.pages_crawled, 2);
+            
+            let mut keys: Vec<String> = crawler.domain_last_access.lock().await.keys().cloned().collect();
+            keys.sort();
+            if by_ip {
+                // Both hosts wait on the one bucket of their shared address
+                assert_eq!(keys, ["127.0.0.1"]);
+                assert!(started.elapsed() >= Duration::from_millis(400));
+            } else {
+                assert_eq!(keys, ["a.test", "b.test"]);
+            }
+        }
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rate_limit_by_ip_shares_fetcher_resolver() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/a">a</a><a href="/b">b</a>"#);
+        server.html("/a", "<title>A</title>");
+        server.html("/b", "<title>B</title>");
+        let lookups = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let resolver = {
+            let (lookups, addr) = (lookups.clone(), server.addr());
+            DnsResolver::with_lookup(move |_| {
+                lookups.fetch_add(1, Ordering::SeqCst);
+                Ok(vec![addr])
+            })
+            .cache_ttl(Duration::from_secs(60))
+        };
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .dns_resolver(resolver)
+            .rate_limit_by_ip(true)
+            .respect_robots(false)
+            .build();
+        crawler.add_seed(Url::parse(&format!("http://a.test:{}/", server.addr().port())).unwrap()).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        // The rate limiter's lookup is answered from the fetcher's cache, or
+        // the other way round, so the host resolves once for all three pages
+        assert_eq!(stats.pages_crawled, 3);
+        assert_eq!(lookups.load(Ordering::SeqCst), 1);
+        assert_eq!(crawler.domain_last_access.lock().await.keys().collect::<Vec<_>>(), ["127.0.0.1"]);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_redirect_out_of_scope_not_followed() {
+        let server = MockServer::start();
+        let port = server.addr().port();
+        server.html("/", r#"<a href="/moved">moved</a><a href="/local">local</a>"#);
+        server.route(
+            "/moved",
+            MockResponse::html("").status(301).header("Location", &format!("http://b.test:{}/target", port)),
+        );
+        server.route("/local", MockResponse::html("").status(302).header("Location", "/target-local"));
+        server.html("/target", "<title>Off site</title>");
+        server.html("/target-local", "<title>On site</title>");
+        
+        let store = InMemoryStore::new();
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .resolve_override("a.test", server.addr())
+            .resolve_override("b.test", server.addr())
+            .same_site(SameSitePolicy::ExactHost)
+            .storage(Arc::new(store.clone()))
+            .build();
+        let mut events = crawler.subscribe();
+        let seed = Url::parse(&format!("http://a.test:{}/", port)).unwrap();
+        crawler.add_seed(seed.clone()).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(server.hits("/target"), 0);
+        assert_eq!(server.hits("/target-local"), 1);
+        assert_eq!(stats.pages_crawled, 2);
+        assert!(store.exists(seed.join("/local").unwrap().as_str()).await.unwrap());
+        assert_eq!(
+            events.try_recv().unwrap(),
+            CrawlEvent::Skipped {
+                url: seed.join("/moved").unwrap(),
+                reason: SkipReason::OutOfScope,
+            }
+        );
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_max_unique_hosts_drops_new_hosts() {
+        let server = MockServer::start();
+        let port = server.addr().port();
+        let links: String = ["a", "b", "c", "d"]
+            .iter()
+            .map(|host| format!(r#"<a href="http://{}.test:{}/{}">{}</a>"#, host, port, host, host))
+            .collect();
+        server.html("/", &links);
+        for path in ["/a", "/b", "/c", "/d"] {
+            server.html(path, "<title>Page</title>");
+        }
+        
+        let mut builder = CrawlerBuilder::new().delay_ms(0).max_concurrent(1).max_unique_hosts(2);
+        for host in ["a.test", "b.test", "c.test", "d.test"] {
+            builder = builder.resolve_override(host, server.addr());
+        }
+        let crawler = builder.build();
+        let mut events = crawler.subscribe();
+        crawler.add_seed(Url::parse(&format!("http://a.test:{}/", port)).unwrap()).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(server.hits("/a"), 1);
+        assert_eq!(server.hits("/b"), 1);
+        assert_eq!(server.hits("/c"), 0);
+        assert_eq!(server.hits("/d"), 0);
+        assert_eq!(stats.new_hosts_dropped, 2);
+        let dropped: Vec<CrawlEvent> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+        assert!(dropped.contains(&CrawlEvent::Skipped {
+            url: Url::parse(&format!("http://c.test:{}/c", port)).unwrap(),
+            reason: SkipReason::HostLimit,
+        }));
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_nofollow_links_not_queued() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/followed">a</a><a href="/ad" rel="nofollow sponsored">b</a>"#);
+        server.html("/followed", "<title>Followed</title>");
+        server.html("/ad", "<title>Ad</title>");
+        
+        let crawler = CrawlerBuilder::new().delay_ms(0).max_concurrent(1).build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        crawler.crawl().await.unwrap();
+        assert_eq!(server.hits("/followed"), 1);
+        assert_eq!(server.hits("/ad"), 0);
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .skip_link_rels(Vec::new())
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        crawler.crawl().await.unwrap();
+        assert_eq!(server.hits("/ad"), 1);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_global_rate_limit() {
+        let server = MockServer::start();
+        let links: String = (0..9).map(|i| format!(r#"<a href="/{}">{}</a>"#, i, i)).collect();
+        server.html("/", &links);
+        for i in 0..9 {
+            server.html(&format!("/{}", i), "<title>Page</title>");
+        }
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(4)
+            .global_rate_limit(20.0)
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        assert_eq!(stats.pages_crawled, 10);
+        
+        // Page requests are spaced at least 50ms apart; robots.txt fetches
+        // aren't rate limited
+        let mut times: Vec<Instant> = server
+            .requests()
+            .into_iter()
+            .filter(|request| request.path != "/robots.txt")
+            .map(|request| request.received_at)
+            .collect();
+        times.sort();
+        assert_eq!(times.len(), 10);
+        for window in times.windows(5) {
+            assert!(window[4] - window[0] >= Duration::from_millis(190));
+        }
+    }
+    
+    /// Treats each line of a text file as a title, counting its calls
+    struct LineExtractor(std::sync::atomic::AtomicUsize);
+    
+    impl ContentExtractor for LineExtractor {
+        fn extract(&self, body: &str, _url: &Url) -> Result<crate::crawler::ParsedPage> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(crate::crawler::ParsedPage {
+                title: body.lines().next().map(str::to_string),
+                text_content: body.to_string(),
+                ..Default::default()
+            })
+        }
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_custom_extractor_for_content_type() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/notes.txt">notes</a><a href="/data">data</a>"#);
+        server.route("/notes.txt", MockResponse::typed("text/plain; charset=utf-8", b"Release notes\nFixed things"));
+        server.route("/data", MockResponse::typed("application/x-lines", b"Lines\nMore"));
+        
+        let plain = Arc::new(LineExtractor(Default::default()));
+        let lines = Arc::new(LineExtractor(Default::default()));
+        let store = InMemoryStore::new();
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .extractor("text/plain", plain.clone())
+            .extractor("application/x-lines", lines.clone())
+            .storage(Arc::new(store.clone()))
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.pages_crawled, 3);
+        assert_eq!(plain.0.load(Ordering::SeqCst), 1);
+        assert_eq!(lines.0.load(Ordering::SeqCst), 1);
+        let notes = store.get_page(server.url("/notes.txt").as_str()).await.unwrap().unwrap();
+        assert_eq!(notes.title.as_deref(), Some("Release notes"));
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_links_followed_on_listed_error_status() {
+        let server = MockServer::start();
+        server.route(
+            "/",
+            MockResponse::html(r#"<nav><a href="/docs">Docs</a></nav>"#).status(404),
+        );
+        server.html("/docs", "<title>Docs</title>");
+        
+        let crawler = CrawlerBuilder::new().delay_ms(0).max_concurrent(1).build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        assert_eq!(stats.pages_failed, 1);
+        assert_eq!(server.hits("/docs"), 0);
+        
+        let store = InMemoryStore::new();
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .parse_status_codes(vec![404])
+            .storage(Arc::new(store.clone()))
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.pages_failed, 1);
+        assert_eq!(stats.pages_crawled, 1);
+        assert_eq!(server.hits("/docs"), 1);
+        assert_eq!(store.all_urls().await.unwrap(), vec![server.url("/docs").to_string()]);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_crawl_into_store() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/a">a</a><a href="/b">b</a><a href="/c">c</a>"#);
+        for path in ["/a", "/b", "/c"] {
+            server.html(path, "<title>Page</title>");
+        }
+        
+        let crawler = CrawlerBuilder::new().delay_ms(0).max_concurrent(1).max_pages(3).build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let store = InMemoryStore::new();
+        let stats = crawler.crawl_into(store.clone()).await.unwrap();
+        
+        assert_eq!(stats.pages_crawled, 3);
+        let urls = store.all_urls().await.unwrap();
+        assert_eq!(urls.len(), 3);
+        assert!(urls.contains(&server.url("/").to_string()));
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_robots_blocked_event() {
+        let server = MockServer::start();
+        server.route("/robots.txt", MockResponse::typed("text/plain", b"User-agent: *\nDisallow: /private"));
+        server.html("/", r#"<a href="/private/page">private</a><a href="/public">public</a>"#);
+        server.html("/public", "<title>Public</title>");
+        server.html("/private/page", "<title>Private</title>");
+        
+        let crawler = CrawlerBuilder::new().delay_ms(0).max_concurrent(1).build();
+        let mut events = crawler.subscribe();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.pages_crawled, 2);
+        assert_eq!(stats.robots_blocked, 1);
+        assert_eq!(server.hits("/private/page"), 0);
+        assert_eq!(
+            events.try_recv().unwrap(),
+            CrawlEvent::Skipped {
+                url: server.url("/private/page"),
+                reason: SkipReason::RobotsDisallowed,
+            }
+        );
+        assert_eq!(events.try_recv().unwrap(), CrawlEvent::Finished { stats });
+        assert!(events.try_recv().is_err());
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_robots_override() {
+        let server = MockServer::start();
+        server.route("/robots.txt", MockResponse::typed("text/plain", b"User-agent: *\nDisallow: /private"));
+        server.html("/", r#"<a href="/private/page">private</a><a href="/public">public</a>"#);
+        server.html("/public", "<title>Public</title>");
+        server.html("/private/page", "<title>Private</title>");
+        let host = server.url("/").host_str().unwrap().to_string();
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .robots_override(&host, RobotsOverride::AllowAll)
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        assert_eq!(stats.pages_crawled, 3);
+        assert_eq!(stats.robots_blocked, 0);
+        assert_eq!(server.hits("/private/page"), 1);
+        assert_eq!(server.hits("/robots.txt"), 0);
+        
+        let rules = RobotsRules::new().disallow("/").allow("/public$");
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .robots_override(&host, RobotsOverride::Rules(rules))
+            .build();
+        crawler.add_seed(server.url("/public")).await.unwrap();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        assert_eq!(stats.pages_crawled, 1);
+        assert_eq!(stats.robots_blocked, 1);
+        assert_eq!(server.hits("/robots.txt"), 0);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_ignore_robots() {
+        let server = MockServer::start();
+        server.route("/robots.txt", MockResponse::typed("text/plain", b"User-agent: *\nDisallow: /private"));
+        server.html("/", r#"<a href="/private/page">private</a>"#);
+        server.html("/private/page", "<title>Private</title>");
+        
+        let crawler = CrawlerBuilder::new().delay_ms(0).build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        assert_eq!(stats.pages_crawled, 1);
+        assert_eq!(stats.robots_blocked, 1);
+        assert_eq!(server.hits("/private/page"), 0);
+        
+        let crawler = CrawlerBuilder::new().delay_ms(0).respect_robots(false).build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        assert_eq!(stats.pages_crawled, 2);
+        assert_eq!(stats.robots_blocked, 0);
+        assert_eq!(server.hits("/private/page"), 1);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_custom_retry_policy() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/missing">missing</a>"#);
+        server.route("/missing", MockResponse::html("").status(404));
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .max_retries(2)
+            .retry_policy(|_: &Error, status: Option<u16>| status == Some(404))
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(server.hits("/missing"), 3);
+        assert_eq!(stats.pages_retried, 2);
+        assert_eq!(stats.pages_failed, 1);
+        
+        // 404 is final under the default policy
+        let crawler = CrawlerBuilder::new().delay_ms(0).max_concurrent(1).max_retries(2).build();
+        crawler.add_seed(server.url("/missing")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        assert_eq!(server.hits("/missing"), 4);
+        assert_eq!(stats.pages_retried, 0);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_failed_urls_recorded() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/broken">broken</a><a href="/gone">gone</a>"#);
+        server.route("/broken", MockResponse::html("").status(500));
+        server.route("/gone", MockResponse::html("").status(404));
+        
+        let crawler = CrawlerBuilder::new().delay_ms(0).max_concurrent(1).max_retries(1).build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        // Only the final failure is recorded, not the retried attempt
+        assert_eq!(server.hits("/broken"), 2);
+        let broken = server.url("/broken");
+        let failed: HashMap<&Url, &str> = stats.failed_urls.iter().map(|(url, e)| (url, e.as_str())).collect();
+        assert_eq!(failed.len(), 2);
+        assert_eq!(failed[&broken], format!("HTTP 500 for {}", broken));
+        assert!(failed[&server.url("/gone")].starts_with("HTTP 404"));
+        
+        let crawler = CrawlerBuilder::new().delay_ms(0).max_concurrent(1).max_failed_urls(1).build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        assert_eq!(stats.pages_failed, 2);
+        assert_eq!(stats.failed_urls.len(), 1);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_crawl_local_file_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("docs");
+        std::fs::create_dir_all(root.join("guide")).unwrap();
+        std::fs::write(
+            root.join("index.html"),
+            r#"<title>Docs</title><a href="guide/">Guide</a><a href="../secret.html">Secret</a>"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("guide/index.html"),
+            r#"<title>Guide</title><a href="setup.html">Setup</a>"#,
+        )
+        .unwrap();
+        std::fs::write(root.join("guide/setup.html"), "<title>Setup</title>").unwrap();
+        std::fs::write(dir.path().join("secret.html"), "<title>Secret</title>").unwrap();
+        
+        let store = InMemoryStore::new();
+        let crawler = CrawlerBuilder::new()
+            .max_concurrent(1)
+            .allow_file_scheme(&root)
+            .storage(Arc::new(store.clone()))
+            .build();
+        let seed = Url::from_file_path(root.join("index.html")).unwrap();
+        crawler.add_seed(seed.clone()).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.pages_crawled, 3);
+        assert_eq!(stats.pages_failed, 1);
+        let setup = seed.join("guide/setup.html").unwrap();
+        assert_eq!(store.get_page(setup.as_str()).await.unwrap().unwrap().title.as_deref(), Some("Setup"));
+        assert!(!store.exists(seed.join("../secret.html").unwrap().as_str()).await.unwrap());
+        
+        // Off by default
+        let crawler = CrawlerBuilder::new().build();
+        assert!(crawler.add_seed(seed).await.is_err());
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_file_root_symlinked_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("docs");
+        std::fs::create_dir_all(root.join("leak")).unwrap();
+        std::fs::write(root.join("index.html"), r#"<title>Docs</title><a href="leak/">Leak</a>"#).unwrap();
+        std::fs::write(dir.path().join("secret.html"), "<title>Secret</title>").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("secret.html"), root.join("leak/index.html")).unwrap();
+        
+        let store = InMemoryStore::new();
+        let crawler = CrawlerBuilder::new()
+            .max_concurrent(1)
+            .allow_file_scheme(&root)
+            .storage(Arc::new(store.clone()))
+            .build();
+        let seed = Url::from_file_path(root.join("index.html")).unwrap();
+        crawler.add_seed(seed.clone()).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.pages_crawled, 1);
+        assert_eq!(stats.pages_failed, 1);
+        assert!(!store.exists(seed.join("leak/").unwrap().as_str()).await.unwrap());
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_auto_restrict_to_seeds() {
+        let server = MockServer::start();
+        let port = server.addr().port();
+        server.html(
+            "/",
+            &format!(
+                r#"<a href="http://www.b.test:{port}/b">b</a><a href="http://c.test:{port}/c">c</a>"#,
+                port = port
+            ),
+        );
+        server.html("/b", "<title>B</title>");
+        server.html("/c", "<title>C</title>");
+        
+        let mut builder = CrawlerBuilder::new().delay_ms(0).max_concurrent(1).auto_restrict_to_seeds(true);
+        for host in ["a.test", "b.test", "www.b.test", "c.test"] {
+            builder = builder.resolve_override(host, server.addr());
+        }
+        let crawler = builder.build();
+        crawler.add_seed(Url::parse(&format!("http://a.test:{}/", port)).unwrap()).await.unwrap();
+        crawler.add_seed(Url::parse(&format!("http://b.test:{}/", port)).unwrap()).await.unwrap();
+        crawler.crawl().await.unwrap();
+        
+        assert_eq!(server.hits("/b"), 1);
+        assert_eq!(server.hits("/c"), 0);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_max_query_variants() {
+        let server = MockServer::start();
+        server.html("/calendar", r#"<a href="/calendar?p=1">next</a>"#);
+        for p in 1..20 {
+            server.html(&format!("/calendar?p={}", p), &format!(r#"<a href="/calendar?p={}">next</a>"#, p + 1));
+        }
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .max_depth(50)
+            .max_query_variants(3)
+            .build();
+        crawler.add_seed(server.url("/calendar")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        let variants = server.requests().iter().filter(|r| r.path.starts_with("/calendar?")).count();
+        assert_eq!(variants, 3);
+        assert_eq!(server.hits("/calendar?p=4"), 0);
+        assert_eq!(stats.query_variants_dropped, 1);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_resume_from_checkpoint() {
+        let server = MockServer::start();
+        let links: String = (1..5).map(|i| format!(r#"<a href="/{}">{}</a>"#, i, i)).collect();
+        server.html("/", &links);
+        for i in 1..5 {
+            server.html(&format!("/{}", i), "<title>Page</title>");
+        }
+        let dir = tempfile::tempdir().unwrap();
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .max_pages(3)
+            .build()
+            .with_checkpointing(dir.path(), Duration::from_secs(60));
+        crawler.add_seed(server.url("/")).await.unwrap();
+        crawler.crawl().await.unwrap();
+        assert!(Checkpoint::path(dir.path()).exists());
+        assert!(!dir.path().join("checkpoint.json.tmp").exists());
+        
+        let resumed = CrawlerBuilder::new().delay_ms(0).max_concurrent(1).max_pages(10).build();
+        resumed.resume_from_checkpoint(dir.path()).await.unwrap();
+        resumed.add_seed(server.url("/")).await.unwrap();
+        let stats = resumed.crawl().await.unwrap();
+        
+        assert_eq!(stats.pages_crawled, 5);
+        for path in ["/", "/1", "/2", "/3", "/4"] {
+            assert_eq!(server.hits(path), 1, "{}", path);
+        }
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_per_seed_limits() {
+        let server = MockServer::start();
+        let port = server.addr().port();
+        let links: String = (1..6).map(|i| format!(r#"<a href="/{}">{}</a>"#, i, i)).collect();
+        server.html("/", &links);
+        for i in 1..6 {
+            server.html(&format!("/{}", i), "<title>Page</title>");
+        }
+        let spec = format!(
+            r#"[
+                {{"url": "http://a.test:{port}/", "max_pages": 3}},
+                {{"url": "http://b.test:{port}/", "max_depth": 0, "delay_ms": 50}}
+            ]"#,
+            port = port
+        );
+        let seeds: Vec<SeedConfig> = serde_json::from_str(&spec).unwrap();
+        
+        let store = InMemoryStore::new();
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .max_pages(20)
+            .resolve_override("a.test", server.addr())
+            .resolve_override("b.test", server.addr())
+            .seeds(seeds.clone())
+            .storage(Arc::new(store.clone()))
+            .build();
+        assert_eq!(crawler.add_configured_seeds().await.unwrap(), 2);
+        crawler.crawl().await.unwrap();
+        
+        let urls = store.all_urls().await.unwrap();
+        assert_eq!(urls.iter().filter(|url| url.contains("a.test")).count(), 3);
+        let on_b: Vec<&String> = urls.iter().filter(|url| url.contains("b.test")).collect();
+        assert_eq!(on_b, vec![&format!("http://b.test:{}/", port)]);
+        
+        // Overrides may not exceed the crawl-wide limits
+        let crawler = CrawlerBuilder::new().max_pages(2).seeds(seeds).build();
+        assert!(matches!(crawler.add_configured_seeds().await, Err(Error::ConfigError(_))));
+        assert_eq!(crawler.frontier.size().await, 0);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_record_and_replay() {
+        let server = MockServer::start();
+        server.route("/robots.txt", MockResponse::typed("text/plain", b"User-agent: *\nDisallow: /private"));
+        server.html("/", r#"<title>Home</title><a href="/a">a</a><a href="/gone">gone</a><a href="/private">p</a>"#);
+        server.html("/a", "<title>A</title>");
+        let dir = tempfile::tempdir().unwrap();
+        let cassette = dir.path().join("crawl.jsonl");
+        
+        let crawl = |builder: CrawlerBuilder| async {
+            let store = InMemoryStore::new();
+            let crawler = builder.delay_ms(0).max_concurrent(1).storage(Arc::new(store.clone())).build();
+            crawler.add_seed(server.url("/")).await.unwrap();
+            let stats = crawler.crawl().await.unwrap();
+            let mut pages: Vec<(String, Option<String>)> = Vec::new();
+            for url in store.all_urls().await.unwrap() {
+                let title = store.get_page(&url).await.unwrap().unwrap().title;
+                pages.push((url, title));
+            }
+            pages.sort();
+            (stats, pages)
+        };
+        
+        let (recorded_stats, recorded_pages) = crawl(CrawlerBuilder::new().record_to(&cassette)).await;
+        let requests = server.requests().len();
+        let (replayed_stats, replayed_pages) = crawl(CrawlerBuilder::new().replay_from(&cassette).unwrap()).await;
+        
+        assert_eq!(server.requests().len(), requests);
+        assert_eq!(replayed_pages, recorded_pages);
+        assert_eq!(recorded_pages.len(), 2);
+        assert_eq!(replayed_stats.pages_crawled, recorded_stats.pages_crawled);
+        assert_eq!(replayed_stats.pages_failed, 1);
+        assert_eq!(replayed_stats.robots_blocked, 1);
+        assert_eq!(replayed_stats.status_codes, recorded_stats.status_codes);
+        
+        assert!(CrawlerBuilder::new().replay_from(dir.path().join("missing.jsonl")).is_err());
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_attachment_skipped() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/report">report</a><a href="/page">page</a>"#);
+        server.route(
+            "/report",
+            MockResponse::html("<title>Report</title>").header("Content-Disposition", r#"Attachment; filename="report.html""#),
+        );
+        server.route("/page", MockResponse::html("<title>Page</title>").header("Content-Disposition", "inline"));
+        
+        let store = InMemoryStore::new();
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .storage(Arc::new(store.clone()))
+            .build();
+        let mut events = crawler.subscribe();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.pages_crawled, 2);
+        assert_eq!(stats.pages_failed, 0);
+        assert_eq!(stats.attachments_skipped, 1);
+        assert!(!store.exists(server.url("/report").as_str()).await.unwrap());
+        assert_eq!(
+            events.try_recv().unwrap(),
+            CrawlEvent::Skipped {
+                url: server.url("/report"),
+                reason: SkipReason::Attachment,
+            }
+        );
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_unwritable_index_fails_preflight() {
+        use std::os::unix::fs::PermissionsExt;
+        
+        let server = MockServer::start();
+        server.html("/", "<title>Home</title><p>kept</p>");
+        let dir = tempfile::tempdir().unwrap();
+        let indexer = Indexer::open(dir.path()).unwrap();
+        std::fs::set_permissions(dir.path(), std::fs::Permissions::from_mode(0o555)).unwrap();
+        
+        let crawler = CrawlerBuilder::new().delay_ms(0).indexer(indexer.clone()).build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        assert!(matches!(crawler.validate_outputs().await, Err(Error::IndexError(_))));
+        assert!(matches!(crawler.crawl().await, Err(Error::IndexError(_))));
+        assert_eq!(server.hits("/"), 0);
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .indexer(indexer)
+            .optional_outputs(true)
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        assert!(crawler.is_crawl_only());
+        assert_eq!(stats.pages_crawled, 1);
+        std::fs::set_permissions(dir.path(), std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mislabeled_binary_skipped() {
+        let mut image = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        image.extend((0..512u32).map(|i| (i % 7) as u8));
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/photo">photo</a><a href="/page">page</a>"#);
+        server.route("/photo", MockResponse::typed("text/html", &image));
+        server.html("/page", "<title>Page</title>");
+        
+        let store = InMemoryStore::new();
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .storage(Arc::new(store.clone()))
+            .build();
+        let mut events = crawler.subscribe();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.pages_crawled, 2);
+        assert_eq!(stats.pages_failed, 0);
+        assert_eq!(stats.binary_skipped, 1);
+        assert!(!store.exists(server.url("/photo").as_str()).await.unwrap());
+        assert_eq!(
+            events.try_recv().unwrap(),
+            CrawlEvent::Skipped {
+                url: server.url("/photo"),
+                reason: SkipReason::Binary,
+            }
+        );
+        
+        // Without the check the body is read as text
+        let crawler = CrawlerBuilder::new().delay_ms(0).binary_threshold(None).build();
+        crawler.add_seed(server.url("/photo")).await.unwrap();
+        assert_eq!(crawler.crawl().await.unwrap().binary_skipped, 0);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_sniffed_image_skipped() {
+        // Few enough control bytes to pass the binary check
+        let mut image = b"\x89PNG\r\n\x1a\n".to_vec();
+        image.extend(b"<html><body>not really a page</body></html>".repeat(20));
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/photo">photo</a>"#);
+        server.route("/photo", MockResponse::typed("text/html", &image));
+        
+        let crawler = CrawlerBuilder::new().delay_ms(0).max_concurrent(1).sniff_content(true).build();
+        let mut events = crawler.subscribe();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.pages_crawled, 1);
+        assert_eq!(stats.mime_mismatch_skipped, 1);
+        assert_eq!(stats.binary_skipped, 0);
+        assert_eq!(
+            events.try_recv().unwrap(),
+            CrawlEvent::Skipped {
+                url: server.url("/photo"),
+                reason: SkipReason::MimeMismatch,
+            }
+        );
+        
+        // Without sniffing it's fetched as a page, and fails to decode
+        let crawler = CrawlerBuilder::new().delay_ms(0).build();
+        crawler.add_seed(server.url("/photo")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        assert_eq!((stats.pages_failed, stats.mime_mismatch_skipped), (1, 0));
+    }
+    
+    #[test]
+    fn test_merge_stats() {
+        use chrono::TimeZone;
+        
+        let at = |minute| chrono::Utc.with_ymd_and_hms(2024, 6, 1, 12, minute, 0).unwrap();
+        let now = Instant::now();
+        let mut first = CrawlStats {
+            pages_crawled: 10,
+            pages_failed: 1,
+            robots_blocked: 2,
+            status_codes: BTreeMap::from([(200, 10), (404, 1)]),
+            start_time: Some(now),
+            end_time: Some(now + Duration::from_secs(60)),
+            started_at: Some(at(5)),
+            finished_at: Some(at(20)),
+            ..CrawlStats::default()
+        };
+        // Stats from another process keep only their wall-clock times
+        let json = serde_json::to_string(&CrawlStats {
+            pages_crawled: 5,
+            total_links_found: 40,
+            status_codes: BTreeMap::from([(200, 5), (500, 2)]),
+            start_time: Some(now),
+            started_at: Some(at(0)),
+            finished_at: Some(at(15)),
+            ..CrawlStats::default()
+        })
+        .unwrap();
+        let second: CrawlStats = serde_json::from_str(&json).unwrap();
+        assert_eq!(second.start_time, None);
+        
+        first.merge(&second);
+        assert_eq!(first.pages_crawled, 15);
+        assert_eq!(first.pages_failed, 1);
+        assert_eq!(first.total_links_found, 40);
+        assert_eq!(first.robots_blocked, 2);
+        assert_eq!(first.status_codes, BTreeMap::from([(200, 15), (404, 1), (500, 2)]));
+        assert_eq!(first.started_at, Some(at(0)));
+        assert_eq!(first.finished_at, Some(at(20)));
+        assert_eq!(first.start_time, Some(now));
+        assert_eq!(first.duration(), Some(Duration::from_secs(20 * 60)));
+        
+        let mut empty = CrawlStats::default();
+        empty.merge(&first);
+        assert_eq!(empty, first);
+    }
+    
+    /// Plain first-in, first-out frontier without limits or scoping
+    #[derive(Default)]
+    struct FifoFrontier {
+        queue: std::sync::Mutex<std::collections::VecDeque<CrawlTask>>,
+        seen: std::sync::Mutex<HashSet<String>>,
+        popped: std::sync::Mutex<Vec<String>>,
+    }
+    
+    #[async_trait::async_trait]
+    impl Frontier for FifoFrontier {
+        async fn push(&self, task: CrawlTask) -> bool {
+            if !self.seen.lock().unwrap().insert(task.url.to_string()) {
+                return false;
+            }
+            self.queue.lock().unwrap().push_back(task);
+            true
+        }
+        
+        async fn pop(&self) -> Option<CrawlTask> {
+            let task = self.queue.lock().unwrap().pop_front()?;
+            self.popped.lock().unwrap().push(task.url.path().to_string());
+            Some(task)
+        }
+        
+        async fn size(&self) -> usize {
+            self.queue.lock().unwrap().len()
+        }
+        
+        async fn mark_seen(&self, url: &Url) {
+            self.seen.lock().unwrap().insert(url.to_string());
+        }
+        
+        async fn has_seen(&self, url: &Url) -> bool {
+            self.seen.lock().unwrap().contains(url.as_str())
+        }
+        
+        async fn retry(&self, mut task: CrawlTask) -> bool {
+            task.retry_count += 1;
+            self.queue.lock().unwrap().push_back(task);
+            true
+        }
+        
+        async fn stats(&self) -> crate::crawler::frontier::FrontierStats {
+            let seen_count = self.seen.lock().unwrap().len();
+            crate::crawler::frontier::FrontierStats {
+                queue_size: self.size().await,
+                seen_count,
+                max_size: usize::MAX,
+                discovered_count: seen_count,
+                max_urls_discovered: None,
+            }
+        }
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_crawl_with_custom_frontier() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/b">b</a><a href="/a">a</a>"#);
+        server.html("/a", r#"<a href="/">home</a><a href="/c">c</a>"#);
+        server.html("/b", "<title>B</title>");
+        server.html("/c", "<title>C</title>");
+        
+        let frontier = Arc::new(FifoFrontier::default());
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .frontier(frontier.clone())
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.pages_crawled, 4);
+        assert_eq!(*frontier.popped.lock().unwrap(), vec!["/", "/b", "/a", "/c"]);
+        assert!(crawler.frontier.has_seen(&server.url("/c")).await);
+        assert_eq!(crawler.report().await.seen_count, 4);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_finished_is_last_event() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/private">private</a><a href="/1">1</a><a href="/2">2</a>"#);
+        server.route("/robots.txt", MockResponse::typed("text/plain", b"User-agent: *\nDisallow: /private"));
+        
+        // Stopped by the page limit
+        let crawler = CrawlerBuilder::new().delay_ms(0).max_concurrent(1).max_pages(2).build();
+        let mut events = crawler.subscribe();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        let received: Vec<CrawlEvent> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+        assert!(received.len() > 1);
+        assert_eq!(received.last(), Some(&CrawlEvent::Finished { stats }));
+        
+        // Cancelled before any page was fetched
+        let crawler = CrawlerBuilder::new().delay_ms(0).build();
+        let mut events = crawler.subscribe();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        crawler.cancel();
+        let stats = crawler.crawl().await.unwrap();
+        assert_eq!(stats.pages_crawled, 0);
+        assert_eq!(events.try_recv().unwrap(), CrawlEvent::Finished { stats });
+        assert!(events.try_recv().is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_add_seeds_from_reader() {
+        let input = "https://a.com/\n\nnot a url\n  https://b.com/page  \nftp://c.com/\nhttps://a.com/\n";
+        let crawler = Crawler::new(CrawlerConfig::default());
+        
+        let added = crawler.add_seeds_from_reader(std::io::Cursor::new(input)).await.unwrap();
+        
+        assert_eq!(added, 3);
+        assert_eq!(crawler.frontier.size().await, 2);
+        assert!(crawler.frontier.has_seen(&Url::parse("https://a.com/").unwrap()).await);
+        assert!(crawler.frontier.has_seen(&Url::parse("https://b.com/page").unwrap()).await);
+    }
+    
+    #[test]
+    fn test_politeness_profiles() {
+        let cases = [
+            (PolitenessProfile::Gentle, 2000, 2, 25.0, 1, 1),
+            (PolitenessProfile::Balanced, 1000, 10, 10.0, 1, 4),
+            (PolitenessProfile::Aggressive, 250, 32, 0.0, 2, 16),
+        ];
+        for (profile, delay_ms, max_concurrent, jitter, initial_limit, max_limit) in cases {
+            let config = CrawlerBuilder::new().politeness(profile).config;
+            assert_eq!(config.delay_ms, delay_ms, "{:?}", profile);
+            assert_eq!(config.max_concurrent, max_concurrent, "{:?}", profile);
+            assert_eq!(config.delay_jitter_percent, jitter, "{:?}", profile);
+            let slow_start = config.slow_start.unwrap();
+            assert_eq!((slow_start.initial_limit, slow_start.max_limit), (initial_limit, max_limit));
+        }
+        
+        let config = CrawlerBuilder::new()
+            .politeness(PolitenessProfile::Gentle)
+            .delay_ms(500)
+            .config;
+        assert_eq!(config.delay_ms, 500);
+        assert_eq!(config.max_concurrent, 2);
+    }
+    
+    #[tokio::test]
+    async fn test_reloaded_delay_applies_to_next_request() {
+        let crawler = CrawlerBuilder::new().delay_ms(0).build();
+        let url = Url::parse("https://example.com/").unwrap();
+        
+        crawler.apply_rate_limit(&url).await.unwrap();
+        let start = Instant::now();
+        crawler.apply_rate_limit(&url).await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(100));
+        
+        crawler
+            .reload(&ReloadableConfig {
+                delay_ms: Some(200),
+                ..ReloadableConfig::default()
+            })
+            .await;
+        let start = Instant::now();
+        crawler.apply_rate_limit(&url).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(190));
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_referer_is_source_page() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/child#section">child</a>"#);
+        server.html("/child", "<title>Child</title>");
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .send_referer(ReferrerPolicy::Strict)
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        crawler.crawl().await.unwrap();
+        
+        let requests = server.requests();
+        let referer = |path: &str| {
+            let request = requests.iter().find(|r| r.path == path).unwrap();
+            request.header("Referer").map(str::to_string)
+        };
+        assert_eq!(referer("/"), None);
+        assert_eq!(referer("/child"), Some(server.url("/").to_string()));
+    }
+    
+    #[tokio::test]
+    async fn test_crawl_with_mock_fetcher() {
+        use crate::crawler::MockFetcher;
+        
+        let page = |path: &str| Url::parse("http://graph.test").unwrap().join(path).unwrap();
+        let fetcher = Arc::new(
+            MockFetcher::new()
+                .html(&page("/"), r#"<a href="/a">A</a><a href="/b">B</a>"#)
+                .html(&page("/a"), r#"<title>A</title><a href="/b">B</a><a href="/c">C</a>"#)
+                .html(&page("/b"), r#"<title>B</title><a href="/">Home</a>"#)
+                .html(&page("/c"), "<title>C</title>"),
+        );
+        
+        let store = Arc::new(InMemoryStore::new());
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .fetcher(fetcher.clone())
+            .storage(store.clone())
+            .build();
+        crawler.add_seed(page("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.pages_crawled, 4);
+        let mut urls = store.all_urls().await.unwrap();
+        urls.sort();
+        assert_eq!(urls, ["http://graph.test/", "http://graph.test/a", "http://graph.test/b", "http://graph.test/c"]);
+        assert!(fetcher.requests().contains(&page("/robots.txt")));
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_max_parse_size_truncates_large_pages() {
+        let server = MockServer::start();
+        let filler = "<p>filler text</p>".repeat(500);
+        server.html("/", &format!(r#"<a href="/early">early</a>{}<a href="/late">late</a>"#, filler));
+        server.html("/early", "<title>Early</title>");
+        server.html("/late", "<title>Late</title>");
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .max_parse_size(1024)
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.pages_parse_truncated, 1);
+        assert_eq!(server.hits("/early"), 1);
+        assert_eq!(server.hits("/late"), 0);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_parse_timeout_skips_slow_pages() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/nested">nested</a><a href="/next">next</a>"#);
+        // Deep nesting makes the HTML parser slow down quadratically
+        server.html("/nested", &"<div>".repeat(3000));
+        server.html("/next", "<title>Next</title>");
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .parse_timeout(Duration::from_millis(50))
+            .build();
+        let mut events = crawler.subscribe();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        
+        assert_eq!(stats.parse_timeouts, 1);
+        assert_eq!(stats.pages_crawled, 2);
+        assert_eq!(server.hits("/next"), 1);
+        assert_eq!(
+            events.try_recv().unwrap(),
+            CrawlEvent::Skipped {
+                url: server.url("/nested"),
+                reason: SkipReason::ParseTimeout,
+            }
+        );
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_domain_stats() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/page">page</a><a href="/missing">missing</a>"#);
+        server.html("/page", "<title>Page</title>");
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .resolve_override("a.test", server.addr())
+            .resolve_override("b.test", server.addr())
+            .build();
+        let port = server.addr().port();
+        crawler.add_seed(Url::parse(&format!("http://a.test:{}/", port)).unwrap()).await.unwrap();
+        crawler.add_seed(Url::parse(&format!("http://b.test:{}/page", port)).unwrap()).await.unwrap();
+        crawler.crawl().await.unwrap();
+        
+        let stats = crawler.domain_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats["a.test"].pages_crawled, 2);
+        assert_eq!(stats["a.test"].pages_failed, 1);
+        assert_eq!(stats["b.test"].pages_crawled, 1);
+        assert_eq!(stats["b.test"].pages_failed, 0);
+        assert_eq!(stats["b.test"].bytes, "<title>Page</title>".len() as u64);
+        assert!(stats["a.test"].avg_latency().is_some());
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_extend_frontier_from_store() {
+        let server = MockServer::start();
+        server.html("/", r#"<a href="/a">a</a><a href="/b">b</a>"#);
+        server.html("/a", r#"<a href="/">home</a>"#);
+        server.html("/b", "<title>B</title>");
+        let store = InMemoryStore::new();
+        
+        // The first crawl stops after the home page, leaving /a and /b discovered
+        let first = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_pages(1)
+            .max_concurrent(1)
+            .storage(Arc::new(store.clone()))
+            .build();
+        first.add_seed(server.url("/")).await.unwrap();
+        first.crawl().await.unwrap();
+        
+        let second = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_concurrent(1)
+            .storage(Arc::new(store.clone()))
+            .build();
+        assert_eq!(second.extend_frontier_from_store().await.unwrap(), 2);
+        let stats = second.crawl().await.unwrap();
+        
+        // /a links back home, which was crawled already
+        assert_eq!(stats.pages_crawled, 2);
+        assert_eq!(server.hits("/"), 1);
+        assert_eq!(server.hits("/a"), 1);
+        assert_eq!(server.hits("/b"), 1);
+        assert!(store.uncrawled_discovered().await.unwrap().is_empty());
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_gone_pages_removed_from_index() {
+        let server = MockServer::start();
+        server.route("/old", MockResponse::html("").status(410));
+        
+        let indexer = Indexer::in_memory().unwrap();
+        indexer
+            .add_document(&IndexDocument {
+                url: server.url("/old").to_string(),
+                title: None,
+                body: "stale content".to_string(),
+                content_type: None,
+                language: None,
+                description: None,
+                image: None,
+                crawled_at: chrono::Utc::now(),
+                published_at: None,
+                tags: Default::default(),
+            })
+            .unwrap();
+        indexer.commit().unwrap();
+        
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .indexer(indexer.clone())
+            .build();
+        crawler.add_seed(server.url("/old")).await.unwrap();
+        let stats = crawler.crawl().await.unwrap();
+        assert_eq!(stats.pages_failed, 1);
+        
+        let searcher = Searcher::for_indexer(&indexer).unwrap();
+        searcher.reload().unwrap();
+        assert_eq!(searcher.search(&SearchQuery::new("stale")).unwrap().total, 0);
+    }
+    
+    /// Layer that records the fields of every `crawl_task` span
+    #[derive(Clone, Default)]
+    struct SpanFieldCapture {
+        fields: Arc<std::sync::Mutex<HashMap<String, String>>>,
+    }
+    
+    impl tracing::field::Visit for SpanFieldCapture {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.fields.lock().unwrap().insert(field.name().to_string(), value.to_string());
+        }
+        
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.fields.lock().unwrap().insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+    
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for SpanFieldCapture {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            _id: &tracing::span::Id,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            if attrs.metadata().name() == "crawl_task" {
+                attrs.record(&mut self.clone());
+            }
+        }
+        
+        fn on_record(
+            &self,
+            _id: &tracing::span::Id,
+            values: &tracing::span::Record<'_>,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            values.record(&mut self.clone());
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_crawl_task_span_fields() {
+        use tracing_subscriber::layer::SubscriberExt;
+        
+        let server = MockServer::start();
+        server.html("/", "<title>Home</title>");
+        
+        let capture = SpanFieldCapture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+        
+        let crawler = CrawlerBuilder::new()
+            .max_concurrent(1)
+            .delay_ms(0)
+            .build();
+        crawler.add_seed(server.url("/")).await.unwrap();
+        crawler.crawl().await.unwrap();
+        
+        let fields = capture.fields.lock().unwrap();
+        assert_eq!(fields.get("url").unwrap(), &server.url("/").to_string());
+        assert_eq!(fields.get("depth").unwrap(), "0");
+        assert_eq!(fields.get("worker_id").unwrap(), "0");
+        assert_eq!(fields.get("domain").unwrap(), "localhost");
+        assert!(fields.contains_key("fetch_ms"));
+        assert_eq!(fields.get("body_bytes").unwrap(), "19");
+    }
+}
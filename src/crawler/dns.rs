@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long answers are reused when rate limiting by IP address and no
+/// cache TTL is configured, so each task doesn't resolve its host twice
+pub const DEFAULT_DNS_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Resolves a `host:port` pair to socket addresses
+pub type LookupFn = dyn Fn(&str) -> io::Result<Vec<SocketAddr>> + Send + Sync;
+
+/// Name resolution for the fetcher's HTTP client: fixed overrides first,
+/// then an optional cache of earlier answers, then the system resolver
+/// with an optional cap on lookups in flight, so a crawl spreading over
+/// thousands of hosts doesn't flood the resolver. Clones share the cache
+/// and the cap.
+#[derive(Clone)]
+pub struct DnsResolver {
+    overrides: Arc<HashMap<String, SocketAddr>>,
+    cache: Option<DnsCache>,
+    slots: Option<Arc<LookupSlots>>,
+    lookup: Arc<LookupFn>,
+}
+
+/// Addresses of a `host:port` pair and when they were resolved
+type CachedAddrs = (Vec<SocketAddr>, Instant);
+
+/// Answers kept for `ttl`, keyed by `host:port`
+#[derive(Clone)]
+struct DnsCache {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<String, CachedAddrs>>>,
+}
+
+/// Counting semaphore for blocking lookups
+struct LookupSlots {
+    max: usize,
+    in_use: Mutex<usize>,
+    freed: Condvar,
+}
+
+impl LookupSlots {
+    fn acquire(&self) -> LookupSlot<'_> {
+        let mut in_use = self.in_use.lock().unwrap();
+        while *in_use >= self.max {
+            in_use = self.freed.wait(in_use).unwrap();
+        }
+        *in_use += 1;
+        LookupSlot(self)
+    }
+}
+
+struct LookupSlot<'a>(&'a LookupSlots);
+
+impl Drop for LookupSlot<'_> {
+    fn drop(&mut self) {
+        *self.0.in_use.lock().unwrap() -= 1;
+        self.0.freed.notify_one();
+    }
+}
+
+impl DnsResolver {
+    /// Resolver using the system resolver, without caching or a lookup cap
+    pub fn new() -> Self {
+        Self::with_lookup(|netloc| netloc.to_socket_addrs().map(|addrs| addrs.collect()))
+    }
+
+    /// Resolver asking `lookup` for names that aren't overridden or cached
+    pub fn with_lookup<F>(lookup: F) -> Self
+    where
+        F: Fn(&str) -> io::Result<Vec<SocketAddr>> + Send + Sync + 'static,
+    {
+        Self {
+            overrides: Arc::new(HashMap::new()),
+            cache: None,
+            slots: None,
+            lookup: Arc::new(lookup),
+        }
+    }
+
+    /// Resolve these lowercase host names to fixed addresses
+    pub fn overrides(mut self, overrides: HashMap<String, SocketAddr>) -> Self {
+        self.overrides = Arc::new(overrides);
+        self
+    }
+
+    /// Reuse successful answers for `ttl` instead of resolving again
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache = Some(DnsCache {
+            ttl,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        });
+        self
+    }
+
+    /// Run at most `max` lookups at a time; further lookups wait
+    pub fn max_concurrent(mut self, max: usize) -> Self {
+        self.slots = Some(Arc::new(LookupSlots {
+            max: max.max(1),
+            in_use: Mutex::new(0),
+            freed: Condvar::new(),
+        }));
+        self
+    }
+
+    /// Resolve a `host:port` pair
+    pub fn resolve(&self, netloc: &str) -> io::Result<Vec<SocketAddr>> {
+        let host = netloc.rsplit_once(':').map_or(netloc, |(host, _)| host);
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if let Some(addr) = self.overrides.get(&host.to_lowercase()) {
+            return Ok(vec![*addr]);
+        }
+
+        let key = netloc.to_lowercase();
+        if let Some(cache) = &self.cache {
+            if let Some((addrs, resolved_at)) = cache.entries.lock().unwrap().get(&key) {
+                if resolved_at.elapsed() < cache.ttl {
+                    return Ok(addrs.clone());
+                }
+            }
+        }
+
+        let addrs = {
+            let _slot = self.slots.as_ref().map(|slots| slots.acquire());
+            (self.lookup)(netloc)?
+        };
+        if let Some(cache) = &self.cache {
+            let mut entries = cache.entries.lock().unwrap();
+            entries.retain(|_, (_, resolved_at)| resolved_at.elapsed() < cache.ttl);
+            entries.insert(key, (addrs.clone(), Instant::now()));
+        }
+        Ok(addrs)
+    }
+}
+
+impl std::fmt::Debug for DnsResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DnsResolver")
+            .field("overrides", &self.overrides)
+            .field("cache_ttl", &self.cache.as_ref().map(|cache| cache.ttl))
+            .field("max_concurrent", &self.slots.as_ref().map(|slots| slots.max))
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for DnsResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ureq::Resolver for DnsResolver {
+    fn resolve(&self, netloc: &str) -> io::Result<Vec<SocketAddr>> {
+        DnsResolver::resolve(self, netloc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn counting_resolver(lookups: Arc<AtomicUsize>) -> DnsResolver {
+        DnsResolver::with_lookup(move |_| {
+            lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(vec!["127.0.0.1:80".parse().unwrap()])
+        })
+    }
+
+    #[test]
+    fn test_cache_resolves_once_within_ttl() {
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let resolver = counting_resolver(lookups.clone()).cache_ttl(Duration::from_millis(100));
+
+        for _ in 0..3 {
+            assert_eq!(resolver.resolve("Example.test:80").unwrap().len(), 1);
+        }
+        resolver.resolve("example.test:80").unwrap();
+        assert_eq!(lookups.load(Ordering::SeqCst), 1);
+        resolver.resolve("other.test:80").unwrap();
+        assert_eq!(lookups.load(Ordering::SeqCst), 2);
+
+        std::thread::sleep(Duration::from_millis(150));
+        resolver.resolve("example.test:80").unwrap();
+        assert_eq!(lookups.load(Ordering::SeqCst), 3);
+
+        // Without a cache every request resolves again
+        let uncached = counting_resolver(lookups.clone());
+        uncached.resolve("example.test:80").unwrap();
+        uncached.resolve("example.test:80").unwrap();
+        assert_eq!(lookups.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn test_concurrent_lookups_are_capped() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let resolver = {
+            let (active, peak) = (active.clone(), peak.clone());
+            DnsResolver::with_lookup(move |_| {
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(20));
+                active.fetch_sub(1, Ordering::SeqCst);
+                Ok(vec!["127.0.0.1:80".parse().unwrap()])
+            })
+            .max_concurrent(2)
+        };
+
+        let threads: Vec<_> = (0..6)
+            .map(|i| {
+                let resolver = resolver.clone();
+                std::thread::spawn(move || resolver.resolve(&format!("host{}.test:80", i)).unwrap())
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[test]
+    fn test_overrides_skip_lookup() {
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let addr: SocketAddr = "10.0.0.1:8080".parse().unwrap();
+        let resolver = counting_resolver(lookups.clone()).overrides(HashMap::from([("example.test".to_string(), addr)]));
+        assert_eq!(resolver.resolve("EXAMPLE.test:443").unwrap(), vec![addr]);
+        assert_eq!(lookups.load(Ordering::SeqCst), 0);
+    }
+}
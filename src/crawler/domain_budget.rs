@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+/// Splits the crawl's page budget between domains by weight.
+///
+/// Each weighted domain may be crawled for `max_pages * weight / total_weight`
+/// pages. A weight applies to the domain and its subdomains. Domains without
+/// a weight are not limited.
+#[derive(Debug)]
+pub struct DomainBudget {
+    /// Pages each weighted domain may use
+    limits: HashMap<String, usize>,
+    /// Pages dispatched so far per weighted domain
+    used: HashMap<String, usize>,
+}
+
+impl DomainBudget {
+    pub fn new(max_pages: usize, weights: &HashMap<String, f32>) -> Self {
+        let total: f32 = weights.values().filter(|w| w.is_finite() && **w > 0.0).sum();
+        let limits = weights
+            .iter()
+            .map(|(domain, weight)| {
+                let share = if total > 0.0 && weight.is_finite() { weight.max(0.0) / total } else { 0.0 };
+                (domain.to_lowercase(), (max_pages as f32 * share).round() as usize)
+            })
+            .collect();
+
+        Self {
+            limits,
+            used: HashMap::new(),
+        }
+    }
+
+    /// Page limit of the weighted domain covering `host`, if any
+    pub fn limit(&self, host: &str) -> Option<usize> {
+        self.budget_key(host).map(|key| self.limits[key])
+    }
+
+    /// Take one page from the budget of the domain covering `host`.
+    /// Returns false when that domain has used up its share.
+    pub fn try_acquire(&mut self, host: &str) -> bool {
+        let Some(key) = self.budget_key(host) else {
+            return true;
+        };
+        let limit = self.limits[key];
+        let used = self.used.entry(key.to_string()).or_default();
+        if *used >= limit {
+            return false;
+        }
+        *used += 1;
+        true
+    }
+
+    /// Give back a page taken for `host` that was not crawled after all
+    pub fn release(&mut self, host: &str) {
+        let Some(key) = self.budget_key(host).map(str::to_string) else {
+            return;
+        };
+        if let Some(used) = self.used.get_mut(&key) {
+            *used = used.saturating_sub(1);
+        }
+    }
+
+    /// Most specific weighted domain that `host` equals or is a subdomain of
+    fn budget_key(&self, host: &str) -> Option<&str> {
+        let host = host.to_lowercase();
+        self.limits
+            .keys()
+            .filter(|domain| host == **domain || host.ends_with(&format!(".{}", domain)))
+            .max_by_key(|domain| domain.len())
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shares_follow_weights() {
+        let weights = HashMap::from([("a.com".to_string(), 7.0), ("b.com".to_string(), 3.0)]);
+        let mut budget = DomainBudget::new(100, &weights);
+
+        assert_eq!(budget.limit("a.com"), Some(70));
+        assert_eq!(budget.limit("www.b.com"), Some(30));
+        assert_eq!(budget.limit("c.com"), None);
+
+        let granted = (0..50).filter(|_| budget.try_acquire("docs.b.com")).count();
+        assert_eq!(granted, 30);
+        assert!(budget.try_acquire("a.com"));
+        assert!(budget.try_acquire("unweighted.org"));
+
+        budget.release("b.com");
+        assert!(budget.try_acquire("b.com"));
+        assert!(!budget.try_acquire("b.com"));
+    }
+}
@@ -0,0 +1,91 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Bucket that collects domains beyond the tracking cap
+pub const OTHER_DOMAINS: &str = "other";
+
+/// Default number of domains tracked individually
+pub const DEFAULT_MAX_TRACKED_DOMAINS: usize = 1000;
+
+/// Fetch statistics of a single domain
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DomainStats {
+    pub pages_crawled: usize,
+    pub pages_failed: usize,
+    /// Body bytes downloaded from successful fetches
+    pub bytes: u64,
+    /// Time spent in requests, successful or not
+    pub total_latency: Duration,
+}
+
+impl DomainStats {
+    /// Mean request latency, if any request was made
+    pub fn avg_latency(&self) -> Option<Duration> {
+        let requests = self.pages_crawled + self.pages_failed;
+        (requests > 0).then(|| self.total_latency / requests as u32)
+    }
+}
+
+/// Per-domain statistics with a bounded number of entries: once
+/// `max_domains` domains are tracked, new ones are added up under
+/// [`OTHER_DOMAINS`].
+#[derive(Debug)]
+pub struct DomainStatsTracker {
+    max_domains: usize,
+    domains: HashMap<String, DomainStats>,
+}
+
+impl DomainStatsTracker {
+    pub fn new(max_domains: usize) -> Self {
+        Self {
+            max_domains,
+            domains: HashMap::new(),
+        }
+    }
+
+    /// Record the outcome of one request
+    pub fn record(&mut self, domain: &str, success: bool, bytes: usize, latency: Duration) {
+        let key = if self.domains.contains_key(domain) || self.domains.len() < self.max_domains {
+            domain
+        } else {
+            OTHER_DOMAINS
+        };
+        let stats = self.domains.entry(key.to_string()).or_default();
+        if success {
+            stats.pages_crawled += 1;
+            stats.bytes += bytes as u64;
+        } else {
+            stats.pages_failed += 1;
+        }
+        stats.total_latency += latency;
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, DomainStats> {
+        self.domains.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_tail_goes_to_other() {
+        let mut tracker = DomainStatsTracker::new(2);
+        tracker.record("a.com", true, 100, Duration::from_millis(10));
+        tracker.record("b.com", false, 0, Duration::from_millis(30));
+        tracker.record("c.com", true, 50, Duration::from_millis(20));
+        tracker.record("a.com", true, 100, Duration::from_millis(30));
+        tracker.record("d.com", true, 50, Duration::from_millis(20));
+
+        let stats = tracker.snapshot();
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats["a.com"].pages_crawled, 2);
+        assert_eq!(stats["a.com"].bytes, 200);
+        assert_eq!(stats["a.com"].avg_latency(), Some(Duration::from_millis(20)));
+        assert_eq!(stats["b.com"].pages_failed, 1);
+        assert_eq!(stats[OTHER_DOMAINS].pages_crawled, 2);
+        assert_eq!(DomainStats::default().avg_latency(), None);
+    }
+}
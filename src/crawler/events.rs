@@ -0,0 +1,46 @@
+use crate::crawler::CrawlStats;
+use serde::Serialize;
+use url::Url;
+
+/// Events buffered per subscriber before the oldest are dropped
+pub const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Something notable that happened during a crawl, delivered to
+/// [`Crawler::subscribe`](crate::crawler::Crawler::subscribe)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CrawlEvent {
+    /// A URL was dropped or taken from the frontier without being fetched
+    Skipped { url: Url, reason: SkipReason },
+    /// A redirect was recorded instead of followed, with `audit_redirects`
+    Redirected { url: Url, status: u16, target: Option<Url> },
+    /// The crawl stopped, for any reason. Always the last event of a crawl.
+    Finished { stats: CrawlStats },
+}
+
+/// Why a URL was not fetched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The site's robots.txt disallows the URL
+    RobotsDisallowed,
+    /// The URL's host is not on the allowed domains
+    DomainNotAllowed,
+    /// The URL redirects off the crawl's sites or allowed domains
+    OutOfScope,
+    /// The URL is on a new host and the crawl already reached `max_unique_hosts`
+    HostLimit,
+    /// The URL's path already had `max_query_variants` query strings queued
+    QueryVariantLimit,
+    /// The response was a download, sent with `Content-Disposition: attachment`
+    Attachment,
+    /// The response body looked binary despite its content type
+    Binary,
+    /// The response body's magic bytes showed a format other than its
+    /// content type, with `sniff_content`
+    MimeMismatch,
+    /// Parsing the page took longer than `parse_timeout`
+    ParseTimeout,
+    /// The stored copy of the page was crawled after `crawl_since`
+    Fresh,
+}
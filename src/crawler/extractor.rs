@@ -0,0 +1,82 @@
+use crate::common::error::Result;
+use crate::crawler::parser::looks_like_fragment;
+use crate::crawler::{ParsedPage, Parser};
+use std::sync::Arc;
+use url::Url;
+
+/// Turns a fetched body into a [`ParsedPage`]
+pub trait ContentExtractor: Send + Sync {
+    fn extract(&self, body: &str, url: &Url) -> Result<ParsedPage>;
+}
+
+/// Bodies that look like fragments, such as AJAX responses, are parsed
+/// with [`Parser::parse_fragment`]
+impl ContentExtractor for Parser {
+    fn extract(&self, body: &str, url: &Url) -> Result<ParsedPage> {
+        if looks_like_fragment(body) {
+            self.parse_fragment(body, url)
+        } else {
+            self.parse(body, url)
+        }
+    }
+}
+
+/// Content extractors keyed by MIME type.
+///
+/// Responses whose type has no registered extractor, or that have no
+/// content type at all, go to the HTML parser.
+#[derive(Clone)]
+pub struct ExtractorRegistry {
+    extractors: Vec<(String, Arc<dyn ContentExtractor>)>,
+    html: Arc<dyn ContentExtractor>,
+}
+
+impl ExtractorRegistry {
+    pub fn new() -> Self {
+        Self {
+            extractors: Vec::new(),
+            html: Arc::new(Parser::new()),
+        }
+    }
+
+    /// Use `extractor` for responses of `mime_type` (`application/pdf`),
+    /// replacing any extractor registered for it before
+    pub fn register(&mut self, mime_type: &str, extractor: Arc<dyn ContentExtractor>) {
+        let mime_type = mime_type.trim().to_lowercase();
+        self.extractors.retain(|(registered, _)| *registered != mime_type);
+        self.extractors.push((mime_type, extractor));
+    }
+
+    /// Use `extractor` for responses without a registered extractor,
+    /// instead of the default HTML parser
+    pub fn set_html(&mut self, extractor: Arc<dyn ContentExtractor>) {
+        self.html = extractor;
+    }
+
+    /// MIME types with a registered extractor
+    pub fn mime_types(&self) -> impl Iterator<Item = &str> {
+        self.extractors.iter().map(|(mime_type, _)| mime_type.as_str())
+    }
+
+    /// Extractor for a `Content-Type` header value
+    pub fn get(&self, content_type: Option<&str>) -> &dyn ContentExtractor {
+        let mime_type = content_type
+            .and_then(|ct| ct.split(';').next())
+            .map(|mime_type| mime_type.trim().to_lowercase());
+        self.extractors
+            .iter()
+            .find(|(registered, _)| Some(registered) == mime_type.as_ref())
+            .map_or(self.html.as_ref(), |(_, extractor)| extractor.as_ref())
+    }
+
+    /// Extract a page with the extractor for its content type
+    pub fn extract(&self, content_type: Option<&str>, body: &str, url: &Url) -> Result<ParsedPage> {
+        self.get(content_type).extract(body, url)
+    }
+}
+
+impl Default for ExtractorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
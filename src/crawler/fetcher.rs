@@ -1,16 +1,219 @@
 use crate::common::error::{Error, Result};
-use std::io::Read;
-use std::time::Duration;
+use crate::crawler::{Body, BufferPool, DnsResolver, ResponseCache};
+use bytes::{BufMut, Bytes};
+use async_trait::async_trait;
+use flate2::read::GzDecoder;
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use rand::Rng;
+use std::time::{Duration, Instant};
 use url::Url;
+use serde::{Deserialize, Serialize};
+
+/// Content types accepted for crawlable pages
+pub const HTML_CONTENT_TYPES: &[&str] = &["text/html", "text/plain"];
+
+/// Content types accepted for sitemaps
+pub const XML_CONTENT_TYPES: &[&str] = &[
+    "application/xml",
+    "text/xml",
+    "text/plain",
+    "application/gzip",
+    "application/x-gzip",
+];
+
+/// Content types accepted for JSON API pages
+pub const JSON_CONTENT_TYPES: &[&str] = &["application/json", "text/json", "+json", "text/plain"];
+
+/// First bytes of a gzip stream
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
 
 /// Response from fetching a URL
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FetchResponse {
     pub url: Url,
     pub status_code: u16,
     pub content_type: Option<String>,
-    pub body: String,
+    pub body: Body,
     pub headers: Vec<(String, String)>,
+    /// The body was cut off at the fetcher's `max_size`
+    pub truncated: bool,
+    /// Time from sending the request to reading the end of the body
+    pub elapsed: Duration,
+    /// Bytes of body read, before decoding to text
+    pub body_bytes: usize,
+}
+
+impl FetchResponse {
+    /// Where a 3xx response points, from its `Location` header resolved
+    /// against its URL. Only returned by fetchers auditing redirects.
+    pub fn redirect_target(&self) -> Option<Url> {
+        if !(300..400).contains(&self.status_code) {
+            return None;
+        }
+        let (_, location) = self
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("location"))?;
+        self.url.join(location.trim()).ok()
+    }
+}
+
+/// Source of HTTP responses for the crawler, so tests can swap the
+/// network out for canned responses
+#[async_trait]
+pub trait HttpFetcher: Send + Sync {
+    /// Fetch a URL, accepting only responses whose content type contains
+    /// one of `content_types`
+    async fn fetch_accepting(&self, url: &Url, content_types: &[&str]) -> Result<FetchResponse>;
+    
+    /// Fetch a URL like [`HttpFetcher::fetch_accepting`], sending `referer`
+    /// as the `Referer` header. Fetchers without headers ignore it.
+    async fn fetch_with_referer(
+        &self,
+        url: &Url,
+        content_types: &[&str],
+        _referer: Option<&Url>,
+    ) -> Result<FetchResponse> {
+        self.fetch_accepting(url, content_types).await
+    }
+    
+    /// Fetch a URL as a crawlable page
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse> {
+        self.fetch_accepting(url, HTML_CONTENT_TYPES).await
+    }
+}
+
+/// Default number of redirects followed for a single fetch
+pub const DEFAULT_MAX_REDIRECTS: u32 = 5;
+
+/// Decides whether a redirect may be followed to a URL
+pub type RedirectFilter = Arc<dyn Fn(&Url) -> bool + Send + Sync>;
+
+/// How the fetcher picks from several user agents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UserAgentRotation {
+    /// Each agent in turn
+    #[default]
+    RoundRobin,
+    /// A random agent for every request
+    Random,
+}
+
+/// When the crawler sends a `Referer` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReferrerPolicy {
+    /// Always send the page a link was found on
+    #[default]
+    Always,
+    /// Only send it to the page's own origin, so the referring URL never
+    /// leaks to other sites
+    Strict,
+}
+
+impl ReferrerPolicy {
+    /// `Referer` to send when following a link from `from` to `to`, without
+    /// the fragment or any credentials
+    pub fn referer(&self, from: &Url, to: &Url) -> Option<Url> {
+        if *self == ReferrerPolicy::Strict && from.origin() != to.origin() {
+            return None;
+        }
+        let mut referer = from.clone();
+        referer.set_fragment(None);
+        let _ = referer.set_username("");
+        let _ = referer.set_password(None);
+        Some(referer)
+    }
+}
+
+/// Configuration for the HTTP fetcher
+#[derive(Debug, Clone)]
+pub struct FetcherConfig {
+    pub user_agent: String,
+    pub timeout_seconds: u64,
+    pub max_size: usize,
+    /// Host names resolved to fixed socket addresses instead of using DNS
+    pub resolve_overrides: HashMap<String, SocketAddr>,
+    /// Agents rotated through per request; when non-empty they replace
+    /// `user_agent` in requests
+    pub user_agents: Vec<String>,
+    pub user_agent_rotation: UserAgentRotation,
+    /// Non-2xx statuses whose response is returned with its body instead of
+    /// as an error
+    pub parse_status_codes: Vec<u16>,
+    /// Longest chain of redirects followed before giving up
+    pub max_redirects: u32,
+    /// Read `file://` URLs from disk. Off by default.
+    pub allow_file_scheme: bool,
+    /// Directory `file://` URLs must resolve into; `None` allows any file
+    pub file_root: Option<PathBuf>,
+    /// Idle body buffers kept for reuse between fetches; 0 allocates a new
+    /// buffer for every body
+    pub body_buffer_pool_size: usize,
+    /// Fail responses sent with `Content-Disposition: attachment` with
+    /// [`Error::Attachment`] instead of reading them as pages
+    pub skip_attachments: bool,
+    /// Fail bodies whose first KB has more than this fraction of control
+    /// bytes with [`Error::BinaryContent`], whatever their content type;
+    /// `None` reads every body as text
+    pub binary_threshold: Option<f64>,
+    /// Fail bodies whose magic bytes mark them as an image, PDF, archive or
+    /// other known binary format that their content type doesn't name with
+    /// [`Error::MimeMismatch`]. Off by default.
+    pub sniff_content: bool,
+    /// Most DNS lookups in flight at once; `None` means no cap
+    pub max_concurrent_dns: Option<usize>,
+    /// How long resolved addresses are reused; `None` resolves every
+    /// new connection
+    pub dns_cache_ttl: Option<Duration>,
+    /// Return 3xx responses as they are, with their `Location` header and
+    /// no body, instead of following them
+    pub audit_redirects: bool,
+}
+
+impl FetcherConfig {
+    /// Resolver for the DNS settings, if any differ from the system resolver
+    pub fn dns_resolver(&self) -> Option<DnsResolver> {
+        if self.resolve_overrides.is_empty() && self.max_concurrent_dns.is_none() && self.dns_cache_ttl.is_none() {
+            return None;
+        }
+        let mut resolver = DnsResolver::new().overrides(self.resolve_overrides.clone());
+        if let Some(max) = self.max_concurrent_dns {
+            resolver = resolver.max_concurrent(max);
+        }
+        if let Some(ttl) = self.dns_cache_ttl {
+            resolver = resolver.cache_ttl(ttl);
+        }
+        Some(resolver)
+    }
+}
+
+impl Default for FetcherConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: "RustCrawler/0.1.0".to_string(),
+            timeout_seconds: 30,
+            max_size: 10 * 1024 * 1024, // 10MB
+            resolve_overrides: HashMap::new(),
+            user_agents: Vec::new(),
+            user_agent_rotation: UserAgentRotation::default(),
+            parse_status_codes: Vec::new(),
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            allow_file_scheme: false,
+            file_root: None,
+            body_buffer_pool_size: 0,
+            skip_attachments: true,
+            binary_threshold: Some(DEFAULT_BINARY_THRESHOLD),
+            sniff_content: false,
+            max_concurrent_dns: None,
+            dns_cache_ttl: None,
+            audit_redirects: false,
+        }
+    }
 }
 
 /// HTTP Fetcher for downloading web pages
@@ -18,120 +221,551 @@ pub struct FetchResponse {
 pub struct Fetcher {
     client: ureq::Agent,
     max_size: usize,
+    cache: Option<ResponseCache>,
+    user_agents: Arc<[String]>,
+    user_agent_rotation: UserAgentRotation,
+    /// Round-robin position, shared by clones
+    next_user_agent: Arc<AtomicUsize>,
+    parse_status_codes: Vec<u16>,
+    max_redirects: u32,
+    redirect_filter: Option<RedirectFilter>,
+    allow_file_scheme: bool,
+    file_root: Option<PathBuf>,
+    buffer_pool: Option<BufferPool>,
+    skip_attachments: bool,
+    binary_threshold: Option<f64>,
+    sniff_content: bool,
+    audit_redirects: bool,
 }
 
 impl Fetcher {
     /// Create a new fetcher with configuration
     pub fn new(user_agent: String, timeout_seconds: u64, max_size: usize) -> Self {
-        let client = ureq::AgentBuilder::new()
-            .timeout(Duration::from_secs(timeout_seconds))
-            .user_agent(&user_agent)
-            .build();
+        Self::from_config(FetcherConfig {
+            user_agent,
+            timeout_seconds,
+            max_size,
+            ..FetcherConfig::default()
+        })
+    }
+    
+    /// Create a new fetcher from a full configuration
+    pub fn from_config(config: FetcherConfig) -> Self {
+        let resolver = config.dns_resolver();
+        Self::with_resolver(config, resolver)
+    }
+    
+    /// Create a fetcher resolving names with `resolver` instead of one built
+    /// from the DNS settings of `config`. Clones of a resolver share its
+    /// cache and lookup cap, so it can be shared with other users. `None`
+    /// uses the system resolver directly.
+    pub fn with_resolver(config: FetcherConfig, resolver: Option<DnsResolver>) -> Self {
+        let mut builder = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .user_agent(&config.user_agent)
+            // Redirects are followed by hand so every hop can be checked
+            .redirects(0);
+        
+        if let Some(resolver) = resolver {
+            builder = builder.resolver(resolver);
+        }
         
         Self {
-            client,
-            max_size,
+            client: builder.build(),
+            max_size: config.max_size,
+            cache: None,
+            user_agents: config.user_agents.into(),
+            user_agent_rotation: config.user_agent_rotation,
+            next_user_agent: Arc::new(AtomicUsize::new(0)),
+            parse_status_codes: config.parse_status_codes,
+            max_redirects: config.max_redirects,
+            redirect_filter: None,
+            allow_file_scheme: config.allow_file_scheme,
+            file_root: config.file_root,
+            buffer_pool: (config.body_buffer_pool_size > 0).then(|| BufferPool::new(config.body_buffer_pool_size)),
+            skip_attachments: config.skip_attachments,
+            binary_threshold: config.binary_threshold,
+            sniff_content: config.sniff_content,
+            audit_redirects: config.audit_redirects,
         }
     }
     
+    /// Only follow redirects to URLs `filter` accepts; fetches redirected
+    /// anywhere else fail with [`Error::RedirectOutOfScope`]
+    pub fn with_redirect_filter(mut self, filter: RedirectFilter) -> Self {
+        self.redirect_filter = Some(filter);
+        self
+    }
+    
+    /// Serve repeated fetches of a URL from `cache` instead of the network
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+    
     /// Fetch a URL and return the response
     pub fn fetch(&self, url: &Url) -> Result<FetchResponse> {
-        // Only fetch HTTP(S) URLs
+        self.fetch_accepting(url, HTML_CONTENT_TYPES)
+    }
+    
+    /// Fetch a URL, accepting only responses whose content type contains
+    /// one of `content_types`
+    pub fn fetch_accepting(&self, url: &Url, content_types: &[&str]) -> Result<FetchResponse> {
+        self.fetch_with_referer(url, content_types, None)
+    }
+    
+    /// Fetch a URL like [`Fetcher::fetch_accepting`], sending `referer` as
+    /// the `Referer` header
+    pub fn fetch_with_referer(
+        &self,
+        url: &Url,
+        content_types: &[&str],
+        referer: Option<&Url>,
+    ) -> Result<FetchResponse> {
+        // Only fetch HTTP(S) URLs, and local files if enabled
         match url.scheme() {
             "http" | "https" => {},
+            "file" if self.allow_file_scheme => return self.fetch_file(url, content_types),
             scheme => return Err(Error::InvalidResponse(
                 format!("Unsupported URL scheme: {}", scheme)
             )),
         }
         
-        // Make the request
-        let response = self.client
-            .get(url.as_str())
-            .call()
-            .map_err(|e| Error::HttpError(e.to_string()))?;
+        if let Some(cached) = self.cache.as_ref().and_then(|cache| cache.get(url.as_str())) {
+            check_content_type(cached.content_type.as_deref(), content_types)?;
+            return Ok(cached);
+        }
+        
+        // Make the request, following redirects
+        let started = Instant::now();
+        let user_agent = self.pick_user_agent();
+        let mut final_url = url.clone();
+        let mut redirects = 0;
+        let response = loop {
+            let mut request = self.client.get(final_url.as_str());
+            if let Some(user_agent) = user_agent {
+                request = request.set("User-Agent", user_agent);
+            }
+            if let Some(referer) = referer {
+                request = request.set("Referer", referer.as_str());
+            }
+            let response = match request.call() {
+                Ok(response) => response,
+                Err(ureq::Error::Status(status, response)) if self.parse_status_codes.contains(&status) => response,
+                Err(ureq::Error::Status(status, _)) => {
+                    return Err(Error::HttpStatus(status, url.to_string()));
+                }
+                Err(e) => return Err(Error::HttpError(e.to_string())),
+            };
+            
+            let location = match response.status() {
+                300..=399 => response.header("location"),
+                _ => None,
+            };
+            let Some(location) = location else {
+                break response;
+            };
+            if self.audit_redirects {
+                return Ok(FetchResponse {
+                    url: final_url,
+                    status_code: response.status(),
+                    content_type: response.header("content-type").map(str::to_string),
+                    body: Body::default(),
+                    headers: response_headers(&response),
+                    truncated: false,
+                    elapsed: started.elapsed(),
+                    body_bytes: 0,
+                });
+            }
+            final_url = self.follow_redirect(url, &final_url, location, &mut redirects)?;
+        };
         
         let status_code = response.status();
         
-        // Check if successful
-        if !(200..300).contains(&status_code) {
-            return Err(Error::HttpError(
-                format!("HTTP {} for {}", status_code, url)
-            ));
+        // Check if successful, or a status whose body is wanted anyway
+        let success = (200..300).contains(&status_code);
+        if !success && !self.parse_status_codes.contains(&status_code) {
+            return Err(Error::HttpStatus(status_code, url.to_string()));
         }
         
         // Get content type
         let content_type = response.header("content-type")
             .map(|s| s.to_string());
         
-        // Check if HTML
-        if let Some(ct) = &content_type {
-            if !ct.contains("text/html") && !ct.contains("text/plain") {
-                return Err(Error::InvalidResponse(
-                    format!("Non-HTML content type: {}", ct)
-                ));
-            }
+        check_content_type(content_type.as_deref(), content_types)?;
+        
+        // Downloads aren't pages, whatever their content type
+        if self.skip_attachments && response.header("content-disposition").is_some_and(is_attachment) {
+            return Err(Error::Attachment(url.to_string()));
         }
         
-        // Get headers
-        let headers: Vec<(String, String)> = response
-            .headers_names()
-            .into_iter()
-            .filter_map(|name| {
-                response.header(&name)
-                    .map(|value| (name.to_string(), value.to_string()))
-            })
-            .collect();
+        let headers = response_headers(&response);
         
-        // Read body with size limit
-        let mut body = String::new();
-        response
-            .into_reader()
-            .take(self.max_size as u64)
-            .read_to_string(&mut body)
-            .map_err(|e| Error::HttpError(format!("Failed to read body: {}", e)))?;
+        // Read body with size limit. The agent's timeout bounds the whole
+        // read, so a server that keeps streaming can't hold us here.
+        let content_length = response.header("content-length").and_then(|len| len.trim().parse().ok());
+        let (bytes, truncated) = read_body(
+            response.into_reader(),
+            content_length,
+            self.max_size,
+            self.buffer_pool.as_ref(),
+        )
+        .map_err(|e| Error::HttpError(format!("Failed to read body: {}", e)))?;
+        let body_bytes = bytes.len();
+        let (bytes, truncated) = if accepts_gzip(content_types) {
+            gunzip_body(bytes, truncated, self.max_size)?
+        } else {
+            (bytes, truncated)
+        };
+        self.check_sniff(&bytes, content_type.as_deref(), url)?;
+        self.check_text(&bytes, url)?;
+        let body = decode_body(bytes, truncated)?;
         
-        Ok(FetchResponse {
-            url: url.clone(),
+        let response = FetchResponse {
+            url: final_url,
             status_code,
             content_type,
             body,
             headers,
+            truncated,
+            elapsed: started.elapsed(),
+            body_bytes,
+        };
+        if let (Some(cache), true) = (&self.cache, success) {
+            cache.insert(url.as_str(), response.clone());
+        }
+        Ok(response)
+    }
+    
+    /// Read a `file://` URL from disk. Directories serve their `index.html`.
+    /// Paths that resolve outside the file root, through `..` or symlinks,
+    /// are refused.
+    fn fetch_file(&self, url: &Url, content_types: &[&str]) -> Result<FetchResponse> {
+        let started = Instant::now();
+        let path = url
+            .to_file_path()
+            .map_err(|_| Error::InvalidResponse(format!("Not a local file path: {}", url)))?;
+        let mut path = path.canonicalize()?;
+        if path.is_dir() {
+            // Resolve again, as index.html may be a symlink out of the root
+            path = path.join("index.html").canonicalize()?;
+        }
+        if let Some(root) = &self.file_root {
+            if !path.starts_with(root.canonicalize()?) {
+                return Err(Error::InvalidResponse(format!("{} is outside the file root", url)));
+            }
+        }
+        
+        let content_type = file_content_type(&path);
+        check_content_type(Some(content_type), content_types)?;
+        
+        let file = std::fs::File::open(&path)?;
+        let file_len = file.metadata()?.len() as usize;
+        let (bytes, truncated) = read_body(file, Some(file_len), self.max_size, self.buffer_pool.as_ref())?;
+        let body_bytes = bytes.len();
+        let (bytes, truncated) = if accepts_gzip(content_types) {
+            gunzip_body(bytes, truncated, self.max_size)?
+        } else {
+            (bytes, truncated)
+        };
+        self.check_sniff(&bytes, Some(content_type), url)?;
+        self.check_text(&bytes, url)?;
+        
+        Ok(FetchResponse {
+            url: url.clone(),
+            status_code: 200,
+            content_type: Some(content_type.to_string()),
+            body: decode_body(bytes, truncated)?,
+            headers: vec![("content-type".to_string(), content_type.to_string())],
+            truncated,
+            elapsed: started.elapsed(),
+            body_bytes,
         })
     }
     
+    /// Refuse a body that looks binary, so mislabeled files aren't
+    /// parsed and indexed as text
+    fn check_text(&self, bytes: &[u8], url: &Url) -> Result<()> {
+        match self.binary_threshold {
+            Some(threshold) if looks_binary(bytes, threshold) => Err(Error::BinaryContent(url.to_string())),
+            _ => Ok(()),
+        }
+    }
+    
+    /// Fail a body whose magic bytes show a binary format its content type
+    /// doesn't declare, when sniffing is enabled
+    fn check_sniff(&self, bytes: &[u8], content_type: Option<&str>, url: &Url) -> Result<()> {
+        if !self.sniff_content {
+            return Ok(());
+        }
+        match sniff_mime(bytes) {
+            Some(sniffed) if !content_type.is_some_and(|ct| ct.to_ascii_lowercase().contains(sniffed)) => {
+                Err(Error::MimeMismatch(url.to_string(), sniffed.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    /// Target of a redirect from `current`, checked against the redirect
+    /// limit and filter
+    fn follow_redirect(&self, url: &Url, current: &Url, location: &str, redirects: &mut u32) -> Result<Url> {
+        *redirects += 1;
+        if *redirects > self.max_redirects {
+            return Err(Error::HttpError(format!("Too many redirects fetching {}", url)));
+        }
+        let target = current.join(location)?;
+        if !matches!(target.scheme(), "http" | "https") {
+            return Err(Error::InvalidResponse(format!("Redirect to unsupported URL {}", target)));
+        }
+        if self.redirect_filter.as_ref().is_some_and(|allows| !allows(&target)) {
+            return Err(Error::RedirectOutOfScope(target.to_string()));
+        }
+        Ok(target)
+    }
+    
+    /// Next agent from the rotation list, if one is configured
+    fn pick_user_agent(&self) -> Option<&str> {
+        if self.user_agents.is_empty() {
+            return None;
+        }
+        let i = match self.user_agent_rotation {
+            UserAgentRotation::RoundRobin => self.next_user_agent.fetch_add(1, Ordering::Relaxed),
+            UserAgentRotation::Random => rand::thread_rng().gen_range(0..self.user_agents.len()),
+        };
+        Some(&self.user_agents[i % self.user_agents.len()])
+    }
+    
     /// Check if a URL should be fetched based on scheme and extension
     pub fn should_fetch(url: &Url) -> bool {
         // Only HTTP(S)
-        if !matches!(url.scheme(), "http" | "https") {
-            return false;
-        }
-        
-        // Skip common non-HTML extensions
-        if let Some(path) = url.path_segments() {
-            if let Some(last) = path.last() {
-                let skip_extensions = [
-                    ".jpg", ".jpeg", ".png", ".gif", ".webp", ".svg",
-                    ".pdf", ".doc", ".docx", ".xls", ".xlsx",
-                    ".zip", ".rar", ".tar", ".gz",
-                    ".mp3", ".mp4", ".avi", ".mov",
-                    ".css", ".js", ".json", ".xml",
-                ];
-                
-                for ext in &skip_extensions {
-                    if last.to_lowercase().ends_with(ext) {
-                        return false;
-                    }
-                }
-            }
+        matches!(url.scheme(), "http" | "https") && !has_skipped_extension(url)
+    }
+    
+    /// Check if a `file://` URL should be read, based on its extension
+    pub fn should_fetch_file(url: &Url) -> bool {
+        url.scheme() == "file" && !has_skipped_extension(url)
+    }
+}
+
+/// Check whether a `Content-Disposition` value marks a download
+fn is_attachment(disposition: &str) -> bool {
+    disposition
+        .split(';')
+        .next()
+        .is_some_and(|kind| kind.trim().eq_ignore_ascii_case("attachment"))
+}
+
+/// Magic bytes of binary formats, with their MIME types. Checked in order;
+/// an offset lets a signature start past the first byte.
+const MAGIC_BYTES: &[(usize, &[u8], &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xff\xd8\xff", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (8, b"WEBP", "image/webp"),
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"\x1f\x8b", "application/gzip"),
+    (0, b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (4, b"ftyp", "video/mp4"),
+    (0, b"ID3", "audio/mpeg"),
+    (0, b"OggS", "audio/ogg"),
+    (0, b"\0asm", "application/wasm"),
+    (0, b"\x7fELF", "application/x-executable"),
+];
+
+/// MIME type of a body whose first bytes match a known binary format.
+/// Text formats aren't recognized, so `None` means "probably text".
+pub fn sniff_mime(bytes: &[u8]) -> Option<&'static str> {
+    MAGIC_BYTES
+        .iter()
+        .find(|(offset, magic, _)| bytes.get(*offset..).is_some_and(|rest| rest.starts_with(magic)))
+        .map(|(_, _, mime)| *mime)
+}
+
+/// Bytes at the start of a body inspected by [`looks_binary`]
+pub const BINARY_SNIFF_BYTES: usize = 1024;
+
+/// Default fraction of control bytes above which a body counts as binary.
+/// Text has next to none; most binary formats are full of NULs.
+pub const DEFAULT_BINARY_THRESHOLD: f64 = 0.05;
+
+/// Check whether more than `threshold` of the first [`BINARY_SNIFF_BYTES`]
+/// bytes are NULs or other control characters that don't occur in text
+pub fn looks_binary(bytes: &[u8], threshold: f64) -> bool {
+    let sample = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+    if sample.is_empty() {
+        return false;
+    }
+    let control = sample
+        .iter()
+        .filter(|&&byte| (byte < 0x20 && !matches!(byte, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b)) || byte == 0x7f)
+        .count();
+    control as f64 / sample.len() as f64 > threshold
+}
+
+/// Buffer size for bodies of unknown length
+const DEFAULT_BODY_CAPACITY: usize = 16 * 1024;
+
+/// Read a body of at most `max_size` bytes, returning it and whether it was
+/// cut off.
+///
+/// The buffer is sized from `content_length` up front, so known-length
+/// bodies are read without reallocating. With a `pool`, the read goes into a
+/// reused buffer and the body shares its memory, so nothing is allocated
+/// or copied once the pool's buffers have grown.
+pub fn read_body(
+    reader: impl Read,
+    content_length: Option<usize>,
+    max_size: usize,
+    pool: Option<&BufferPool>,
+) -> io::Result<(Bytes, bool)> {
+    // One byte past the limit to detect truncation
+    let capacity = content_length.map_or(DEFAULT_BODY_CAPACITY, |len| len.min(max_size) + 1);
+    let mut reader = reader.take(max_size as u64 + 1);
+    let Some(pool) = pool else {
+        let mut buffer = Vec::with_capacity(capacity);
+        let read = reader.read_to_end(&mut buffer);
+        let truncated = buffer.len() > max_size;
+        buffer.truncate(max_size);
+        return read.map(|_| (Bytes::from(buffer), truncated));
+    };
+    let mut writer = pool.take(capacity).writer();
+    let read = io::copy(&mut reader, &mut writer);
+    let mut buffer = writer.into_inner();
+    if let Err(e) = read {
+        pool.give(buffer);
+        return Err(e);
+    }
+    let truncated = buffer.len() > max_size;
+    buffer.truncate(max_size);
+    Ok((pool.finish(buffer), truncated))
+}
+
+/// Content type of a local file, from its extension
+fn file_content_type(path: &Path) -> &'static str {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+    match extension.to_ascii_lowercase().as_str() {
+        "html" | "htm" | "xhtml" => "text/html",
+        "txt" | "md" => "text/plain",
+        "xml" => "application/xml",
+        "json" => "application/json",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Check whether a URL's path ends in a common non-HTML extension
+fn has_skipped_extension(url: &Url) -> bool {
+    let Some(last) = url.path_segments().and_then(|mut path| path.next_back()) else {
+        return false;
+    };
+    let skip_extensions = [
+        ".jpg", ".jpeg", ".png", ".gif", ".webp", ".svg",
+        ".pdf", ".doc", ".docx", ".xls", ".xlsx",
+        ".zip", ".rar", ".tar", ".gz",
+        ".mp3", ".mp4", ".avi", ".mov",
+        ".css", ".js", ".json", ".xml",
+    ];
+    let last = last.to_lowercase();
+    skip_extensions.iter().any(|ext| last.ends_with(ext))
+}
+
+#[async_trait]
+impl HttpFetcher for Fetcher {
+    async fn fetch_accepting(&self, url: &Url, content_types: &[&str]) -> Result<FetchResponse> {
+        HttpFetcher::fetch_with_referer(self, url, content_types, None).await
+    }
+    
+    async fn fetch_with_referer(
+        &self,
+        url: &Url,
+        content_types: &[&str],
+        referer: Option<&Url>,
+    ) -> Result<FetchResponse> {
+        // Run the blocking request off the async workers
+        let fetcher = self.clone();
+        let url = url.clone();
+        let referer = referer.cloned();
+        let content_types: Vec<String> = content_types.iter().map(|ct| ct.to_string()).collect();
+        tokio::task::spawn_blocking(move || {
+            let content_types: Vec<&str> = content_types.iter().map(String::as_str).collect();
+            Fetcher::fetch_with_referer(&fetcher, &url, &content_types, referer.as_ref())
+        })
+        .await
+        .map_err(|e| Error::Unknown(format!("Task error: {}", e)))?
+    }
+}
+
+/// Headers of a response, keeping every value of repeated headers
+fn response_headers(response: &ureq::Response) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for name in response.headers_names() {
+        if headers.iter().any(|(seen, _)| *seen == name) {
+            continue;
+        }
+        for value in response.all(&name) {
+            headers.push((name.clone(), value.to_string()));
+        }
+    }
+    headers
+}
+
+/// Whether a fetch accepting `content_types` takes gzip files, like a
+/// sitemap fetch. Page fetches don't, so their bodies are never inflated.
+fn accepts_gzip(content_types: &[&str]) -> bool {
+    content_types.iter().any(|accepted| accepted.contains("gzip"))
+}
+
+/// Decompress a body that is a gzip file, such as a `.xml.gz` sitemap
+/// served without `Content-Encoding`, recognized by its magic bytes. At
+/// most `max_size` bytes are inflated, so a small file can't expand into
+/// an unbounded body.
+fn gunzip_body(bytes: Bytes, truncated: bool, max_size: usize) -> Result<(Bytes, bool)> {
+    if !bytes.starts_with(GZIP_MAGIC) {
+        return Ok((bytes, truncated));
+    }
+    let mut inflated = Vec::new();
+    let read = GzDecoder::new(&bytes[..])
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut inflated);
+    // A cut-off download ends partway through the stream; keep what inflated
+    if let Err(e) = read {
+        if !truncated {
+            return Err(Error::HttpError(format!("Failed to decompress body: {}", e)));
+        }
+    }
+    let over = inflated.len() > max_size;
+    inflated.truncate(max_size);
+    Ok((Bytes::from(inflated), truncated || over))
+}
+
+/// Decode a body as UTF-8, without copying it. A truncated body may end
+/// partway through a character; that incomplete character is dropped.
+fn decode_body(bytes: Bytes, truncated: bool) -> Result<Body> {
+    match std::str::from_utf8(&bytes) {
+        Ok(_) => Body::from_utf8(bytes),
+        Err(e) if truncated && e.error_len().is_none() => Body::from_utf8(bytes.slice(..e.valid_up_to())),
+        Err(e) => Err(e),
+    }
+    .map_err(|e| Error::HttpError(format!("Failed to read body: {}", e)))
+}
+
+/// Check the content type is one we accept
+pub(crate) fn check_content_type(content_type: Option<&str>, content_types: &[&str]) -> Result<()> {
+    if let Some(ct) = content_type {
+        if !content_types.iter().any(|accepted| ct.contains(accepted)) {
+            return Err(Error::InvalidResponse(
+                format!("Unaccepted content type: {}", ct)
+            ));
         }
-        
-        true
     }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::crawler::mock_server::MockResponse;
     
     #[test]
     fn test_should_fetch() {
@@ -140,4 +774,198 @@ mod tests {
         assert!(!Fetcher::should_fetch(&Url::parse("https://example.com/image.jpg").unwrap()));
         assert!(!Fetcher::should_fetch(&Url::parse("ftp://example.com").unwrap()));
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_read_body() {
+        let body = b"<html>page</html>";
+        let pool = BufferPool::new(1);
+        for pool in [None, Some(&pool)] {
+            let (bytes, truncated) = read_body(&body[..], Some(body.len()), 100, pool).unwrap();
+            assert_eq!(&bytes[..], body);
+            assert!(!truncated);
+            
+            // A wrong or missing Content-Length doesn't change the result
+            let (bytes, truncated) = read_body(&body[..], Some(3), 6, pool).unwrap();
+            assert_eq!(&bytes[..], b"<html>");
+            assert!(truncated);
+            let (bytes, _) = read_body(&body[..], None, 100, pool).unwrap();
+            assert_eq!(&bytes[..], body);
+        }
+        assert_eq!(pool.idle(), 1);
+    }
+    
+    #[test]
+    fn test_referrer_policy() {
+        let from = Url::parse("https://user:pw@example.com/page?q=1#top").unwrap();
+        let same_origin = Url::parse("https://example.com/other").unwrap();
+        let cross_origin = Url::parse("https://other.com/").unwrap();
+        
+        let referer = ReferrerPolicy::Always.referer(&from, &cross_origin).unwrap();
+        assert_eq!(referer.as_str(), "https://example.com/page?q=1");
+        assert!(ReferrerPolicy::Strict.referer(&from, &same_origin).is_some());
+        assert!(ReferrerPolicy::Strict.referer(&from, &cross_origin).is_none());
+    }
+    
+    #[test]
+    fn test_resolve_override() {
+        let server = crate::crawler::mock_server::MockServer::start();
+        server.html("/", "<title>Overridden</title>");
+        
+        let mut overrides = HashMap::new();
+        overrides.insert("example.test".to_string(), server.addr());
+        let fetcher = Fetcher::from_config(FetcherConfig {
+            resolve_overrides: overrides,
+            ..FetcherConfig::default()
+        });
+        
+        let response = fetcher.fetch(&Url::parse("http://example.test/").unwrap()).unwrap();
+        assert_eq!(response.body, "<title>Overridden</title>");
+        assert_eq!(server.hits("/"), 1);
+    }
+    
+    #[test]
+    fn test_streamed_body_truncated_at_max_size() {
+        let server = crate::crawler::mock_server::MockServer::start();
+        // Multi-byte characters, so the cap falls inside one
+        let body = "é".repeat(10_000);
+        server.route("/stream", crate::crawler::mock_server::MockResponse::html(&body).chunked());
+        server.route("/small", crate::crawler::mock_server::MockResponse::html("<p>small</p>").chunked());
+        let fetcher = Fetcher::from_config(FetcherConfig {
+            max_size: 1001,
+            ..FetcherConfig::default()
+        });
+        
+        let response = fetcher.fetch(&server.url("/stream")).unwrap();
+        assert!(response.truncated);
+        assert_eq!(response.body_bytes, 1001);
+        assert_eq!(response.body, "é".repeat(500));
+        assert!(response.headers.iter().all(|(name, _)| !name.eq_ignore_ascii_case("content-length")));
+        
+        let response = fetcher.fetch(&server.url("/small")).unwrap();
+        assert!(!response.truncated);
+        assert_eq!(response.body, "<p>small</p>");
+    }
+    
+    #[test]
+    fn test_timing_and_size() {
+        let server = crate::crawler::mock_server::MockServer::start();
+        let body = "<p>naïve café</p>";
+        server.route(
+            "/",
+            crate::crawler::mock_server::MockResponse::html(body).delay(Duration::from_millis(20)),
+        );
+        
+        let response = Fetcher::from_config(FetcherConfig::default()).fetch(&server.url("/")).unwrap();
+        
+        assert!(response.elapsed >= Duration::from_millis(20));
+        assert_eq!(response.body_bytes, body.len());
+        assert!(response.body_bytes > response.body.chars().count());
+    }
+    
+    #[test]
+    fn test_audit_redirects() {
+        use crate::crawler::mock_server::MockResponse;
+        
+        let server = crate::crawler::mock_server::MockServer::start();
+        server.route("/old", MockResponse::html("moved").status(302).header("Location", "/new"));
+        server.html("/new", "<title>New</title>");
+        let fetcher = Fetcher::from_config(FetcherConfig {
+            audit_redirects: true,
+            ..FetcherConfig::default()
+        });
+        
+        let response = fetcher.fetch(&server.url("/old")).unwrap();
+        
+        assert_eq!(response.status_code, 302);
+        assert_eq!(response.url, server.url("/old"));
+        assert_eq!(response.redirect_target(), Some(server.url("/new")));
+        assert!(response.body.is_empty());
+        assert_eq!(server.hits("/new"), 0);
+        
+        // Followed as usual otherwise
+        let response = Fetcher::from_config(FetcherConfig::default()).fetch(&server.url("/old")).unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.redirect_target(), None);
+    }
+    
+    #[test]
+    fn test_user_agents_rotate() {
+        let server = crate::crawler::mock_server::MockServer::start();
+        server.html("/", "<title>Agents</title>");
+        let fetcher = Fetcher::from_config(FetcherConfig {
+            user_agents: vec!["AgentA/1.0".to_string(), "AgentB/2.0".to_string()],
+            ..FetcherConfig::default()
+        });
+        
+        // Clones share the rotation
+        for fetcher in [&fetcher, &fetcher.clone(), &fetcher] {
+            fetcher.fetch(&server.url("/")).unwrap();
+        }
+        
+        let agents: Vec<String> = server
+            .requests()
+            .iter()
+            .map(|request| request.header("user-agent").unwrap().to_string())
+            .collect();
+        assert_eq!(agents, vec!["AgentA/1.0", "AgentB/2.0", "AgentA/1.0"]);
+    }
+    
+    #[test]
+    fn test_response_cache_skips_network() {
+        let server = crate::crawler::mock_server::MockServer::start();
+        server.html("/", "<title>Cached</title>");
+        let fetcher = Fetcher::from_config(FetcherConfig::default())
+            .with_cache(ResponseCache::new(Duration::from_secs(60), 16));
+        let url = server.url("/");
+        
+        let first = fetcher.fetch(&url).unwrap();
+        let second = fetcher.fetch(&url).unwrap();
+        assert_eq!(second.body, first.body);
+        assert_eq!(server.hits("/"), 1);
+        
+        // Without a cache every fetch goes to the network
+        Fetcher::from_config(FetcherConfig::default()).fetch(&url).unwrap();
+        assert_eq!(server.hits("/"), 2);
+    }
+    
+    #[test]
+    fn test_looks_binary() {
+        let html = "<html>\n\t<p>Caf\u{e9} \u{1b}[0m</p>\r\n</html>".repeat(100);
+        assert!(!looks_binary(html.as_bytes(), DEFAULT_BINARY_THRESHOLD));
+        assert!(!looks_binary(b"", DEFAULT_BINARY_THRESHOLD));
+        
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        png.extend([0u8; 64]);
+        assert!(looks_binary(&png, DEFAULT_BINARY_THRESHOLD));
+        // Only the start of the body is inspected
+        let mut late = "<p>text</p>".repeat(BINARY_SNIFF_BYTES).into_bytes();
+        late.extend([0u8; 4096]);
+        assert!(!looks_binary(&late, DEFAULT_BINARY_THRESHOLD));
+        assert!(!looks_binary(&png, 0.9));
+    }
+    
+    #[test]
+    fn test_gzip_only_inflated_for_sitemaps() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+        
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"<urlset></urlset>").unwrap();
+        let gzip = encoder.finish().unwrap();
+        let server = crate::crawler::mock_server::MockServer::start();
+        server.route("/sitemap.xml.gz", MockResponse::typed("application/xml", &gzip));
+        server.route("/page", MockResponse::typed("text/html", &gzip));
+        
+        let fetcher = Fetcher::from_config(FetcherConfig {
+            sniff_content: true,
+            ..FetcherConfig::default()
+        });
+        let sitemap = fetcher.fetch_accepting(&server.url("/sitemap.xml.gz"), XML_CONTENT_TYPES).unwrap();
+        assert_eq!(sitemap.body, "<urlset></urlset>");
+        // A page body is left as sent, so sniffing sees the gzip file
+        assert!(matches!(
+            fetcher.fetch(&server.url("/page")),
+            Err(Error::MimeMismatch(_, sniffed)) if sniffed == "application/gzip"
+        ));
+    }
+}
@@ -1,37 +1,436 @@
-use std::collections::{HashSet, VecDeque};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
+use std::ops::Bound;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use url::Url;
+use crate::common::error::Result;
+use async_trait::async_trait;
+use crate::crawler::same_site::{SameSitePolicy, SiteScope};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Priority given to tasks that don't specify one
+pub const DEFAULT_PRIORITY: f32 = 0.5;
+
+/// Priority given to `rel="next"` pagination links so paginated sets are
+/// traversed in order
+pub const NEXT_PAGE_PRIORITY: f32 = 0.9;
 
 /// URL Frontier manages the queue of URLs to be crawled
 #[derive(Clone)]
 pub struct UrlFrontier {
-    /// Queue of URLs to crawl
-    queue: Arc<Mutex<VecDeque<CrawlTask>>>,
+    /// Queue of URLs to crawl, highest priority first
+    queue: Arc<Mutex<TaskQueue>>,
     /// Set of seen URLs to avoid duplicates
-    seen: Arc<Mutex<HashSet<String>>>,
+    seen: Arc<Mutex<SeenSet>>,
     /// Maximum queue size
     max_size: usize,
+    /// Sites links must stay on, if restricted
+    scope: Option<Arc<std::sync::Mutex<SiteScope>>>,
+    /// Hosts queued so far, if their number is capped
+    hosts: Option<Arc<std::sync::Mutex<HostLimit>>>,
+    /// Query strings queued per path, if their number is capped
+    query_variants: Option<Arc<std::sync::Mutex<QueryVariantLimit>>>,
+    /// Most URLs ever accepted, if capped
+    max_urls_discovered: Option<usize>,
+    /// URLs accepted so far, whether or not they were fetched since
+    discovered: Arc<AtomicUsize>,
+    /// Trailing slash normalization applied to every URL pushed
+    trailing_slash: TrailingSlash,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrawlTask {
     pub url: Url,
     pub depth: usize,
     pub retry_count: u32,
+    /// Higher priority tasks are popped first; equal priorities are FIFO
+    pub priority: f32,
+    /// Page the URL was found on; `None` for seeds
+    pub referer: Option<Url>,
+    /// Labels given to a seed (`category=news`), inherited by every page
+    /// crawled from it and stored and indexed with them
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+}
+
+impl CrawlTask {
+    /// A task that hasn't been retried yet
+    pub fn new(url: Url, depth: usize, priority: f32, referer: Option<Url>) -> Self {
+        Self {
+            url,
+            depth,
+            retry_count: 0,
+            priority,
+            referer,
+            tags: HashMap::new(),
+        }
+    }
+    
+    /// Task for a link found on this task's page, one level deeper and
+    /// carrying its tags
+    pub fn link(&self, url: Url, priority: f32) -> Self {
+        Self::new(url, self.depth + 1, priority, Some(self.url.clone())).with_tags(self.tags.clone())
+    }
+    
+    /// The same task carrying `tags`
+    pub fn with_tags(mut self, tags: HashMap<String, String>) -> Self {
+        self.tags = tags;
+        self
+    }
+}
+
+/// Order in which queued tasks are handed out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CrawlStrategy {
+    /// Highest priority first, breadth-first among equal priorities
+    #[default]
+    Priority,
+    /// Round-robin over depth levels so every depth makes progress;
+    /// priority order applies within each depth
+    Fair,
+}
+
+/// How a trailing `/` on a URL path is normalized before deduplication,
+/// so `/docs` and `/docs/` count as one page. The root path `/` is never
+/// changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrailingSlash {
+    /// Keep paths as they are
+    #[default]
+    Preserve,
+    /// Remove trailing slashes: `/docs/` becomes `/docs`
+    Strip,
+    /// Add a slash to paths whose last segment has no file extension:
+    /// `/docs` becomes `/docs/`, `/docs/intro.html` is kept
+    Add,
+}
+
+impl TrailingSlash {
+    /// Normalize the path of `url` in place
+    pub fn apply(self, url: &mut Url) {
+        if url.cannot_be_a_base() || url.path() == "/" {
+            return;
+        }
+        let path = url.path();
+        let normalized = match self {
+            TrailingSlash::Preserve => return,
+            TrailingSlash::Strip => match path.trim_end_matches('/') {
+                "" => "/".to_string(),
+                trimmed => trimmed.to_string(),
+            },
+            TrailingSlash::Add => {
+                let last_segment = path.rsplit('/').next().unwrap_or_default();
+                if last_segment.is_empty() || last_segment.contains('.') {
+                    return;
+                }
+                format!("{}/", path)
+            }
+        };
+        if normalized != path {
+            url.set_path(&normalized);
+        }
+    }
+}
+
+/// Priority queue of tasks that preserves insertion order within a priority
+#[derive(Default)]
+struct TaskQueue {
+    strategy: CrawlStrategy,
+    /// Tasks bucketed by depth under `Fair`, or all in bucket 0 otherwise.
+    /// Empty buckets are removed.
+    buckets: BTreeMap<usize, BinaryHeap<QueuedTask>>,
+    /// Bucket popped from last, for round-robin
+    last_bucket: Option<usize>,
+    len: usize,
+    next_seq: u64,
+}
+
+impl TaskQueue {
+    fn new(strategy: CrawlStrategy) -> Self {
+        Self {
+            strategy,
+            ..Self::default()
+        }
+    }
+    
+    fn push(&mut self, task: CrawlTask) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let bucket = match self.strategy {
+            CrawlStrategy::Priority => 0,
+            CrawlStrategy::Fair => task.depth,
+        };
+        self.buckets.entry(bucket).or_default().push(QueuedTask { task, seq });
+        self.len += 1;
+    }
+    
+    fn pop(&mut self) -> Option<CrawlTask> {
+        // Next bucket after the last one served, wrapping around
+        let bucket = self
+            .last_bucket
+            .and_then(|last| {
+                self.buckets
+                    .range((Bound::Excluded(last), Bound::Unbounded))
+                    .next()
+                    .map(|(key, _)| *key)
+            })
+            .or_else(|| self.buckets.keys().next().copied())?;
+        
+        let heap = self.buckets.get_mut(&bucket)?;
+        let queued = heap.pop()?;
+        if heap.is_empty() {
+            self.buckets.remove(&bucket);
+        }
+        self.last_bucket = Some(bucket);
+        self.len -= 1;
+        Some(queued.task)
+    }
+    
+    fn len(&self) -> usize {
+        self.len
+    }
+    
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    
+    /// Queued tasks in the order they were pushed
+    fn tasks(&self) -> Vec<CrawlTask> {
+        let mut queued: Vec<&QueuedTask> = self.buckets.values().flat_map(|heap| heap.iter()).collect();
+        queued.sort_by_key(|queued| queued.seq);
+        queued.into_iter().map(|queued| queued.task.clone()).collect()
+    }
+}
+
+struct QueuedTask {
+    task: CrawlTask,
+    seq: u64,
+}
+
+impl Ord for QueuedTask {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.task
+            .priority
+            .total_cmp(&other.task.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for QueuedTask {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for QueuedTask {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueuedTask {}
+
+/// Queue of URLs waiting to be crawled together with the set of URLs
+/// already seen. The crawler only talks to its frontier through this trait,
+/// so the queue can live in memory ([`UrlFrontier`]) or be shared by
+/// several crawler processes.
+#[async_trait]
+pub trait Frontier: Send + Sync {
+    /// Queue a task and mark its URL seen, unless the URL was seen before or
+    /// the frontier refuses it. Returns whether the task was queued.
+    async fn push(&self, task: CrawlTask) -> bool;
+    
+    /// Take the next task to crawl
+    async fn pop(&self) -> Option<CrawlTask>;
+    
+    /// Number of queued tasks
+    async fn size(&self) -> usize;
+    
+    /// Check whether no tasks are queued
+    async fn is_empty(&self) -> bool {
+        self.size().await == 0
+    }
+    
+    /// Mark a URL as seen without queueing it, so later adds are ignored
+    async fn mark_seen(&self, url: &Url);
+    
+    /// Check if a URL has been seen
+    async fn has_seen(&self, url: &Url) -> bool;
+    
+    /// Queue a failed task again with its retry count incremented. Its URL
+    /// is already seen, so this bypasses deduplication.
+    async fn retry(&self, task: CrawlTask) -> bool;
+    
+    /// Queue and seen-set sizes
+    async fn stats(&self) -> FrontierStats;
+    
+    /// Copy out the queued tasks and seen URLs, for checkpoints. Frontiers
+    /// that persist their own state return an empty snapshot.
+    async fn snapshot(&self) -> FrontierSnapshot {
+        FrontierSnapshot::default()
+    }
+    
+    /// Add the tasks and seen URLs of a snapshot
+    async fn restore(&self, _snapshot: FrontierSnapshot) {}
+    
+    /// Add a URL to the frontier
+    async fn add(&self, url: Url, depth: usize) -> bool {
+        self.add_with_priority(url, depth, DEFAULT_PRIORITY).await
+    }
+    
+    /// Add a URL to the frontier with an explicit priority
+    async fn add_with_priority(&self, url: Url, depth: usize, priority: f32) -> bool {
+        self.push(CrawlTask::new(url, depth, priority, None)).await
+    }
+    
+    /// Add a URL found on the page `referer`
+    async fn add_link(&self, url: Url, depth: usize, priority: f32, referer: &Url) -> bool {
+        self.push(CrawlTask::new(url, depth, priority, Some(referer.clone()))).await
+    }
+    
+    /// Add multiple URLs
+    async fn add_many(&self, urls: Vec<(Url, usize)>) {
+        for (url, depth) in urls {
+            self.add(url, depth).await;
+        }
+    }
+    
+    /// Allow links to the site of a seed URL, for frontiers restricted to
+    /// the seeds' sites
+    fn add_seed_site(&self, _url: &Url) {}
+    
+    /// Check whether a URL is on a seed's site, or any URL when the
+    /// frontier isn't restricted
+    fn in_scope(&self, _url: &Url) -> bool {
+        true
+    }
+    
+    /// Check whether a URL's host is already known or there is room for
+    /// another host
+    fn admits_host(&self, _url: &Url) -> bool {
+        true
+    }
+    
+    /// Check whether a URL has no query or its path has room for another
+    /// query variant
+    fn admits_query_variant(&self, _url: &Url) -> bool {
+        true
+    }
 }
 
 impl UrlFrontier {
     pub fn new(max_size: usize) -> Self {
+        Self::with_strategy(max_size, CrawlStrategy::default())
+    }
+    
+    /// Create a frontier that hands out tasks according to `strategy`
+    pub fn with_strategy(max_size: usize, strategy: CrawlStrategy) -> Self {
         Self {
-            queue: Arc::new(Mutex::new(VecDeque::new())),
-            seen: Arc::new(Mutex::new(HashSet::new())),
+            queue: Arc::new(Mutex::new(TaskQueue::new(strategy))),
+            seen: Arc::new(Mutex::new(SeenSet::Memory(HashSet::new()))),
             max_size,
+            scope: None,
+            hosts: None,
+            query_variants: None,
+            max_urls_discovered: None,
+            discovered: Arc::new(AtomicUsize::new(0)),
+            trailing_slash: TrailingSlash::default(),
         }
     }
     
-    /// Add a URL to the frontier
-    pub async fn add(&self, url: Url, depth: usize) -> bool {
+    /// Keep the seen set in a sled database at `path` instead of memory,
+    /// so URLs seen by earlier runs using the same database are skipped
+    pub fn with_dedup_db(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        let tree = sled::open(path)?.open_tree(SEEN_TREE)?;
+        self.seen = Arc::new(Mutex::new(SeenSet::Disk(tree)));
+        Ok(self)
+    }
+    
+    /// Only accept discovered URLs on the same site as a seed, as decided
+    /// by `policy`. Seeds themselves (depth 0) are always accepted.
+    pub fn with_same_site(mut self, policy: SameSitePolicy) -> Self {
+        self.scope = Some(Arc::new(std::sync::Mutex::new(SiteScope::new(policy))));
+        self
+    }
+    
+    /// Stop accepting discovered URLs on new hosts once URLs from
+    /// `max_hosts` distinct hosts have been queued. Seeds are always accepted.
+    pub fn with_max_hosts(mut self, max_hosts: usize) -> Self {
+        self.hosts = Some(Arc::new(std::sync::Mutex::new(HostLimit {
+            max_hosts,
+            hosts: HashSet::new(),
+        })));
+        self
+    }
+    
+    /// Stop accepting discovered URLs on a path once `max_variants` URLs
+    /// with different query strings have been queued for it, so links like
+    /// `?p=1`, `?p=2`, ... can't grow the crawl forever
+    pub fn with_max_query_variants(mut self, max_variants: usize) -> Self {
+        self.query_variants = Some(Arc::new(std::sync::Mutex::new(QueryVariantLimit {
+            max_variants,
+            counts: HashMap::new(),
+        })));
+        self
+    }
+    
+    /// Normalize the trailing slash of every URL pushed, so the variants
+    /// of a path are deduplicated and crawled under one URL
+    pub fn with_trailing_slash(mut self, mode: TrailingSlash) -> Self {
+        self.trailing_slash = mode;
+        self
+    }
+    
+    /// Stop accepting URLs, seeds included, once `max_urls` have been
+    /// accepted. Unlike the queue size this never frees up as tasks are
+    /// popped, so it bounds the whole crawl.
+    pub fn with_max_urls_discovered(mut self, max_urls: usize) -> Self {
+        self.max_urls_discovered = Some(max_urls);
+        self
+    }
+    
+    /// Check whether the discovery cap has been reached
+    pub fn discovery_exhausted(&self) -> bool {
+        self.max_urls_discovered
+            .is_some_and(|max| self.discovered.load(AtomicOrdering::SeqCst) >= max)
+    }
+}
+
+#[async_trait]
+impl Frontier for UrlFrontier {
+    fn admits_host(&self, url: &Url) -> bool {
+        self.hosts.as_ref().is_none_or(|hosts| hosts.lock().unwrap().admits(url))
+    }
+    
+    fn admits_query_variant(&self, url: &Url) -> bool {
+        self.query_variants
+            .as_ref()
+            .is_none_or(|variants| variants.lock().unwrap().admits(url))
+    }
+    
+    fn add_seed_site(&self, url: &Url) {
+        if let Some(scope) = &self.scope {
+            scope.lock().unwrap().add_seed(url);
+        }
+    }
+    
+    fn in_scope(&self, url: &Url) -> bool {
+        self.scope
+            .as_ref()
+            .is_none_or(|scope| scope.lock().unwrap().allows(url))
+    }
+    
+    async fn push(&self, mut task: CrawlTask) -> bool {
+        self.trailing_slash.apply(&mut task.url);
+        let url = &task.url;
+        if task.depth > 0 && !(self.in_scope(url) && self.admits_host(url) && self.admits_query_variant(url)) {
+            return false;
+        }
+        
         let url_str = url.as_str().to_string();
         
         let mut seen = self.seen.lock().await;
@@ -40,65 +439,103 @@ impl UrlFrontier {
         }
         
         let mut queue = self.queue.lock().await;
-        if queue.len() >= self.max_size {
+        if queue.len() >= self.max_size || self.discovery_exhausted() {
             return false;
         }
         
         seen.insert(url_str);
-        queue.push_back(CrawlTask {
-            url,
-            depth,
-            retry_count: 0,
-        });
+        self.discovered.fetch_add(1, AtomicOrdering::SeqCst);
+        if let (Some(hosts), Some(host)) = (&self.hosts, url.host_str()) {
+            hosts.lock().unwrap().hosts.insert(host.to_string());
+        }
+        if let Some(variants) = &self.query_variants {
+            variants.lock().unwrap().record(url);
+        }
+        queue.push(task);
         
         true
     }
     
-    /// Add multiple URLs
-    pub async fn add_many(&self, urls: Vec<(Url, usize)>) {
-        for (url, depth) in urls {
-            self.add(url, depth).await;
-        }
-    }
-    
-    /// Get the next URL to crawl
-    pub async fn pop(&self) -> Option<CrawlTask> {
+    async fn pop(&self) -> Option<CrawlTask> {
         let mut queue = self.queue.lock().await;
-        queue.pop_front()
+        queue.pop()
     }
     
-    /// Get the current queue size
-    pub async fn size(&self) -> usize {
+    async fn size(&self) -> usize {
         let queue = self.queue.lock().await;
         queue.len()
     }
     
-    /// Check if the frontier is empty
-    pub async fn is_empty(&self) -> bool {
+    async fn is_empty(&self) -> bool {
         let queue = self.queue.lock().await;
         queue.is_empty()
     }
     
-    /// Check if a URL has been seen
-    pub async fn has_seen(&self, url: &Url) -> bool {
+    async fn mark_seen(&self, url: &Url) {
+        let mut url = url.clone();
+        self.trailing_slash.apply(&mut url);
+        self.seen.lock().await.insert(url.to_string());
+    }
+    
+    async fn has_seen(&self, url: &Url) -> bool {
+        let mut url = url.clone();
+        self.trailing_slash.apply(&mut url);
         let seen = self.seen.lock().await;
         seen.contains(url.as_str())
     }
     
-    /// Re-add a failed task with incremented retry count
-    pub async fn retry(&self, mut task: CrawlTask) -> bool {
+    async fn retry(&self, mut task: CrawlTask) -> bool {
         task.retry_count += 1;
         let mut queue = self.queue.lock().await;
         if queue.len() < self.max_size {
-            queue.push_back(task);
+            queue.push(task);
             true
         } else {
             false
         }
     }
     
-    /// Get statistics about the frontier
-    pub async fn stats(&self) -> FrontierStats {
+    async fn snapshot(&self) -> FrontierSnapshot {
+        let queue = self.queue.lock().await;
+        let seen = self.seen.lock().await;
+        FrontierSnapshot {
+            tasks: queue.tasks(),
+            seen: seen.urls(),
+        }
+    }
+    
+    async fn restore(&self, snapshot: FrontierSnapshot) {
+        let mut seen = self.seen.lock().await;
+        let mut queue = self.queue.lock().await;
+        for url in snapshot.seen {
+            // Checkpoints may predate the trailing-slash mode
+            match Url::parse(&url) {
+                Ok(mut parsed) => {
+                    self.trailing_slash.apply(&mut parsed);
+                    seen.insert(parsed.to_string());
+                }
+                Err(_) => {
+                    seen.insert(url);
+                }
+            }
+        }
+        let mut restored = HashSet::new();
+        for mut task in snapshot.tasks {
+            self.trailing_slash.apply(&mut task.url);
+            if !restored.insert(task.url.to_string()) {
+                continue;
+            }
+            if queue.len() >= self.max_size {
+                warn!("Frontier full, dropping restored task {}", task.url);
+                continue;
+            }
+            seen.insert(task.url.to_string());
+            self.discovered.fetch_add(1, AtomicOrdering::SeqCst);
+            queue.push(task);
+        }
+    }
+    
+    async fn stats(&self) -> FrontierStats {
         let queue = self.queue.lock().await;
         let seen = self.seen.lock().await;
         
@@ -106,13 +543,284 @@ impl UrlFrontier {
             queue_size: queue.len(),
             seen_count: seen.len(),
             max_size: self.max_size,
+            discovered_count: self.discovered.load(AtomicOrdering::SeqCst),
+            max_urls_discovered: self.max_urls_discovered,
+        }
+    }
+}
+
+/// Distinct hosts queued, up to a cap
+struct HostLimit {
+    max_hosts: usize,
+    hosts: HashSet<String>,
+}
+
+impl HostLimit {
+    fn admits(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        self.hosts.contains(host) || self.hosts.len() < self.max_hosts
+    }
+}
+
+/// Query-string variants queued per path, up to a cap
+struct QueryVariantLimit {
+    max_variants: usize,
+    counts: HashMap<String, usize>,
+}
+
+impl QueryVariantLimit {
+    fn admits(&self, url: &Url) -> bool {
+        url.query().is_none() || self.counts.get(&path_key(url)).is_none_or(|count| *count < self.max_variants)
+    }
+
+    fn record(&mut self, url: &Url) {
+        if url.query().is_some() {
+            *self.counts.entry(path_key(url)).or_default() += 1;
+        }
+    }
+}
+
+/// A URL without its query string and fragment
+fn path_key(url: &Url) -> String {
+    let mut key = url.clone();
+    key.set_query(None);
+    key.set_fragment(None);
+    key.into()
+}
+
+/// Name of the sled tree holding seen URLs
+const SEEN_TREE: &str = "seen";
+
+/// URLs the frontier has already accepted
+enum SeenSet {
+    Memory(HashSet<String>),
+    Disk(sled::Tree),
+}
+
+impl SeenSet {
+    fn contains(&self, url: &str) -> bool {
+        match self {
+            SeenSet::Memory(urls) => urls.contains(url),
+            SeenSet::Disk(tree) => tree.contains_key(url).unwrap_or_else(|e| {
+                warn!("Failed to look up {} in the dedup database: {}", url, e);
+                false
+            }),
+        }
+    }
+    
+    fn insert(&mut self, url: String) {
+        match self {
+            SeenSet::Memory(urls) => {
+                urls.insert(url);
+            }
+            SeenSet::Disk(tree) => {
+                if let Err(e) = tree.insert(url.as_bytes(), &[]) {
+                    warn!("Failed to record {} in the dedup database: {}", url, e);
+                }
+            }
         }
     }
+    
+    fn len(&self) -> usize {
+        match self {
+            SeenSet::Memory(urls) => urls.len(),
+            SeenSet::Disk(tree) => tree.len(),
+        }
+    }
+    
+    fn urls(&self) -> Vec<String> {
+        match self {
+            SeenSet::Memory(urls) => urls.iter().cloned().collect(),
+            SeenSet::Disk(tree) => tree
+                .iter()
+                .keys()
+                .filter_map(|key| key.ok())
+                .filter_map(|key| String::from_utf8(key.to_vec()).ok())
+                .collect(),
+        }
+    }
+}
+
+/// Queued tasks and seen URLs of a frontier at one point in time
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FrontierSnapshot {
+    /// Tasks in the order they were queued
+    pub tasks: Vec<CrawlTask>,
+    pub seen: Vec<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FrontierStats {
     pub queue_size: usize,
     pub seen_count: usize,
     pub max_size: usize,
-}
\ No newline at end of file
+    /// URLs accepted over the frontier's lifetime
+    pub discovered_count: usize,
+    /// Cap on `discovered_count`, if any
+    pub max_urls_discovered: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn test_dedup_db_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = Url::parse("https://example.com/page").unwrap();
+        
+        {
+            let frontier = UrlFrontier::new(10).with_dedup_db(dir.path()).unwrap();
+            assert!(frontier.add(url.clone(), 0).await);
+            assert!(!frontier.add(url.clone(), 0).await);
+        }
+        
+        let frontier = UrlFrontier::new(10).with_dedup_db(dir.path()).unwrap();
+        assert!(frontier.has_seen(&url).await);
+        assert!(!frontier.add(url, 0).await);
+        assert!(frontier.add(Url::parse("https://example.com/other").unwrap(), 0).await);
+        assert_eq!(frontier.stats().await.seen_count, 2);
+    }
+    
+    #[test]
+    fn test_trailing_slash_modes() {
+        let normalize = |mode: TrailingSlash, url: &str| {
+            let mut url = Url::parse(url).unwrap();
+            mode.apply(&mut url);
+            url.to_string()
+        };
+        
+        for mode in [TrailingSlash::Preserve, TrailingSlash::Strip, TrailingSlash::Add] {
+            assert_eq!(normalize(mode, "https://example.com"), "https://example.com/");
+            assert_eq!(normalize(mode, "https://example.com/?q=1"), "https://example.com/?q=1");
+        }
+        
+        assert_eq!(normalize(TrailingSlash::Preserve, "https://example.com/docs/"), "https://example.com/docs/");
+        assert_eq!(normalize(TrailingSlash::Preserve, "https://example.com/docs"), "https://example.com/docs");
+        
+        assert_eq!(normalize(TrailingSlash::Strip, "https://example.com/docs/"), "https://example.com/docs");
+        assert_eq!(normalize(TrailingSlash::Strip, "https://example.com/docs//?q=1#top"), "https://example.com/docs?q=1#top");
+        assert_eq!(normalize(TrailingSlash::Strip, "https://example.com/docs"), "https://example.com/docs");
+        
+        assert_eq!(normalize(TrailingSlash::Add, "https://example.com/docs"), "https://example.com/docs/");
+        assert_eq!(normalize(TrailingSlash::Add, "https://example.com/docs?q=1"), "https://example.com/docs/?q=1");
+        assert_eq!(normalize(TrailingSlash::Add, "https://example.com/docs/"), "https://example.com/docs/");
+        assert_eq!(normalize(TrailingSlash::Add, "https://example.com/docs/intro.html"), "https://example.com/docs/intro.html");
+    }
+    
+    #[tokio::test]
+    async fn test_trailing_slash_dedup() {
+        let page = Url::parse("https://example.com/a").unwrap();
+        let slashed = Url::parse("https://example.com/a/").unwrap();
+        
+        let frontier = UrlFrontier::new(10);
+        assert!(frontier.add(page.clone(), 0).await);
+        assert!(frontier.add(slashed.clone(), 0).await);
+        
+        let frontier = UrlFrontier::new(10).with_trailing_slash(TrailingSlash::Strip);
+        assert!(frontier.add(slashed.clone(), 0).await);
+        assert!(!frontier.add(page.clone(), 0).await);
+        assert!(frontier.has_seen(&slashed).await);
+        assert_eq!(frontier.pop().await.unwrap().url, page);
+        
+        // URLs marked seen, as from storage, are normalized too
+        let docs = Url::parse("https://example.com/docs").unwrap();
+        let frontier = UrlFrontier::new(10).with_trailing_slash(TrailingSlash::Strip);
+        frontier.mark_seen(&Url::parse("https://example.com/docs/").unwrap()).await;
+        assert!(!frontier.add(docs.clone(), 0).await);
+        
+        // So are the URLs of a checkpoint taken before the mode was set
+        let snapshot = FrontierSnapshot {
+            tasks: vec![
+                CrawlTask::new(slashed.clone(), 0, DEFAULT_PRIORITY, None),
+                CrawlTask::new(page.clone(), 0, DEFAULT_PRIORITY, None),
+            ],
+            seen: vec!["https://example.com/docs/".to_string()],
+        };
+        let frontier = UrlFrontier::new(10).with_trailing_slash(TrailingSlash::Strip);
+        frontier.restore(snapshot).await;
+        assert!(!frontier.add(docs, 0).await);
+        assert_eq!(frontier.pop().await.unwrap().url, page);
+        assert!(frontier.pop().await.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_priority_order() {
+        let frontier = UrlFrontier::new(10);
+        let low = Url::parse("https://example.com/low").unwrap();
+        let high = Url::parse("https://example.com/high").unwrap();
+        let first = Url::parse("https://example.com/first").unwrap();
+        let second = Url::parse("https://example.com/second").unwrap();
+        
+        frontier.add(first.clone(), 0).await;
+        frontier.add_with_priority(low.clone(), 0, 0.1).await;
+        frontier.add(second.clone(), 0).await;
+        frontier.add_with_priority(high.clone(), 0, 1.0).await;
+        
+        assert_eq!(frontier.pop().await.unwrap().url, high);
+        // Equal priorities keep insertion order
+        assert_eq!(frontier.pop().await.unwrap().url, first);
+        assert_eq!(frontier.pop().await.unwrap().url, second);
+        assert_eq!(frontier.pop().await.unwrap().url, low);
+        assert!(frontier.pop().await.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_fair_strategy_interleaves_depths() {
+        let frontier = UrlFrontier::with_strategy(20, CrawlStrategy::Fair);
+        let url = |path: &str| Url::parse(&format!("https://example.com/{}", path)).unwrap();
+        
+        for path in ["a", "b", "c"] {
+            frontier.add(url(path), 0).await;
+        }
+        for path in ["d", "e"] {
+            frontier.add(url(path), 1).await;
+        }
+        frontier.add(url("f"), 2).await;
+        
+        let mut order = Vec::new();
+        while let Some(task) = frontier.pop().await {
+            order.push((task.url.path().to_string(), task.depth));
+        }
+        
+        let expected = [("/a", 0), ("/d", 1), ("/f", 2), ("/b", 0), ("/e", 1), ("/c", 0)];
+        let expected: Vec<(String, usize)> = expected.iter().map(|(p, d)| (p.to_string(), *d)).collect();
+        assert_eq!(order, expected);
+    }
+    
+    #[tokio::test]
+    async fn test_same_site_filter() {
+        let frontier = UrlFrontier::new(10).with_same_site(SameSitePolicy::RegistrableDomain);
+        let seed = Url::parse("https://www.example.co.uk/").unwrap();
+        frontier.add_seed_site(&seed);
+        
+        assert!(frontier.add(seed, 0).await);
+        assert!(frontier.add(Url::parse("https://blog.example.co.uk/").unwrap(), 1).await);
+        assert!(!frontier.add(Url::parse("https://other.co.uk/").unwrap(), 1).await);
+        // Seeds from other sites are still accepted
+        assert!(frontier.add(Url::parse("https://other.co.uk/start").unwrap(), 0).await);
+        assert_eq!(frontier.size().await, 3);
+    }
+    
+    #[tokio::test]
+    async fn test_max_urls_discovered() {
+        let frontier = UrlFrontier::new(10).with_max_urls_discovered(2);
+        let url = |path: &str| Url::parse(&format!("https://example.com/{}", path)).unwrap();
+        
+        assert!(frontier.add(url(""), 0).await);
+        assert!(frontier.add(url("a"), 1).await);
+        // Popping frees queue room but not discovery budget
+        frontier.pop().await.unwrap();
+        frontier.pop().await.unwrap();
+        assert!(frontier.discovery_exhausted());
+        assert!(!frontier.add(url("b"), 1).await);
+        assert!(!frontier.add(url("seed"), 0).await);
+        
+        let stats = frontier.stats().await;
+        assert_eq!(stats.discovered_count, 2);
+        assert_eq!(stats.max_urls_discovered, Some(2));
+        assert_eq!(stats.queue_size, 0);
+    }
+}
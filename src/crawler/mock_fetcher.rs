@@ -0,0 +1,59 @@
+use crate::common::error::{Error, Result};
+use crate::crawler::fetcher::{check_content_type, FetchResponse, HttpFetcher};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use url::Url;
+
+/// In-memory [`HttpFetcher`] serving canned responses by URL, for crawling
+/// a link graph without a network. Unknown URLs answer 404.
+#[derive(Debug, Default)]
+pub struct MockFetcher {
+    responses: HashMap<Url, FetchResponse>,
+    requests: Mutex<Vec<Url>>,
+}
+
+impl MockFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Serve `response` for its URL
+    pub fn insert(&mut self, response: FetchResponse) {
+        self.responses.insert(response.url.clone(), response);
+    }
+    
+    /// Serve an HTML page with status 200
+    pub fn html(mut self, url: &Url, body: &str) -> Self {
+        self.insert(FetchResponse {
+            url: url.clone(),
+            status_code: 200,
+            content_type: Some("text/html".to_string()),
+            body: body.into(),
+            headers: Vec::new(),
+            truncated: false,
+            elapsed: Duration::ZERO,
+            body_bytes: body.len(),
+        });
+        self
+    }
+    
+    /// URLs fetched so far, in order
+    pub fn requests(&self) -> Vec<Url> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl HttpFetcher for MockFetcher {
+    async fn fetch_accepting(&self, url: &Url, content_types: &[&str]) -> Result<FetchResponse> {
+        self.requests.lock().unwrap().push(url.clone());
+        let response = self
+            .responses
+            .get(url)
+            .ok_or_else(|| Error::HttpStatus(404, url.to_string()))?;
+        check_content_type(response.content_type.as_deref(), content_types)?;
+        Ok(response.clone())
+    }
+}
@@ -0,0 +1,221 @@
+//! Minimal in-process HTTP server used by crawler tests and the
+//! [`doctor`](crate::doctor) self-test.
+//!
+//! Serves canned responses per path over plain HTTP/1.1 on `localhost`
+//! and records every request it receives.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Canned response for a path
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub delay: Duration,
+    /// Send the body with chunked transfer encoding instead of a Content-Length
+    pub chunked: bool,
+}
+
+impl MockResponse {
+    pub fn html(body: &str) -> Self {
+        Self::typed("text/html", body.as_bytes())
+    }
+
+    pub fn typed(content_type: &str, body: &[u8]) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("Content-Type".to_string(), content_type.to_string())],
+            body: body.to_vec(),
+            delay: Duration::ZERO,
+            chunked: false,
+        }
+    }
+
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn chunked(mut self) -> Self {
+        self.chunked = true;
+        self
+    }
+}
+
+/// A request received by the mock server
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub received_at: Instant,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Default)]
+struct ServerState {
+    routes: HashMap<String, MockResponse>,
+    requests: Vec<RecordedRequest>,
+    active: usize,
+    max_active: usize,
+}
+
+/// In-process HTTP server bound to an ephemeral port
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<ServerState>>,
+}
+
+impl MockServer {
+    /// Start a server in a background thread
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(ServerState::default()));
+
+        let accept_state = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = accept_state.clone();
+                thread::spawn(move || handle_connection(stream, state));
+            }
+        });
+
+        Self { addr, state }
+    }
+
+    /// Register a response for a path (including any query string)
+    pub fn route(&self, path: &str, response: MockResponse) {
+        self.state.lock().unwrap().routes.insert(path.to_string(), response);
+    }
+
+    /// Register an HTML page for a path
+    pub fn html(&self, path: &str, body: &str) {
+        self.route(path, MockResponse::html(body));
+    }
+
+    /// Socket address the server is listening on
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Absolute URL for a path on this server
+    pub fn url(&self, path: &str) -> Url {
+        Url::parse(&format!("http://localhost:{}{}", self.addr.port(), path)).unwrap()
+    }
+
+    /// All requests received so far
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Highest number of requests served concurrently
+    pub fn max_concurrent(&self) -> usize {
+        self.state.lock().unwrap().max_active
+    }
+
+    /// Number of requests received for a path
+    pub fn hits(&self, path: &str) -> usize {
+        self.requests().iter().filter(|r| r.path == path).count()
+    }
+}
+
+fn handle_connection(stream: TcpStream, state: Arc<Mutex<ServerState>>) {
+    let mut reader = BufReader::new(match stream.try_clone() {
+        Ok(s) => s,
+        Err(_) => return,
+    });
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or("/")
+        .to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let response = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(RecordedRequest {
+            path: path.clone(),
+            headers,
+            received_at: Instant::now(),
+        });
+        state.active += 1;
+        state.max_active = state.max_active.max(state.active);
+        state.routes.get(&path).cloned()
+    };
+    let response = response.unwrap_or_else(|| MockResponse::html("Not Found").status(404));
+
+    if !response.delay.is_zero() {
+        thread::sleep(response.delay);
+    }
+    // Count the request as finished before the client can see the response,
+    // so a client's next request never overlaps with this one
+    state.lock().unwrap().active -= 1;
+
+    let mut out = stream;
+    let mut head = format!("HTTP/1.1 {} Mock\r\n", response.status);
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if response.chunked {
+        head.push_str("Transfer-Encoding: chunked\r\nConnection: close\r\n\r\n");
+        let _ = out.write_all(head.as_bytes());
+        for chunk in response.body.chunks(1024) {
+            let _ = out.write_all(format!("{:x}\r\n", chunk.len()).as_bytes());
+            let _ = out.write_all(chunk);
+            let _ = out.write_all(b"\r\n");
+        }
+        let _ = out.write_all(b"0\r\n\r\n");
+    } else {
+        head.push_str(&format!("Content-Length: {}\r\nConnection: close\r\n\r\n", response.body.len()));
+        let _ = out.write_all(head.as_bytes());
+        let _ = out.write_all(&response.body);
+    }
+    let _ = out.flush();
+}
@@ -1,11 +1,64 @@
 pub mod frontier;
 pub mod fetcher;
+pub mod mock_fetcher;
 pub mod parser;
+pub mod extractor;
+#[allow(clippy::module_inception)]
 pub mod crawler;
 pub mod robots;
+pub mod circuit_breaker;
+pub mod domain_budget;
+pub mod domain_stats;
+pub mod sitemap;
+pub mod api_paginator;
+pub mod response_cache;
+pub mod slow_start;
+pub mod adaptive_delay;
+pub mod rate_limiter;
+pub mod same_site;
+pub mod events;
+pub mod reload;
+pub mod politeness;
+pub mod report;
+pub mod retry;
+pub mod checkpoint;
+pub mod body;
+pub mod buffer_pool;
+pub mod seed_limits;
+pub mod cassette;
+pub mod dns;
+#[cfg(feature = "redis")]
+pub mod redis_frontier;
 
-pub use frontier::{UrlFrontier, CrawlTask};
-pub use fetcher::{Fetcher, FetchResponse};
-pub use parser::{Parser, ParsedPage};
+pub(crate) mod mock_server;
+
+pub use frontier::{Frontier, UrlFrontier, CrawlTask, CrawlStrategy, FrontierSnapshot, TrailingSlash};
+pub use fetcher::{read_body, Fetcher, FetcherConfig, FetchResponse, HttpFetcher, ReferrerPolicy, UserAgentRotation};
+pub use mock_fetcher::MockFetcher;
+pub use parser::{looks_like_fragment, parse_date, truncate_html, Link, MicrodataItem, MicrodataValue, Parser, ParsedPage};
+pub use extractor::{ContentExtractor, ExtractorRegistry};
 pub use crawler::{Crawler, CrawlerBuilder, CrawlStats};
-pub use robots::RobotsChecker;
\ No newline at end of file
+pub use robots::{RobotsChecker, RobotsOverride, RobotsRules};
+pub use circuit_breaker::CircuitBreaker;
+pub use domain_budget::DomainBudget;
+pub use domain_stats::{DomainStats, DomainStatsTracker};
+pub use sitemap::{SitemapEntry, SitemapParser};
+pub use api_paginator::{ApiPage, ApiPaginator};
+pub use response_cache::ResponseCache;
+pub use slow_start::{DomainConcurrency, SlowStartConfig};
+pub use adaptive_delay::{AdaptiveDelay, AdaptiveDelayConfig};
+pub use rate_limiter::TokenBucket;
+pub use same_site::{SameSitePolicy, SiteScope};
+pub use events::{CrawlEvent, SkipReason};
+pub use reload::{LiveSettings, ReloadableConfig};
+pub use politeness::PolitenessProfile;
+pub use report::{CrawlReport, OutputFormat};
+pub use retry::{is_retryable, DefaultRetryPolicy, RetryPolicy};
+pub use checkpoint::Checkpoint;
+pub use buffer_pool::BufferPool;
+pub use body::Body;
+pub use seed_limits::SeedLimits;
+pub use cassette::{RecordingFetcher, ReplayFetcher};
+pub use dns::DnsResolver;
+#[cfg(feature = "redis")]
+pub use redis_frontier::RedisFrontier;
\ No newline at end of file
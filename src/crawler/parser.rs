@@ -1,20 +1,143 @@
 use crate::common::error::{Error, Result};
-use scraper::{Html, Selector};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use regex::Regex;
+use ego_tree::iter::Edge;
+use ego_tree::NodeRef;
+use scraper::{ElementRef, Html, Node, Selector};
 use url::Url;
-use std::collections::HashSet;
+use std::collections::HashMap;
+
+/// Paragraphs shorter than this don't count towards a block's score
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+/// Extracted articles shorter than this are discarded as false positives
+const MIN_ARTICLE_CHARS: usize = 140;
+
+/// `class`/`id` fragments that mark boilerplate blocks
+const BOILERPLATE_HINTS: &[&str] = &["nav", "menu", "footer", "sidebar", "comment", "share", "banner", "ad-"];
+
+/// Link schemes that never lead to a crawlable page
+const SKIPPED_SCHEMES: &[&str] = &["mailto:", "tel:", "javascript:", "data:"];
+
+/// A link found on a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub url: Url,
+    /// Lowercased `rel` tokens (`nofollow`, `sponsored`, `ugc`, ...)
+    pub rel: Vec<String>,
+}
+
+impl Link {
+    /// Check whether the link carries a `rel` token
+    pub fn has_rel(&self, token: &str) -> bool {
+        self.rel.iter().any(|rel| rel.eq_ignore_ascii_case(token))
+    }
+}
+
+/// An item described with HTML microdata (`itemscope`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MicrodataItem {
+    /// `itemtype` URLs, such as `https://schema.org/Product`
+    pub types: Vec<String>,
+    /// Global identifier from `itemid`
+    pub id: Option<String>,
+    /// Values of each `itemprop` name, in document order
+    pub properties: HashMap<String, Vec<MicrodataValue>>,
+}
+
+impl MicrodataItem {
+    /// First value of a property
+    pub fn property(&self, name: &str) -> Option<&MicrodataValue> {
+        self.properties.get(name)?.first()
+    }
+}
+
+/// Value of a microdata property
+#[derive(Debug, Clone, PartialEq)]
+pub enum MicrodataValue {
+    /// Text, or a URL resolved against the page for link and media elements
+    Text(String),
+    /// A nested item, from an element with both `itemprop` and `itemscope`
+    Item(MicrodataItem),
+}
+
+impl MicrodataValue {
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            MicrodataValue::Text(text) => Some(text),
+            MicrodataValue::Item(_) => None,
+        }
+    }
+
+    pub fn as_item(&self) -> Option<&MicrodataItem> {
+        match self {
+            MicrodataValue::Item(item) => Some(item),
+            MicrodataValue::Text(_) => None,
+        }
+    }
+}
 
 /// Extracted data from an HTML page
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ParsedPage {
     pub title: Option<String>,
-    pub links: Vec<Url>,
+    /// Unique links in document order. A URL linked more than once keeps
+    /// only the `rel` tokens shared by every link to it.
+    pub links: Vec<Link>,
     pub text_content: String,
+    /// Next page of a paginated set, from `rel="next"`
+    pub next_url: Option<Url>,
+    /// Previous page of a paginated set, from `rel="prev"`
+    pub prev_url: Option<Url>,
+    /// Declared language, from `<html lang>`
+    pub language: Option<String>,
+    /// Main content with navigation and boilerplate stripped, when the page
+    /// has a recognizable article
+    pub article_text: Option<String>,
+    /// OpenGraph metadata from `<meta property="og:...">`, keyed by the full
+    /// property name (`og:title`). `og:image` is resolved against the page URL.
+    pub open_graph: HashMap<String, String>,
+    /// Addresses from `mailto:` links, when email collection is enabled
+    pub emails: Vec<String>,
+    /// Language variants from `<link rel="alternate" hreflang>`, as
+    /// (hreflang, URL) in document order
+    pub alternates: Vec<(String, Url)>,
+    /// Preferred URL of the page, from `<link rel="canonical">`
+    pub canonical_url: Option<Url>,
+    /// Non-empty `alt` and `title` texts of `<img>` elements, without
+    /// duplicates. Not part of `text_content`.
+    pub image_alts: Vec<String>,
+    /// Publication date, from JSON-LD `datePublished`, then
+    /// `<meta property="article:published_time">`, then `<time datetime>`
+    pub published_at: Option<DateTime<Utc>>,
+    /// Top-level microdata items, with nested items as property values
+    pub microdata: Vec<MicrodataItem>,
 }
 
 /// HTML Parser for extracting links and content
 pub struct Parser {
     link_selector: Selector,
     title_selector: Selector,
+    rel_selector: Selector,
+    html_selector: Selector,
+    paragraph_selector: Selector,
+    article_block_selector: Selector,
+    meta_selector: Selector,
+    alternate_selector: Selector,
+    canonical_selector: Selector,
+    image_selector: Selector,
+    json_ld_selector: Selector,
+    time_selector: Selector,
+    microdata_selector: Selector,
+    collect_emails: bool,
+    /// Elements carrying any of the extra link attributes
+    link_attribute_selector: Option<Selector>,
+    link_attributes: Vec<String>,
+    /// Navigation target inside an event handler, such as
+    /// `location.href='/page'` or `window.open("/page")`
+    handler_url: Regex,
+    /// Nodes nested deeper than this are ignored when extracting text and links
+    max_dom_depth: Option<usize>,
 }
 
 impl Parser {
@@ -23,13 +146,78 @@ impl Parser {
         Self {
             link_selector: Selector::parse("a[href]").unwrap(),
             title_selector: Selector::parse("title").unwrap(),
+            rel_selector: Selector::parse("link[rel][href], a[rel][href]").unwrap(),
+            html_selector: Selector::parse("html[lang]").unwrap(),
+            paragraph_selector: Selector::parse("p").unwrap(),
+            article_block_selector: Selector::parse("h1, h2, h3, h4, h5, h6, p, pre, blockquote, li").unwrap(),
+            meta_selector: Selector::parse("meta[content]").unwrap(),
+            alternate_selector: Selector::parse("link[rel][hreflang][href]").unwrap(),
+            canonical_selector: Selector::parse("link[rel][href]").unwrap(),
+            image_selector: Selector::parse("img[alt], img[title]").unwrap(),
+            json_ld_selector: Selector::parse(r#"script[type="application/ld+json"]"#).unwrap(),
+            time_selector: Selector::parse("time[datetime]").unwrap(),
+            microdata_selector: Selector::parse("[itemscope]:not([itemprop])").unwrap(),
+            collect_emails: false,
+            link_attribute_selector: None,
+            link_attributes: Vec::new(),
+            handler_url: Regex::new(
+                r#"(?:location(?:\.href)?\s*=|location\.(?:assign|replace)\s*\(|window\.open\s*\()\s*['"]([^'"]+)['"]"#,
+            )
+            .unwrap(),
+            max_dom_depth: None,
         }
     }
     
+    /// Collect the addresses of `mailto:` links into [`ParsedPage::emails`]
+    pub fn collect_emails(mut self, collect: bool) -> Self {
+        self.collect_emails = collect;
+        self
+    }
+    
+    /// Ignore text and links nested more than `depth` elements deep, so
+    /// pathological documents can't make extraction crawl
+    pub fn max_dom_depth(mut self, depth: usize) -> Self {
+        self.max_dom_depth = Some(depth);
+        self
+    }
+    
+    /// Also take links from these attributes (`data-href`, `data-url`), for
+    /// sites that navigate with JavaScript instead of `href`s. Event handler
+    /// attributes (`onclick`) are searched for `location.href = '...'` and
+    /// `window.open('...')` targets. Names that aren't plain attribute names
+    /// are ignored.
+    pub fn link_attributes<S: AsRef<str>>(mut self, attributes: &[S]) -> Self {
+        self.link_attributes = attributes
+            .iter()
+            .map(|attribute| attribute.as_ref().trim().to_ascii_lowercase())
+            .filter(|attribute| {
+                !attribute.is_empty()
+                    && attribute.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            })
+            .collect();
+        let selector = self
+            .link_attributes
+            .iter()
+            .map(|attribute| format!("[{}]", attribute))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.link_attribute_selector = Selector::parse(&selector).ok();
+        self
+    }
+    
     /// Parse HTML and extract links and content
     pub fn parse(&self, html: &str, base_url: &Url) -> Result<ParsedPage> {
-        let document = Html::parse_document(html);
-        
+        self.parse_html(&Html::parse_document(html), base_url)
+    }
+    
+    /// Parse partial HTML, such as an AJAX response or an embedded snippet,
+    /// as the contents of a `<body>` rather than as a whole document
+    pub fn parse_fragment(&self, html: &str, base_url: &Url) -> Result<ParsedPage> {
+        self.parse_html(&Html::parse_fragment(html), base_url)
+    }
+    
+    /// Extract links and content from a parsed document or fragment
+    fn parse_html(&self, document: &Html, base_url: &Url) -> Result<ParsedPage> {
         // Extract title
         let title = document
             .select(&self.title_selector)
@@ -37,24 +225,44 @@ impl Parser {
             .map(|el| el.text().collect::<String>().trim().to_string());
         
         // Extract all links
-        let mut links = Vec::new();
-        let mut seen_links = HashSet::new();
+        let mut links: Vec<Link> = Vec::new();
+        let mut seen_links: HashMap<String, usize> = HashMap::new();
+        let mut emails = Vec::new();
         
         for element in document.select(&self.link_selector) {
+            if self.too_deep(*element) {
+                continue;
+            }
             if let Some(href) = element.value().attr("href") {
+                let href = href.trim();
                 // Skip empty hrefs and anchors
                 if href.is_empty() || href.starts_with('#') {
                     continue;
                 }
                 
+                // Skip links that aren't pages, keeping email addresses if asked
+                if let Some(scheme) = skipped_scheme(href) {
+                    if self.collect_emails && scheme == "mailto:" {
+                        for email in mailto_addresses(&href[scheme.len()..]) {
+                            if !emails.contains(&email) {
+                                emails.push(email);
+                            }
+                        }
+                    }
+                    continue;
+                }
+                
                 // Try to resolve the URL
                 match self.resolve_url(href, base_url) {
                     Ok(url) => {
-                        let url_str = url.as_str();
-                        if !seen_links.contains(url_str) {
-                            seen_links.insert(url_str.to_string());
-                            links.push(url);
-                        }
+                        let rel: Vec<String> = element
+                            .value()
+                            .attr("rel")
+                            .unwrap_or_default()
+                            .split_ascii_whitespace()
+                            .map(str::to_ascii_lowercase)
+                            .collect();
+                        add_link(&mut links, &mut seen_links, url, rel);
                     }
                     Err(_) => {
                         // Skip invalid URLs
@@ -64,13 +272,78 @@ impl Parser {
             }
         }
         
+        // Extract links from the extra attributes, if any
+        if let Some(selector) = &self.link_attribute_selector {
+            for element in document.select(selector) {
+                if self.too_deep(*element) {
+                    continue;
+                }
+                for attribute in &self.link_attributes {
+                    let Some(value) = element.value().attr(attribute) else {
+                        continue;
+                    };
+                    let targets: Vec<&str> = if attribute.starts_with("on") {
+                        self.handler_url
+                            .captures_iter(value)
+                            .filter_map(|captures| captures.get(1))
+                            .map(|target| target.as_str())
+                            .collect()
+                    } else {
+                        vec![value]
+                    };
+                    for target in targets {
+                        let target = target.trim();
+                        if target.is_empty()
+                            || target.starts_with('#')
+                            || target.contains(char::is_whitespace)
+                            || skipped_scheme(target).is_some()
+                        {
+                            continue;
+                        }
+                        if let Ok(url) = self.resolve_url(target, base_url) {
+                            add_link(&mut links, &mut seen_links, url, Vec::new());
+                        }
+                    }
+                }
+            }
+        }
+        
+        // Extract pagination links
+        let (next_url, prev_url) = self.extract_pagination(document, base_url);
+        
         // Extract text content (for future search functionality)
-        let text_content = self.extract_text(&document);
+        let text_content = self.extract_text(document);
+        
+        let language = document
+            .select(&self.html_selector)
+            .next()
+            .and_then(|el| el.value().attr("lang"))
+            .map(|lang| lang.trim().to_string())
+            .filter(|lang| !lang.is_empty());
+        
+        let article_text = self.extract_article(document);
+        let open_graph = self.extract_open_graph(document, base_url);
+        let alternates = self.extract_alternates(document, base_url);
+        let canonical_url = self.extract_canonical(document, base_url);
+        let image_alts = self.extract_image_alts(document);
+        let published_at = self.extract_published_at(document);
+        let microdata = self.extract_microdata(document, base_url);
         
         Ok(ParsedPage {
             title,
             links,
             text_content,
+            next_url,
+            prev_url,
+            language,
+            article_text,
+            open_graph,
+            emails,
+            alternates,
+            canonical_url,
+            image_alts,
+            published_at,
+            microdata,
         })
     }
     
@@ -83,38 +356,312 @@ impl Parser {
         
         // Otherwise, join with base URL
         base_url.join(href)
-            .map_err(|e| Error::UrlParseError(e))
+            .map_err(Error::UrlParseError)
+    }
+    
+    /// Find `rel="next"` / `rel="prev"` links from `<link>` or `<a>` elements
+    fn extract_pagination(&self, document: &Html, base_url: &Url) -> (Option<Url>, Option<Url>) {
+        let mut next_url = None;
+        let mut prev_url = None;
+        
+        for element in document.select(&self.rel_selector) {
+            let (Some(rel), Some(href)) = (element.value().attr("rel"), element.value().attr("href")) else {
+                continue;
+            };
+            
+            for token in rel.split_ascii_whitespace() {
+                let slot = match token.to_ascii_lowercase().as_str() {
+                    "next" => &mut next_url,
+                    "prev" | "previous" => &mut prev_url,
+                    _ => continue,
+                };
+                if slot.is_none() {
+                    *slot = self.resolve_url(href, base_url).ok();
+                }
+            }
+        }
+        
+        (next_url, prev_url)
+    }
+    
+    /// Find the first `<link rel="canonical">` pointing at a web page
+    fn extract_canonical(&self, document: &Html, base_url: &Url) -> Option<Url> {
+        document.select(&self.canonical_selector).find_map(|element| {
+            let element = element.value();
+            let rel = element.attr("rel")?;
+            if !rel.split_ascii_whitespace().any(|token| token.eq_ignore_ascii_case("canonical")) {
+                return None;
+            }
+            let mut url = self.resolve_url(element.attr("href")?.trim(), base_url).ok()?;
+            url.set_fragment(None);
+            matches!(url.scheme(), "http" | "https").then_some(url)
+        })
+    }
+    
+    /// Find `<link rel="alternate" hreflang>` language variants. The first
+    /// URL given for a language wins; malformed language codes are skipped.
+    fn extract_alternates(&self, document: &Html, base_url: &Url) -> Vec<(String, Url)> {
+        let mut alternates: Vec<(String, Url)> = Vec::new();
+        for element in document.select(&self.alternate_selector) {
+            let element = element.value();
+            let (Some(rel), Some(hreflang), Some(href)) =
+                (element.attr("rel"), element.attr("hreflang"), element.attr("href"))
+            else {
+                continue;
+            };
+            if !rel.split_ascii_whitespace().any(|token| token.eq_ignore_ascii_case("alternate")) {
+                continue;
+            }
+            let hreflang = hreflang.trim();
+            if !is_language_code(hreflang) || alternates.iter().any(|(lang, _)| lang.eq_ignore_ascii_case(hreflang)) {
+                continue;
+            }
+            if let Ok(url) = self.resolve_url(href.trim(), base_url) {
+                alternates.push((hreflang.to_string(), url));
+            }
+        }
+        alternates
     }
     
     /// Extract visible text content from the document
     fn extract_text(&self, document: &Html) -> String {
         let mut text = String::new();
+        let max_depth = self.max_dom_depth.unwrap_or(usize::MAX);
         
-        // Simple text extraction - just get all text nodes
-        for node in document.root_element().descendants() {
-            if let Some(text_node) = node.value().as_text() {
-                let text_str = text_node.trim();
-                if !text_str.is_empty() {
-                    text.push_str(text_str);
-                    text.push(' ');
+        // Simple text extraction - just get all text nodes, tracking the
+        // depth as the walk enters and leaves nodes
+        let mut depth = 0;
+        for edge in document.root_element().traverse() {
+            match edge {
+                Edge::Open(node) => {
+                    depth += 1;
+                    if depth > max_depth {
+                        continue;
+                    }
+                    if let Some(text_node) = node.value().as_text() {
+                        let text_str = text_node.trim();
+                        if !text_str.is_empty() {
+                            text.push_str(text_str);
+                            text.push(' ');
+                        }
+                    }
                 }
+                Edge::Close(_) => depth -= 1,
             }
         }
         
         text.trim().to_string()
     }
     
+    /// Whether a node is nested deeper than `max_dom_depth`
+    fn too_deep(&self, node: NodeRef<Node>) -> bool {
+        self.max_dom_depth.is_some_and(|max| node.ancestors().count() > max)
+    }
+    
+    /// Collect the `alt` and `title` texts of images, whitespace collapsed
+    fn extract_image_alts(&self, document: &Html) -> Vec<String> {
+        let mut alts: Vec<String> = Vec::new();
+        for element in document.select(&self.image_selector) {
+            for attr in ["alt", "title"] {
+                let Some(text) = element.value().attr(attr) else {
+                    continue;
+                };
+                let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                if !text.is_empty() && !alts.contains(&text) {
+                    alts.push(text);
+                }
+            }
+        }
+        alts
+    }
+    
+    /// Collect the microdata items that aren't a property of another item
+    fn extract_microdata(&self, document: &Html, base_url: &Url) -> Vec<MicrodataItem> {
+        document
+            .select(&self.microdata_selector)
+            .map(|scope| self.microdata_item(scope, base_url))
+            .collect()
+    }
+    
+    /// Read the item of an `itemscope` element: the properties below it,
+    /// down to but not into nested scopes. `itemref` isn't followed.
+    fn microdata_item(&self, scope: ElementRef, base_url: &Url) -> MicrodataItem {
+        let attr = |name| scope.value().attr(name).map(str::trim).filter(|value| !value.is_empty());
+        let mut item = MicrodataItem {
+            types: attr("itemtype").unwrap_or_default().split_ascii_whitespace().map(str::to_string).collect(),
+            id: attr("itemid").map(str::to_string),
+            properties: HashMap::new(),
+        };
+        
+        let mut pending: Vec<ElementRef> = scope.children().filter_map(ElementRef::wrap).rev().collect();
+        while let Some(element) = pending.pop() {
+            let nested = element.value().attr("itemscope").is_some();
+            if let Some(names) = element.value().attr("itemprop") {
+                let value = if nested {
+                    MicrodataValue::Item(self.microdata_item(element, base_url))
+                } else {
+                    MicrodataValue::Text(self.microdata_value(element, base_url))
+                };
+                for name in names.split_ascii_whitespace() {
+                    item.properties.entry(name.to_string()).or_default().push(value.clone());
+                }
+            }
+            if !nested {
+                pending.extend(element.children().filter_map(ElementRef::wrap).rev());
+            }
+        }
+        item
+    }
+    
+    /// Value of a property element, read from the attribute the microdata
+    /// spec names for its tag, or its text
+    fn microdata_value(&self, element: ElementRef, base_url: &Url) -> String {
+        let value = element.value();
+        let url = |name| {
+            let href = value.attr(name).unwrap_or_default().trim();
+            self.resolve_url(href, base_url).map_or_else(|_| href.to_string(), |url| url.to_string())
+        };
+        let text = || element.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ");
+        match value.name() {
+            "meta" => value.attr("content").unwrap_or_default().trim().to_string(),
+            "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => url("src"),
+            "a" | "area" | "link" => url("href"),
+            "object" => url("data"),
+            "data" | "meter" => value.attr("value").unwrap_or_default().trim().to_string(),
+            "time" => value.attr("datetime").map_or_else(text, |datetime| datetime.trim().to_string()),
+            _ => text(),
+        }
+    }
+    
+    /// Find the publication date, taking the first parseable date of the
+    /// highest-precedence source
+    fn extract_published_at(&self, document: &Html) -> Option<DateTime<Utc>> {
+        let json_ld = || {
+            document.select(&self.json_ld_selector).find_map(|script| {
+                let value = serde_json::from_str(&script.text().collect::<String>()).ok()?;
+                json_ld_date_published(&value)
+            })
+        };
+        let meta = || {
+            document.select(&self.meta_selector).find_map(|meta| {
+                let element = meta.value();
+                let property = element.attr("property").or_else(|| element.attr("name"))?;
+                if !property.trim().eq_ignore_ascii_case("article:published_time") {
+                    return None;
+                }
+                parse_date(element.attr("content")?)
+            })
+        };
+        let time = || {
+            document
+                .select(&self.time_selector)
+                .find_map(|time| parse_date(time.value().attr("datetime")?))
+        };
+        json_ld().or_else(meta).or_else(time)
+    }
+    
+    /// Collect `og:*` meta tags, given as either `property=` or `name=`.
+    /// The first occurrence of a property wins.
+    fn extract_open_graph(&self, document: &Html, base_url: &Url) -> HashMap<String, String> {
+        let mut open_graph = HashMap::new();
+        for meta in document.select(&self.meta_selector) {
+            let element = meta.value();
+            let Some(property) = element.attr("property").or_else(|| element.attr("name")) else {
+                continue;
+            };
+            let property = property.trim().to_lowercase();
+            let content = element.attr("content").unwrap_or("").trim();
+            if !property.starts_with("og:") || content.is_empty() || open_graph.contains_key(&property) {
+                continue;
+            }
+            
+            let content = if property == "og:image" {
+                match self.resolve_url(content, base_url) {
+                    Ok(url) => url.to_string(),
+                    Err(_) => continue,
+                }
+            } else {
+                content.to_string()
+            };
+            open_graph.insert(property, content);
+        }
+        open_graph
+    }
+    
+    /// Extract the main article text, readability style.
+    ///
+    /// Each paragraph scores its parent block by length (and its grandparent
+    /// by half that). Blocks are then discounted by their link density and
+    /// boilerplate `class`/`id` hints, and the text of the best block is
+    /// returned, one paragraph or heading per line.
+    pub fn extract_article(&self, document: &Html) -> Option<String> {
+        let mut scores = HashMap::new();
+        for paragraph in document.select(&self.paragraph_selector) {
+            let len = normalized_text(&paragraph).len();
+            if len < MIN_PARAGRAPH_CHARS {
+                continue;
+            }
+            let score = 1.0 + (len as f64 / 100.0).min(3.0);
+            
+            let mut weight = 1.0;
+            for ancestor in paragraph.ancestors().filter_map(ElementRef::wrap).take(2) {
+                let entry = scores.entry(ancestor.id()).or_insert((ancestor, 0.0));
+                entry.1 += score * weight;
+                weight /= 2.0;
+            }
+        }
+        
+        let (best, _) = scores
+            .into_values()
+            .map(|(block, score)| (block, score * (1.0 - self.link_density(&block)) * boilerplate_factor(&block)))
+            .filter(|(_, score)| *score > 0.0)
+            .max_by(|a, b| a.1.total_cmp(&b.1))?;
+        
+        let article: Vec<String> = best
+            .select(&self.article_block_selector)
+            // Nested blocks (a `p` in a `blockquote`) are covered by their parent
+            .filter(|block| {
+                !block
+                    .ancestors()
+                    .filter_map(ElementRef::wrap)
+                    .take_while(|ancestor| ancestor.id() != best.id())
+                    .any(|ancestor| self.article_block_selector.matches(&ancestor))
+            })
+            .map(|block| normalized_text(&block))
+            .filter(|text| !text.is_empty())
+            .collect();
+        let article = article.join("\n");
+        
+        (article.len() >= MIN_ARTICLE_CHARS).then_some(article)
+    }
+    
+    /// Share of an element's text that sits inside links
+    fn link_density(&self, element: &ElementRef) -> f64 {
+        let total = normalized_text(element).len();
+        if total == 0 {
+            return 1.0;
+        }
+        let linked: usize = element
+            .select(&self.link_selector)
+            .map(|link| normalized_text(&link).len())
+            .sum();
+        (linked as f64 / total as f64).min(1.0)
+    }
+    
     /// Filter links to only include crawlable URLs
     pub fn filter_links(&self, links: Vec<Url>) -> Vec<Url> {
+        self.filter_links_with_schemes(links, &["http", "https"])
+    }
+    
+    /// Filter links like [`Parser::filter_links`], keeping those with any
+    /// of `schemes`
+    pub fn filter_links_with_schemes(&self, links: Vec<Url>, schemes: &[&str]) -> Vec<Url> {
         links.into_iter()
-            .filter(|url| {
-                // Only HTTP(S) URLs
-                matches!(url.scheme(), "http" | "https")
-            })
+            .filter(|url| schemes.contains(&url.scheme()))
             .filter(|url| {
                 // Skip common non-HTML extensions
-                if let Some(path) = url.path_segments() {
-                    if let Some(last) = path.last() {
+                if let Some(mut path) = url.path_segments() {
+                    if let Some(last) = path.next_back() {
                         let skip_extensions = [
                             ".jpg", ".jpeg", ".png", ".gif", ".webp",
                             ".pdf", ".zip", ".mp3", ".mp4", ".css", ".js"
@@ -133,8 +680,520 @@ impl Parser {
     }
 }
 
+/// Text of an element with whitespace collapsed
+fn normalized_text(element: &ElementRef) -> String {
+    element.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Penalty for blocks whose tag, `class` or `id` suggests boilerplate
+fn boilerplate_factor(element: &ElementRef) -> f64 {
+    let value = element.value();
+    if matches!(value.name(), "nav" | "footer" | "aside" | "header") {
+        return 0.0;
+    }
+    let hints = format!("{} {}", value.attr("class").unwrap_or(""), value.attr("id").unwrap_or("")).to_lowercase();
+    if BOILERPLATE_HINTS.iter().any(|hint| hints.contains(hint)) {
+        0.25
+    } else {
+        1.0
+    }
+}
+
 impl Default for Parser {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+/// Loose BCP 47 check: `x-default`, or a 2-3 letter language followed by
+/// alphanumeric subtags (`en`, `en-GB`, `zh-Hant-TW`)
+fn is_language_code(code: &str) -> bool {
+    if code.eq_ignore_ascii_case("x-default") {
+        return true;
+    }
+    let mut subtags = code.split(['-', '_']);
+    let language = subtags.next().unwrap_or_default();
+    (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_alphabetic())
+        && subtags.all(|subtag| (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// Cut an HTML document to at most `max_len` bytes, ending before the last
+/// tag that would be split. Returns the document unchanged if it fits.
+pub fn truncate_html(html: &str, max_len: usize) -> &str {
+    if html.len() <= max_len {
+        return html;
+    }
+    let mut end = max_len;
+    while !html.is_char_boundary(end) {
+        end -= 1;
+    }
+    let head = &html[..end];
+    match head.rfind('<') {
+        Some(tag_start) if tag_start > 0 => &head[..tag_start],
+        _ => head,
+    }
+}
+
+/// Add a link unless its URL was seen before, in which case the earlier
+/// link keeps only the `rel` tokens both share
+fn add_link(links: &mut Vec<Link>, seen_links: &mut HashMap<String, usize>, url: Url, rel: Vec<String>) {
+    match seen_links.get(url.as_str()) {
+        Some(&i) => links[i].rel.retain(|token| rel.contains(token)),
+        None => {
+            seen_links.insert(url.to_string(), links.len());
+            links.push(Link { url, rel });
+        }
+    }
+}
+
+/// Whether HTML is a fragment rather than a document: it has none of
+/// `<!doctype>`, `<html>`, `<head>` or `<body>`
+pub fn looks_like_fragment(html: &str) -> bool {
+    let html = html.to_ascii_lowercase();
+    !["<!doctype", "<html", "<head", "<body"].iter().any(|tag| html.contains(tag))
+}
+
+/// The skipped scheme an href starts with, matched case-insensitively
+fn skipped_scheme(href: &str) -> Option<&'static str> {
+    SKIPPED_SCHEMES.iter().copied().find(|scheme| {
+        href.get(..scheme.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+    })
+}
+
+/// Addresses of a `mailto:` link, without the scheme or `?subject=...`
+fn mailto_addresses(target: &str) -> Vec<String> {
+    let addresses = target.split('?').next().unwrap_or_default();
+    url::form_urlencoded::parse(format!("to={}", addresses.replace('+', "%2B")).as_bytes())
+        .flat_map(|(_, value)| {
+            value
+                .split(',')
+                .map(|address| address.trim().to_string())
+                .filter(|address| address.contains('@'))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// First parseable `datePublished` in a JSON-LD value, searching nested
+/// objects and arrays such as `@graph`
+fn json_ld_date_published(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    match value {
+        serde_json::Value::Object(object) => object
+            .get("datePublished")
+            .and_then(serde_json::Value::as_str)
+            .and_then(parse_date)
+            .or_else(|| object.values().find_map(json_ld_date_published)),
+        serde_json::Value::Array(values) => values.iter().find_map(json_ld_date_published),
+        _ => None,
+    }
+}
+
+/// Naive date-time formats accepted by [`parse_date`], read as UTC
+const NAIVE_DATE_TIME_FORMATS: &[&str] =
+    &["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"];
+
+/// Date formats accepted by [`parse_date`], read as midnight UTC
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%B %d, %Y", "%b %d, %Y", "%d %B %Y", "%d %b %Y"];
+
+/// Leniently parse a date as found in page markup or HTTP headers: RFC 3339,
+/// RFC 2822 / HTTP dates, ISO 8601 with a `+0000` offset, and date-times or
+/// dates without a zone, which are taken as UTC
+pub fn parse_date(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    if let Ok(date) = DateTime::parse_from_rfc3339(text) {
+        return Some(date.with_timezone(&Utc));
+    }
+    if let Ok(date) = DateTime::parse_from_rfc2822(text) {
+        return Some(date.with_timezone(&Utc));
+    }
+    if let Ok(date) = DateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f%z") {
+        return Some(date.with_timezone(&Utc));
+    }
+    if let Some(date) = NAIVE_DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+    {
+        return Some(date.and_utc());
+    }
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date| date.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_truncate_html_at_tag_boundary() {
+        let html = "<p>short</p><p>héllo world</p>";
+        assert_eq!(truncate_html(html, 100), html);
+        assert_eq!(truncate_html(html, 20), "<p>short</p>");
+        assert_eq!(truncate_html("héllo", 2), "h");
+        assert_eq!(truncate_html("<p>plain text", 8), "<p>plain");
+    }
+    
+    #[test]
+    fn test_pagination_links() {
+        let parser = Parser::new();
+        let base = Url::parse("https://example.com/archive/page/2").unwrap();
+        let html = r#"
+            <html><head>
+                <link rel="prev" href="/archive/page/1">
+            </head><body>
+                <a href="/about">About</a>
+                <a rel="next nofollow" href="3">Older posts</a>
+            </body></html>
+        "#;
+        
+        let page = parser.parse(html, &base).unwrap();
+        
+        assert_eq!(page.next_url.unwrap().as_str(), "https://example.com/archive/page/3");
+        assert_eq!(page.prev_url.unwrap().as_str(), "https://example.com/archive/page/1");
+    }
+    
+    #[test]
+    fn test_declared_language() {
+        let parser = Parser::new();
+        let base = Url::parse("https://example.com/").unwrap();
+        
+        let page = parser.parse(r#"<html lang="fr-CA"><body>Bonjour</body></html>"#, &base).unwrap();
+        assert_eq!(page.language.as_deref(), Some("fr-CA"));
+        
+        let page = parser.parse("<html><body>Hello</body></html>", &base).unwrap();
+        assert!(page.language.is_none());
+    }
+    
+    #[test]
+    fn test_extract_article() {
+        let parser = Parser::new();
+        let base = Url::parse("https://example.com/post").unwrap();
+        let html = r#"
+            <html><body>
+                <nav class="site-nav"><ul>
+                    <li><a href="/">Home navigation link</a></li>
+                    <li><a href="/blog">Blog navigation link</a></li>
+                    <li><a href="/about">About navigation link</a></li>
+                </ul></nav>
+                <div class="content">
+                    <h1>Ownership in Rust</h1>
+                    <p>Ownership is the set of rules that govern how a Rust program manages memory.</p>
+                    <p>Every value has a single owner, and the value is dropped when the owner goes out of scope.</p>
+                    <p>Borrowing lets code use a value without taking ownership, as explained <a href="/borrowing">here</a>.</p>
+                </div>
+                <div class="sidebar"><p>Subscribe to the newsletter for weekly updates and offers.</p></div>
+                <footer><p>Copyright 2024 Example Corp. All rights reserved worldwide.</p></footer>
+            </body></html>
+        "#;
+        
+        let page = parser.parse(html, &base).unwrap();
+        let article = page.article_text.unwrap();
+        
+        assert!(article.starts_with("Ownership in Rust\n"));
+        assert!(article.contains("single owner"));
+        assert!(article.contains("as explained here."));
+        assert!(!article.contains("navigation"));
+        assert!(!article.contains("newsletter"));
+        assert!(!article.contains("Copyright"));
+        // The full text dump still has everything
+        assert!(page.text_content.contains("Copyright"));
+    }
+    
+    #[test]
+    fn test_no_article_on_link_pages() {
+        let parser = Parser::new();
+        let base = Url::parse("https://example.com/").unwrap();
+        let links: String = (0..20).map(|i| format!(r#"<li><a href="/{}">Link number {}</a></li>"#, i, i)).collect();
+        
+        let page = parser.parse(&format!("<html><body><ul>{}</ul></body></html>", links), &base).unwrap();
+        assert!(page.article_text.is_none());
+    }
+    
+    #[test]
+    fn test_open_graph() {
+        let parser = Parser::new();
+        let base = Url::parse("https://example.com/posts/rust").unwrap();
+        let html = r#"
+            <html><head>
+                <meta property="og:title" content="Learning Rust">
+                <meta name="og:description" content=" A gentle introduction ">
+                <meta property="og:image" content="/images/rust.png">
+                <meta property="OG:TYPE" content="article">
+                <meta property="og:title" content="Duplicate title">
+                <meta name="description" content="Not OpenGraph">
+                <meta property="og:locale" content="">
+            </head><body></body></html>
+        "#;
+        
+        let og = parser.parse(html, &base).unwrap().open_graph;
+        
+        assert_eq!(og.len(), 4);
+        assert_eq!(og["og:title"], "Learning Rust");
+        assert_eq!(og["og:description"], "A gentle introduction");
+        assert_eq!(og["og:image"], "https://example.com/images/rust.png");
+        assert_eq!(og["og:type"], "article");
+    }
+    
+    #[test]
+    fn test_parse_fragment() {
+        let base = Url::parse("https://example.com/list/").unwrap();
+        let html = r#"<ul><li><a href="one">One</a></li><li><a href="/two">Two</a></li></ul>"#;
+        
+        assert!(looks_like_fragment(html));
+        assert!(!looks_like_fragment("<!DOCTYPE html><p>Page</p>"));
+        let page = Parser::new().parse_fragment(html, &base).unwrap();
+        
+        let links: Vec<&str> = page.links.iter().map(|link| link.url.as_str()).collect();
+        assert_eq!(links, vec!["https://example.com/list/one", "https://example.com/two"]);
+        assert_eq!(page.text_content, "One Two");
+    }
+    
+    #[test]
+    fn test_max_dom_depth() {
+        let base = Url::parse("https://example.com/").unwrap();
+        let html = format!(
+            r#"<html><body><p>Shallow <a href="/near">near</a></p>{}<p>Buried <a href="/far">far</a></p>{}</body></html>"#,
+            "<div>".repeat(2000),
+            "</div>".repeat(2000)
+        );
+        
+        let page = Parser::new().max_dom_depth(64).parse(&html, &base).unwrap();
+        
+        assert_eq!(page.text_content, "Shallow near");
+        let links: Vec<&str> = page.links.iter().map(|link| link.url.as_str()).collect();
+        assert_eq!(links, vec!["https://example.com/near"]);
+    }
+    
+    #[test]
+    fn test_link_attributes() {
+        let base = Url::parse("https://example.com/shop/").unwrap();
+        let html = r#"
+            <a href="/cart">Cart</a>
+            <div class="card" data-href="item/1">Item 1</div>
+            <div class="card" data-href="/cart">Cart again</div>
+            <button onclick="location.href='/checkout'; return false">Buy</button>
+            <span data-href="javascript:void(0)">Nothing</span>
+        "#;
+        
+        let links = |page: ParsedPage| page.links.into_iter().map(|link| link.url.to_string()).collect::<Vec<_>>();
+        
+        assert_eq!(links(Parser::new().parse(html, &base).unwrap()), vec!["https://example.com/cart"]);
+        
+        let parser = Parser::new().link_attributes(&["data-href", "onclick"]);
+        assert_eq!(
+            links(parser.parse(html, &base).unwrap()),
+            vec!["https://example.com/cart", "https://example.com/shop/item/1", "https://example.com/checkout"]
+        );
+    }
+    
+    #[test]
+    fn test_non_page_schemes_skipped() {
+        let base = Url::parse("https://example.com/").unwrap();
+        let html = r#"
+            <a href="/contact">Contact</a>
+            <a href="mailto:info@example.com?subject=Hi">Mail</a>
+            <a href="MAILTO:Sales%40example.com,support+web@example.com">Mail both</a>
+            <a href="tel:+15550100">Call</a>
+            <a href=" javascript:void(0)">Menu</a>
+            <a href="data:text/plain,hello">Data</a>
+            <a href="mailto:info@example.com">Mail again</a>
+        "#;
+        
+        let page = Parser::new().parse(html, &base).unwrap();
+        let urls: Vec<&str> = page.links.iter().map(|link| link.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://example.com/contact"]);
+        assert!(page.emails.is_empty());
+        
+        let page = Parser::new().collect_emails(true).parse(html, &base).unwrap();
+        assert_eq!(page.links.len(), 1);
+        assert_eq!(
+            page.emails,
+            vec!["info@example.com", "Sales@example.com", "support+web@example.com"]
+        );
+    }
+    
+    #[test]
+    fn test_canonical_url() {
+        let parser = Parser::new();
+        let base = Url::parse("https://example.com/page?utm_source=feed").unwrap();
+        let html = r#"
+            <html><head>
+                <link rel="stylesheet" href="/style.css">
+                <link rel="Canonical" href="/page#top">
+                <link rel="canonical" href="/other">
+            </head><body><a rel="canonical" href="/anchor">a</a></body></html>
+        "#;
+        
+        let page = parser.parse(html, &base).unwrap();
+        assert_eq!(page.canonical_url.unwrap().as_str(), "https://example.com/page");
+        assert_eq!(parser.parse("<title>None</title>", &base).unwrap().canonical_url, None);
+    }
+    
+    #[test]
+    fn test_image_alts() {
+        let parser = Parser::new();
+        let base = Url::parse("https://example.com/").unwrap();
+        let html = r#"
+            <html><body>
+                <p>Our trip</p>
+                <img src="a.jpg" alt="Sunset over  the bay" title="Sunset over the bay">
+                <img src="b.jpg" alt="" title="Harbour at dawn">
+                <img src="c.jpg" alt="   ">
+                <img src="d.jpg">
+            </body></html>
+        "#;
+        
+        let page = parser.parse(html, &base).unwrap();
+        assert_eq!(page.image_alts, vec!["Sunset over the bay", "Harbour at dawn"]);
+        assert!(page.text_content.contains("Our trip"));
+        assert!(!page.text_content.contains("Sunset"));
+    }
+    
+    #[test]
+    fn test_published_at_precedence() {
+        let parser = Parser::new();
+        let base = Url::parse("https://example.com/post").unwrap();
+        let json_ld = r#"<script type="application/ld+json">
+            {"@context": "https://schema.org", "@graph": [
+                {"@type": "WebSite", "name": "Blog"},
+                {"@type": "BlogPosting", "datePublished": "2024-03-05T09:30:00+02:00"}
+            ]}
+        </script>"#;
+        let meta = r#"<meta property="article:published_time" content="2024-02-01">"#;
+        let time = r#"<time datetime="not a date">soon</time><time datetime="2024-01-15 08:00">Jan 15</time>"#;
+        let date = |html: &str| parser.parse(html, &base).unwrap().published_at.map(|date| date.to_rfc3339());
+        
+        assert_eq!(date(time).as_deref(), Some("2024-01-15T08:00:00+00:00"));
+        assert_eq!(date(&format!("{time}{meta}")).as_deref(), Some("2024-02-01T00:00:00+00:00"));
+        assert_eq!(date(&format!("{time}{meta}{json_ld}")).as_deref(), Some("2024-03-05T07:30:00+00:00"));
+        assert_eq!(date("<p>No dates</p>"), None);
+    }
+    
+    #[test]
+    fn test_parse_date_formats() {
+        let expected = "2024-03-05T00:00:00+00:00";
+        for text in [
+            "2024-03-05T00:00:00Z",
+            "Tue, 05 Mar 2024 00:00:00 GMT",
+            "2024-03-05T01:00:00.000+0100",
+            "2024-03-05 00:00:00",
+            " 2024-03-05 ",
+            "2024/03/05",
+            "March 5, 2024",
+            "5 Mar 2024",
+        ] {
+            assert_eq!(parse_date(text).map(|date| date.to_rfc3339()).as_deref(), Some(expected), "{text}");
+        }
+        assert_eq!(parse_date("yesterday"), None);
+        assert_eq!(parse_date(""), None);
+    }
+    
+    #[test]
+    fn test_hreflang_alternates() {
+        let parser = Parser::new();
+        let base = Url::parse("https://example.com/en/page").unwrap();
+        let html = r#"
+            <html><head>
+                <link rel="alternate" hreflang="en" href="https://example.com/en/page">
+                <link rel="alternate" hreflang="de-AT" href="/de-at/seite">
+                <link rel="Alternate" hreflang="fr" href="../fr/page">
+                <link rel="alternate" hreflang="x-default" href="/">
+                <link rel="alternate" hreflang="fr" href="/fr/duplicate">
+                <link rel="alternate" hreflang="not a language" href="/bogus">
+                <link rel="canonical" hreflang="es" href="/es/page">
+                <link rel="alternate" type="application/rss+xml" href="/feed">
+            </head><body></body></html>
+        "#;
+        
+        let alternates = parser.parse(html, &base).unwrap().alternates;
+        
+        let expected: Vec<(String, Url)> = [
+            ("en", "https://example.com/en/page"),
+            ("de-AT", "https://example.com/de-at/seite"),
+            ("fr", "https://example.com/fr/page"),
+            ("x-default", "https://example.com/"),
+        ]
+        .into_iter()
+        .map(|(lang, url)| (lang.to_string(), Url::parse(url).unwrap()))
+        .collect();
+        assert_eq!(alternates, expected);
+    }
+    
+    #[test]
+    fn test_link_rel_tokens() {
+        let base = Url::parse("https://example.com/").unwrap();
+        let html = r#"
+            <a href="/plain">Plain</a>
+            <a href="/ad" rel="Sponsored NOFOLLOW">Ad</a>
+            <a href="/both" rel="nofollow">Comment</a>
+            <a href="/both" rel="author">Author</a>
+        "#;
+        
+        let links = Parser::new().parse(html, &base).unwrap().links;
+        
+        assert_eq!(links.len(), 3);
+        assert!(links[0].rel.is_empty());
+        assert_eq!(links[1].rel, vec!["sponsored", "nofollow"]);
+        assert!(links[1].has_rel("nofollow"));
+        // Followed from somewhere, so not nofollow overall
+        assert!(!links[2].has_rel("nofollow"));
+    }
+    
+    #[test]
+    fn test_no_pagination_links() {
+        let parser = Parser::new();
+        let base = Url::parse("https://example.com/").unwrap();
+        
+        let page = parser.parse(r#"<a href="/a">a</a>"#, &base).unwrap();
+        
+        assert!(page.next_url.is_none());
+        assert!(page.prev_url.is_none());
+    }
+    
+    #[test]
+    fn test_product_microdata() {
+        let parser = Parser::new();
+        let base = Url::parse("https://shop.example.com/kettle").unwrap();
+        let html = r#"
+            <div itemscope itemtype="https://schema.org/Product" itemid="urn:sku:K-100">
+                <h1 itemprop="name">Copper   Kettle</h1>
+                <img itemprop="image" src="/img/kettle.jpg" alt="Kettle">
+                <div>
+                    <span itemprop="brand">Acme</span>
+                </div>
+                <div itemprop="offers" itemscope itemtype="https://schema.org/Offer">
+                    <meta itemprop="priceCurrency" content="EUR">
+                    <span itemprop="price">39.90</span>
+                    <link itemprop="availability" href="https://schema.org/InStock">
+                </div>
+            </div>
+            <div itemscope itemtype="https://schema.org/Organization"><span itemprop="name">Acme</span></div>
+        "#;
+        
+        let page = parser.parse(html, &base).unwrap();
+        assert_eq!(page.microdata.len(), 2);
+        let product = &page.microdata[0];
+        assert_eq!(product.types, ["https://schema.org/Product"]);
+        assert_eq!(product.id.as_deref(), Some("urn:sku:K-100"));
+        let text = |item: &MicrodataItem, name| item.property(name).and_then(MicrodataValue::as_text).map(str::to_string);
+        assert_eq!(text(product, "name").as_deref(), Some("Copper Kettle"));
+        assert_eq!(text(product, "image").as_deref(), Some("https://shop.example.com/img/kettle.jpg"));
+        assert_eq!(text(product, "brand").as_deref(), Some("Acme"));
+        // Properties of the nested offer belong to it, not the product
+        assert!(product.property("price").is_none());
+        
+        let offer = product.property("offers").and_then(MicrodataValue::as_item).unwrap();
+        assert_eq!(offer.types, ["https://schema.org/Offer"]);
+        assert_eq!(text(offer, "price").as_deref(), Some("39.90"));
+        assert_eq!(text(offer, "priceCurrency").as_deref(), Some("EUR"));
+        assert_eq!(text(offer, "availability").as_deref(), Some("https://schema.org/InStock"));
+        assert_eq!(page.microdata[1].types, ["https://schema.org/Organization"]);
+    }
+}
@@ -0,0 +1,35 @@
+use crate::crawler::crawler::CrawlerConfig;
+use crate::crawler::SlowStartConfig;
+
+/// Named presets for how hard the crawler pushes on the sites it visits.
+///
+/// | Profile      | `delay_ms` | `max_concurrent` | jitter | per-domain concurrency |
+/// |--------------|-----------:|-----------------:|-------:|------------------------|
+/// | `Gentle`     | 2000       | 2                | ±25%   | 1                      |
+/// | `Balanced`   | 1000       | 10               | ±10%   | ramps from 1 to 4      |
+/// | `Aggressive` | 250        | 32               | none   | ramps from 2 to 16     |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolitenessProfile {
+    Gentle,
+    Balanced,
+    Aggressive,
+}
+
+impl PolitenessProfile {
+    /// Set the profile's delay, concurrency, jitter and per-domain limits
+    pub fn apply(self, config: &mut CrawlerConfig) {
+        let (delay_ms, max_concurrent, jitter, initial_limit, max_limit) = match self {
+            PolitenessProfile::Gentle => (2000, 2, 25.0, 1, 1),
+            PolitenessProfile::Balanced => (1000, 10, 10.0, 1, 4),
+            PolitenessProfile::Aggressive => (250, 32, 0.0, 2, 16),
+        };
+        config.delay_ms = delay_ms;
+        config.max_concurrent = max_concurrent;
+        config.delay_jitter_percent = jitter;
+        config.slow_start = Some(SlowStartConfig {
+            initial_limit,
+            max_limit,
+            ..SlowStartConfig::default()
+        });
+    }
+}
@@ -0,0 +1,77 @@
+use std::time::{Duration, Instant};
+
+/// Token bucket capping the request rate of the whole crawl.
+///
+/// Tokens refill continuously at `rate` per second. The bucket holds a
+/// single token, so requests are spaced evenly rather than let through
+/// in bursts.
+#[derive(Debug)]
+pub struct TokenBucket {
+    /// Tokens added per second
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Tokens the bucket can hold
+const BUCKET_CAPACITY: f64 = 1.0;
+
+impl TokenBucket {
+    pub fn new(requests_per_second: f64) -> Self {
+        Self::new_at(requests_per_second, Instant::now())
+    }
+
+    fn new_at(requests_per_second: f64, now: Instant) -> Self {
+        Self {
+            rate: requests_per_second.max(f64::MIN_POSITIVE),
+            tokens: BUCKET_CAPACITY,
+            last_refill: now,
+        }
+    }
+
+    /// Change the refill rate, keeping the tokens built up so far
+    pub fn set_rate(&mut self, requests_per_second: f64) {
+        self.rate = requests_per_second.max(f64::MIN_POSITIVE);
+    }
+    
+    /// Take a token if one is available, or return how long until one is
+    pub fn try_take(&mut self) -> Result<(), Duration> {
+        self.try_take_at(Instant::now())
+    }
+
+    fn try_take_at(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(BUCKET_CAPACITY);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_refill_at_rate() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new_at(10.0, start);
+
+        assert!(bucket.try_take_at(start).is_ok());
+        assert_eq!(bucket.try_take_at(start), Err(Duration::from_millis(100)));
+
+        let later = start + Duration::from_millis(40);
+        let wait = bucket.try_take_at(later).unwrap_err();
+        assert!(wait > Duration::from_millis(59) && wait <= Duration::from_millis(60));
+
+        // Idle time doesn't build up a burst
+        let much_later = start + Duration::from_secs(10);
+        assert!(bucket.try_take_at(much_later).is_ok());
+        assert!(bucket.try_take_at(much_later).is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use crate::common::error::Result;
+use crate::crawler::frontier::{CrawlTask, Frontier, FrontierStats};
+use async_trait::async_trait;
+use redis::aio::MultiplexedConnection;
+use redis::{AsyncCommands, Script};
+use tracing::warn;
+use url::Url;
+
+/// Queues a task unless the queue is full or its URL was already seen, as
+/// one atomic step so two crawlers adding the same URL can't both queue it.
+/// KEYS: queue, seen. ARGV: url, task JSON, max queue size.
+const PUSH_SCRIPT: &str = r#"
+if redis.call('LLEN', KEYS[1]) >= tonumber(ARGV[3]) then
+    return 0
+end
+if redis.call('SADD', KEYS[2], ARGV[1]) == 0 then
+    return 0
+end
+redis.call('RPUSH', KEYS[1], ARGV[2])
+return 1
+"#;
+
+/// Frontier kept in Redis, so several crawler processes share one queue
+/// and one seen set. Tasks are a list of JSON documents popped with `LPOP`,
+/// so each task goes to exactly one crawler, and seen URLs a set.
+///
+/// Tasks are handed out first in, first out; priorities are kept with the
+/// task but don't reorder the queue.
+#[derive(Clone)]
+pub struct RedisFrontier {
+    connection: MultiplexedConnection,
+    queue_key: String,
+    seen_key: String,
+    max_size: usize,
+    push_script: Script,
+}
+
+impl RedisFrontier {
+    /// Connect to the Redis server at `redis_url`, keeping the frontier
+    /// under keys starting with `prefix`. Crawlers using the same server
+    /// and prefix share the frontier.
+    pub async fn connect(redis_url: &str, prefix: &str, max_size: usize) -> Result<Self> {
+        let client = redis::Client::open(redis_url)?;
+        let connection = client.get_multiplexed_tokio_connection().await?;
+        Ok(Self {
+            connection,
+            queue_key: format!("{}:queue", prefix),
+            seen_key: format!("{}:seen", prefix),
+            max_size,
+            push_script: Script::new(PUSH_SCRIPT),
+        })
+    }
+
+    /// Delete the queue and seen set, for starting a crawl afresh
+    pub async fn clear(&self) -> Result<()> {
+        let mut connection = self.connection.clone();
+        connection.del::<_, ()>(&[&self.queue_key, &self.seen_key]).await?;
+        Ok(())
+    }
+
+    async fn try_push(&self, task: &CrawlTask) -> Result<bool> {
+        let mut connection = self.connection.clone();
+        let queued: i64 = self
+            .push_script
+            .key(&self.queue_key)
+            .key(&self.seen_key)
+            .arg(task.url.as_str())
+            .arg(serde_json::to_string(task)?)
+            .arg(self.max_size)
+            .invoke_async(&mut connection)
+            .await?;
+        Ok(queued == 1)
+    }
+
+    async fn try_pop(&self) -> Result<Option<CrawlTask>> {
+        let mut connection = self.connection.clone();
+        let task: Option<String> = connection.lpop(&self.queue_key, None).await?;
+        Ok(task.map(|task| serde_json::from_str(&task)).transpose()?)
+    }
+
+    async fn try_retry(&self, task: &CrawlTask) -> Result<bool> {
+        let mut connection = self.connection.clone();
+        if connection.llen::<_, usize>(&self.queue_key).await? >= self.max_size {
+            return Ok(false);
+        }
+        connection.rpush::<_, _, ()>(&self.queue_key, serde_json::to_string(task)?).await?;
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl Frontier for RedisFrontier {
+    async fn push(&self, task: CrawlTask) -> bool {
+        self.try_push(&task).await.unwrap_or_else(|e| {
+            warn!("Failed to queue {} in Redis: {}", task.url, e);
+            false
+        })
+    }
+
+    async fn pop(&self) -> Option<CrawlTask> {
+        self.try_pop().await.unwrap_or_else(|e| {
+            warn!("Failed to pop a task from Redis: {}", e);
+            None
+        })
+    }
+
+    async fn size(&self) -> usize {
+        let mut connection = self.connection.clone();
+        connection.llen(&self.queue_key).await.unwrap_or_else(|e| {
+            warn!("Failed to read the Redis queue length: {}", e);
+            0
+        })
+    }
+
+    async fn mark_seen(&self, url: &Url) {
+        let mut connection = self.connection.clone();
+        if let Err(e) = connection.sadd::<_, _, ()>(&self.seen_key, url.as_str()).await {
+            warn!("Failed to mark {} seen in Redis: {}", url, e);
+        }
+    }
+
+    async fn has_seen(&self, url: &Url) -> bool {
+        let mut connection = self.connection.clone();
+        connection.sismember(&self.seen_key, url.as_str()).await.unwrap_or_else(|e| {
+            warn!("Failed to look up {} in Redis: {}", url, e);
+            false
+        })
+    }
+
+    async fn retry(&self, mut task: CrawlTask) -> bool {
+        task.retry_count += 1;
+        self.try_retry(&task).await.unwrap_or_else(|e| {
+            warn!("Failed to requeue {} in Redis: {}", task.url, e);
+            false
+        })
+    }
+
+    async fn stats(&self) -> FrontierStats {
+        let mut connection = self.connection.clone();
+        let seen_count = connection.scard(&self.seen_key).await.unwrap_or_else(|e| {
+            warn!("Failed to read the Redis seen set size: {}", e);
+            0
+        });
+        FrontierStats {
+            queue_size: self.size().await,
+            seen_count,
+            max_size: self.max_size,
+            discovered_count: seen_count,
+            max_urls_discovered: None,
+        }
+    }
+}
+
+/// These tests need a Redis server, at `REDIS_URL` or on localhost. Run
+/// them with `cargo test --features redis -- --ignored`.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    async fn node(prefix: &str) -> RedisFrontier {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379/".to_string());
+        RedisFrontier::connect(&url, prefix, 1000).await.unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore = "needs a Redis server"]
+    async fn test_nodes_share_queue_without_duplicates() {
+        let prefix = format!("crawler-test-{}", uuid::Uuid::new_v4());
+        let (a, b) = (node(&prefix).await, node(&prefix).await);
+        let urls: Vec<Url> = (0..50)
+            .map(|i| Url::parse(&format!("https://example.com/{}", i)).unwrap())
+            .collect();
+
+        for url in &urls {
+            assert!(a.add(url.clone(), 0).await);
+            // The other node finds every URL already seen
+            assert!(!b.add(url.clone(), 1).await);
+        }
+        assert!(b.has_seen(&urls[0]).await);
+        assert_eq!(b.size().await, 50);
+
+        let drain = |frontier: RedisFrontier| {
+            tokio::spawn(async move {
+                let mut popped = Vec::new();
+                while let Some(task) = frontier.pop().await {
+                    popped.push(task.url);
+                }
+                popped
+            })
+        };
+        let (from_a, from_b) = (drain(a.clone()), drain(b.clone()));
+        let mut crawled = from_a.await.unwrap();
+        crawled.extend(from_b.await.unwrap());
+
+        assert_eq!(crawled.len(), 50);
+        assert_eq!(crawled.iter().collect::<HashSet<_>>().len(), 50);
+        assert!(a.is_empty().await);
+        assert_eq!(a.stats().await.seen_count, 50);
+        a.clear().await.unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+use crate::common::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+/// Settings that can be changed while a crawl runs, read from JSON.
+///
+/// Only the per-request politeness settings are hot-reloadable:
+/// `delay_ms`, `delay_jitter_percent`, `global_rate_limit` and
+/// `allowed_domains`. Everything else in
+/// [`CrawlerConfig`](crate::crawler::crawler::CrawlerConfig), such as the
+/// worker count, page limits and storage, is fixed when the crawler is
+/// built. Fields left out keep their current value.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReloadableConfig {
+    pub delay_ms: Option<u64>,
+    pub delay_jitter_percent: Option<f64>,
+    pub global_rate_limit: Option<f64>,
+    pub allowed_domains: Option<Vec<String>>,
+}
+
+impl ReloadableConfig {
+    /// Read the settings from a JSON file
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        serde_json::from_str(&contents)
+            .map_err(|e| Error::ConfigError(format!("Invalid config {}: {}", path.display(), e)))
+    }
+}
+
+/// Current values of the hot-reloadable settings, shared by every worker
+#[derive(Debug)]
+pub struct LiveSettings {
+    delay_ms: AtomicU64,
+    /// `f64` bits of the jitter percentage
+    delay_jitter_percent: AtomicU64,
+    allowed_domains: RwLock<Vec<String>>,
+}
+
+impl LiveSettings {
+    pub fn new(delay_ms: u64, delay_jitter_percent: f64, allowed_domains: Vec<String>) -> Self {
+        Self {
+            delay_ms: AtomicU64::new(delay_ms),
+            delay_jitter_percent: AtomicU64::new(delay_jitter_percent.to_bits()),
+            allowed_domains: RwLock::new(normalize_domains(allowed_domains)),
+        }
+    }
+
+    pub fn delay_ms(&self) -> u64 {
+        self.delay_ms.load(Ordering::Relaxed)
+    }
+
+    pub fn delay_jitter_percent(&self) -> f64 {
+        f64::from_bits(self.delay_jitter_percent.load(Ordering::Relaxed))
+    }
+
+    /// Check whether a host is on one of the allowed domains or their
+    /// subdomains. Every host is allowed when no domains are set.
+    pub fn is_host_allowed(&self, host: &str) -> bool {
+        let domains = self.allowed_domains.read().unwrap();
+        let host = host.to_lowercase();
+        domains.is_empty()
+            || domains
+                .iter()
+                .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)))
+    }
+
+    /// Apply the settings present in `changes`
+    pub fn apply(&self, changes: &ReloadableConfig) {
+        if let Some(delay_ms) = changes.delay_ms {
+            self.delay_ms.store(delay_ms, Ordering::Relaxed);
+        }
+        if let Some(percent) = changes.delay_jitter_percent {
+            self.delay_jitter_percent.store(percent.to_bits(), Ordering::Relaxed);
+        }
+        if let Some(domains) = &changes.allowed_domains {
+            *self.allowed_domains.write().unwrap() = normalize_domains(domains.clone());
+        }
+    }
+}
+
+fn normalize_domains(domains: Vec<String>) -> Vec<String> {
+    domains
+        .into_iter()
+        .map(|domain| domain.trim().trim_start_matches("*.").to_lowercase())
+        .filter(|domain| !domain.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_keeps_missing_fields() {
+        let live = LiveSettings::new(1000, 10.0, vec!["Example.com".to_string()]);
+        assert!(live.is_host_allowed("www.example.com"));
+        assert!(!live.is_host_allowed("other.com"));
+
+        let changes: ReloadableConfig =
+            serde_json::from_str(r#"{"delay_ms": 250, "allowed_domains": []}"#).unwrap();
+        live.apply(&changes);
+
+        assert_eq!(live.delay_ms(), 250);
+        assert_eq!(live.delay_jitter_percent(), 10.0);
+        assert!(live.is_host_allowed("other.com"));
+        assert!(serde_json::from_str::<ReloadableConfig>(r#"{"max_concurrent": 4}"#).is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use crate::common::error::{Error, Result};
+use crate::crawler::frontier::FrontierStats;
+use crate::crawler::CrawlStats;
+use serde::Serialize;
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// How a crawl report is written out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// A single JSON object
+    Json,
+    /// `metric,value` rows under a header
+    Csv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            other => Err(Error::ConfigError(format!("Unknown output format {:?}", other))),
+        }
+    }
+}
+
+/// Final statistics of a crawl along with the state of its frontier
+#[derive(Debug, Clone, Serialize)]
+pub struct CrawlReport {
+    #[serde(flatten)]
+    pub stats: CrawlStats,
+    pub duration_ms: Option<u64>,
+    pub queue_size: usize,
+    pub seen_count: usize,
+}
+
+impl CrawlReport {
+    pub fn new(stats: CrawlStats, frontier: FrontierStats) -> Self {
+        Self {
+            duration_ms: stats.duration().map(|duration| duration.as_millis() as u64),
+            stats,
+            queue_size: frontier.queue_size,
+            seen_count: frontier.seen_count,
+        }
+    }
+
+    /// Render the report in `format`
+    pub fn render(&self, format: OutputFormat) -> Result<String> {
+        match format {
+            OutputFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            OutputFormat::Text => Ok(self
+                .metrics()
+                .into_iter()
+                .map(|(name, value)| format!("{}: {}\n", name, value))
+                .collect()),
+            OutputFormat::Csv => {
+                let mut out = String::from("metric,value\n");
+                for (name, value) in self.metrics() {
+                    let _ = writeln!(out, "{},{}", name, value);
+                }
+                Ok(out)
+            }
+        }
+    }
+
+    /// Every figure of the report as (name, value), status codes last
+    fn metrics(&self) -> Vec<(String, String)> {
+        let stats = &self.stats;
+        let mut metrics: Vec<(String, String)> = [
+            ("pages_crawled", stats.pages_crawled),
+            ("pages_failed", stats.pages_failed),
+            ("total_links_found", stats.total_links_found),
+            ("pages_out_of_range", stats.pages_out_of_range),
+            ("breaker_trips", stats.breaker_trips),
+            ("breaker_skipped", stats.breaker_skipped),
+            ("pages_over_budget", stats.pages_over_budget),
+            ("robots_blocked", stats.robots_blocked),
+            ("pages_parse_truncated", stats.pages_parse_truncated),
+            ("new_hosts_dropped", stats.new_hosts_dropped),
+            ("pages_retried", stats.pages_retried),
+            ("query_variants_dropped", stats.query_variants_dropped),
+            ("attachments_skipped", stats.attachments_skipped),
+            ("binary_skipped", stats.binary_skipped),
+            ("mime_mismatch_skipped", stats.mime_mismatch_skipped),
+            ("parse_timeouts", stats.parse_timeouts),
+            ("fresh_skipped", stats.fresh_skipped),
+            ("redirects_audited", stats.redirects_audited),
+            ("queue_size", self.queue_size),
+            ("seen_count", self.seen_count),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        if let Some(duration_ms) = self.duration_ms {
+            metrics.push(("duration_ms".to_string(), duration_ms.to_string()));
+        }
+        for (status, count) in &stats.status_codes {
+            metrics.push((format!("status_{}", status), count.to_string()));
+        }
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> CrawlReport {
+        let mut stats = CrawlStats {
+            pages_crawled: 3,
+            pages_failed: 1,
+            ..CrawlStats::default()
+        };
+        stats.status_codes.insert(200, 3);
+        stats.status_codes.insert(404, 1);
+        CrawlReport::new(
+            stats,
+            FrontierStats {
+                queue_size: 2,
+                seen_count: 6,
+                max_size: 100,
+                discovered_count: 8,
+                max_urls_discovered: None,
+            },
+        )
+    }
+
+    #[test]
+    fn test_json_output_parses() {
+        let json: serde_json::Value = serde_json::from_str(&report().render(OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["pages_crawled"], 3);
+        assert_eq!(json["seen_count"], 6);
+        assert_eq!(json["status_codes"]["404"], 1);
+    }
+
+    #[test]
+    fn test_csv_output() {
+        let csv = report().render(OutputFormat::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "metric,value");
+        assert!(lines.contains(&"pages_failed,1"));
+        assert_eq!(lines.last(), Some(&"status_404,1"));
+        assert_eq!("CSV".parse::<OutputFormat>().unwrap(), OutputFormat::Csv);
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+}
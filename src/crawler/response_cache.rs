@@ -0,0 +1,147 @@
+use crate::crawler::FetchResponse;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Default number of responses kept by a [`ResponseCache`]
+pub const DEFAULT_RESPONSE_CACHE_ENTRIES: usize = 256;
+
+/// In-process LRU cache of successful responses, keyed by URL.
+///
+/// Entries expire `ttl` after they were stored. Once more than `capacity`
+/// responses are cached, the least recently used one is evicted. Clones
+/// share the same cache.
+#[derive(Clone)]
+pub struct ResponseCache {
+    inner: Arc<Mutex<LruResponses>>,
+}
+
+struct LruResponses {
+    ttl: Duration,
+    capacity: usize,
+    entries: HashMap<String, Entry>,
+    /// Access tick -> URL, oldest first
+    recency: BTreeMap<u64, String>,
+    next_tick: u64,
+}
+
+struct Entry {
+    response: FetchResponse,
+    stored_at: Instant,
+    tick: u64,
+}
+
+impl LruResponses {
+    fn remove(&mut self, url: &str) {
+        if let Some(entry) = self.entries.remove(url) {
+            self.recency.remove(&entry.tick);
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        let tick = self.next_tick;
+        self.next_tick += 1;
+        tick
+    }
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(LruResponses {
+                ttl,
+                capacity: capacity.max(1),
+                entries: HashMap::new(),
+                recency: BTreeMap::new(),
+                next_tick: 0,
+            })),
+        }
+    }
+
+    /// Cached response for `url`, if one was stored within the TTL
+    pub fn get(&self, url: &str) -> Option<FetchResponse> {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        let ttl = inner.ttl;
+        let tick = inner.next_tick();
+        let entry = inner.entries.get_mut(url)?;
+        if entry.stored_at.elapsed() >= ttl {
+            inner.remove(url);
+            return None;
+        }
+        let old_tick = std::mem::replace(&mut entry.tick, tick);
+        let response = entry.response.clone();
+        inner.recency.remove(&old_tick);
+        inner.recency.insert(tick, url.to_string());
+        Some(response)
+    }
+
+    /// Cache a response under `url`, evicting the least recently used
+    /// entries over capacity
+    pub fn insert(&self, url: &str, response: FetchResponse) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.remove(url);
+        let tick = inner.next_tick();
+        inner.entries.insert(
+            url.to_string(),
+            Entry {
+                response,
+                stored_at: Instant::now(),
+                tick,
+            },
+        );
+        inner.recency.insert(tick, url.to_string());
+        while inner.entries.len() > inner.capacity {
+            let Some((_, oldest)) = inner.recency.pop_first() else {
+                break;
+            };
+            inner.entries.remove(&oldest);
+        }
+    }
+
+    /// Number of cached responses, including expired ones not yet dropped
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use url::Url;
+
+    fn response(url: &str) -> FetchResponse {
+        FetchResponse {
+            url: Url::parse(url).unwrap(),
+            status_code: 200,
+            content_type: Some("text/html".to_string()),
+            body: Default::default(),
+            headers: Vec::new(),
+            truncated: false,
+            elapsed: Duration::ZERO,
+            body_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used_and_expired() {
+        let cache = ResponseCache::new(Duration::from_secs(60), 2);
+        cache.insert("https://a.com/", response("https://a.com/"));
+        cache.insert("https://b.com/", response("https://b.com/"));
+        assert!(cache.get("https://a.com/").is_some());
+        cache.insert("https://c.com/", response("https://c.com/"));
+
+        assert!(cache.get("https://b.com/").is_none());
+        assert!(cache.get("https://a.com/").is_some());
+        assert_eq!(cache.len(), 2);
+
+        let expired = ResponseCache::new(Duration::ZERO, 2);
+        expired.insert("https://a.com/", response("https://a.com/"));
+        assert!(expired.get("https://a.com/").is_none());
+        assert!(expired.is_empty());
+    }
+}
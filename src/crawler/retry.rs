@@ -0,0 +1,61 @@
+use crate::common::error::Error;
+
+/// Decides whether a failed fetch is worth trying again.
+///
+/// Any `Fn(&Error, Option<u16>) -> bool` closure is a policy, so a crawler
+/// can be given its own rules with
+/// [`CrawlerBuilder::retry_policy`](crate::crawler::CrawlerBuilder::retry_policy).
+pub trait RetryPolicy: Send + Sync {
+    /// `status` is the HTTP status of the response, if one was received
+    fn is_retryable(&self, error: &Error, status: Option<u16>) -> bool;
+}
+
+impl<F> RetryPolicy for F
+where
+    F: Fn(&Error, Option<u16>) -> bool + Send + Sync,
+{
+    fn is_retryable(&self, error: &Error, status: Option<u16>) -> bool {
+        self(error, status)
+    }
+}
+
+/// The policy used unless one is set, see [`is_retryable`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultRetryPolicy;
+
+impl RetryPolicy for DefaultRetryPolicy {
+    fn is_retryable(&self, error: &Error, status: Option<u16>) -> bool {
+        is_retryable(error, status)
+    }
+}
+
+/// Default retry classification. Retried:
+///
+/// - HTTP 408, 425, 429, 500, 502, 503 and 504
+/// - timeouts and connection errors
+///
+/// Other statuses and errors, such as a bad URL, an unwanted content type
+/// or an out-of-scope redirect, are not.
+pub fn is_retryable(error: &Error, status: Option<u16>) -> bool {
+    match status {
+        Some(status) => matches!(status, 408 | 425 | 429 | 500 | 502 | 503 | 504),
+        None => matches!(error, Error::Timeout | Error::HttpError(_) | Error::IoError(_)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_classification() {
+        let status = |code: u16| Error::HttpStatus(code, "http://example.com/".to_string());
+        assert!(is_retryable(&status(503), Some(503)));
+        assert!(is_retryable(&status(429), Some(429)));
+        assert!(!is_retryable(&status(404), Some(404)));
+        assert!(!is_retryable(&status(403), Some(403)));
+        assert!(is_retryable(&Error::Timeout, None));
+        assert!(is_retryable(&Error::HttpError("connection refused".to_string()), None));
+        assert!(!is_retryable(&Error::InvalidResponse("Unsupported content type".to_string()), None));
+    }
+}
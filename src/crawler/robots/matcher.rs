@@ -0,0 +1,153 @@
+//! robots.txt path matching, following Google's robots.txt specification:
+//! `*` matches any run of characters, a trailing `$` anchors the pattern to
+//! the end of the path, and the longest matching rule wins, with `Allow`
+//! winning ties.
+
+use serde::{Deserialize, Serialize};
+
+/// A single `Allow` or `Disallow` line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rule {
+    pub allow: bool,
+    pub pattern: String,
+}
+
+impl Rule {
+    pub fn allow(pattern: &str) -> Self {
+        Self {
+            allow: true,
+            pattern: pattern.to_string(),
+        }
+    }
+    
+    pub fn disallow(pattern: &str) -> Self {
+        Self {
+            allow: false,
+            pattern: pattern.to_string(),
+        }
+    }
+}
+
+/// Outcome of matching a path against a group's rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Allow,
+    Disallow,
+    /// No rule matched the path
+    Undefined,
+}
+
+/// Decide whether `path` (including any query string) may be crawled.
+///
+/// Rules with an empty pattern never match, so an empty `Disallow:` allows
+/// everything.
+pub fn matches(rules: &[Rule], path: &str) -> Decision {
+    let mut best: Option<&Rule> = None;
+    for rule in rules {
+        if rule.pattern.is_empty() || !pattern_matches(&rule.pattern, path) {
+            continue;
+        }
+        let better = match best {
+            None => true,
+            Some(current) => {
+                rule.pattern.len() > current.pattern.len()
+                    || (rule.pattern.len() == current.pattern.len() && rule.allow && !current.allow)
+            }
+        };
+        if better {
+            best = Some(rule);
+        }
+    }
+    
+    match best {
+        Some(rule) if rule.allow => Decision::Allow,
+        Some(_) => Decision::Disallow,
+        None => Decision::Undefined,
+    }
+}
+
+/// Whether a pattern matches from the start of `path`
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let pattern = pattern.as_bytes();
+    let path = path.as_bytes();
+    
+    // Path positions the pattern prefix matched so far can end at
+    let mut positions = vec![0];
+    for (i, &token) in pattern.iter().enumerate() {
+        if token == b'*' {
+            // A run of stars matches any suffix starting at the earliest position
+            if i > 0 && pattern[i - 1] == b'*' {
+                continue;
+            }
+            let Some(&start) = positions.first() else {
+                return false;
+            };
+            positions = (start..=path.len()).collect();
+        } else {
+            positions = positions
+                .into_iter()
+                .filter(|&pos| pos < path.len() && path[pos] == token)
+                .map(|pos| pos + 1)
+                .collect();
+        }
+        if positions.is_empty() {
+            return false;
+        }
+    }
+    
+    !anchored || positions.last() == Some(&path.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_google_spec_cases() {
+        use Decision::*;
+        
+        let cases: &[(&[Rule], &str, Decision)] = &[
+            // Prefix matching
+            (&[Rule::disallow("/fish")], "/fish", Disallow),
+            (&[Rule::disallow("/fish")], "/fish.html", Disallow),
+            (&[Rule::disallow("/fish")], "/fish/salmon.html", Disallow),
+            (&[Rule::disallow("/fish")], "/fishheads", Disallow),
+            (&[Rule::disallow("/fish")], "/Fish.asp", Undefined),
+            (&[Rule::disallow("/fish")], "/catfish", Undefined),
+            (&[Rule::disallow("/fish/")], "/fish", Undefined),
+            // Wildcards
+            (&[Rule::disallow("/fish*")], "/fishheads/yummy.html", Disallow),
+            (&[Rule::disallow("/*.php")], "/folder/filename.php?parameters", Disallow),
+            (&[Rule::disallow("/*.php")], "/windows.PHP", Undefined),
+            (&[Rule::disallow("/fish*.php")], "/fishheads/catfish.php?parameters", Disallow),
+            (&[Rule::disallow("/fish*.php")], "/Fish.PHP", Undefined),
+            (&[Rule::disallow("/*")], "/", Disallow),
+            (&[Rule::disallow("/a**b")], "/axxb", Disallow),
+            // End anchor
+            (&[Rule::disallow("/*.php$")], "/filename.php", Disallow),
+            (&[Rule::disallow("/*.php$")], "/folder/filename.php", Disallow),
+            (&[Rule::disallow("/*.php$")], "/filename.php?parameters", Undefined),
+            (&[Rule::disallow("/*.php$")], "/filename.php5", Undefined),
+            (&[Rule::disallow("/$")], "/", Disallow),
+            (&[Rule::disallow("/$")], "/page", Undefined),
+            // Longest match wins, Allow wins ties
+            (&[Rule::allow("/p"), Rule::disallow("/")], "/page", Allow),
+            (&[Rule::allow("/folder"), Rule::disallow("/folder")], "/folder/page", Allow),
+            (&[Rule::allow("/page"), Rule::disallow("/*.htm")], "/page.htm", Disallow),
+            (&[Rule::allow("/$"), Rule::disallow("/")], "/", Allow),
+            (&[Rule::allow("/$"), Rule::disallow("/")], "/page.htm", Disallow),
+            (&[Rule::disallow("/private/"), Rule::allow("/private/public.html")], "/private/public.html", Allow),
+            // Empty patterns match nothing
+            (&[Rule::disallow("")], "/anything", Undefined),
+            (&[], "/anything", Undefined),
+        ];
+        
+        for (rules, path, expected) in cases {
+            assert_eq!(matches(rules, path), *expected, "{:?} on {}", rules, path);
+        }
+    }
+}
@@ -0,0 +1,458 @@
+pub mod matcher;
+
+use crate::common::error::{Error, Result};
+use crate::crawler::{FetcherConfig, HttpFetcher};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use url::Url;
+use std::sync::Arc;
+use tracing::{info, warn};
+use self::matcher::{Decision, Rule};
+
+/// Cache entry for robots.txt data
+#[derive(Clone, Debug)]
+struct RobotsCache {
+    rules: RobotsRules,
+    fetched_at: Instant,
+}
+
+/// Cache entry as written to disk, with a wall-clock fetch time that
+/// survives restarts
+#[derive(Serialize, Deserialize)]
+struct PersistedRobots {
+    rules: RobotsRules,
+    fetched_at: DateTime<Utc>,
+}
+
+/// Parsed robots.txt rules for a domain
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RobotsRules {
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+    sitemap: Option<String>,
+}
+
+impl RobotsRules {
+    /// Rules allowing everything, to build custom rules on
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Allow paths matching `pattern`, in robots.txt syntax
+    pub fn allow(mut self, pattern: &str) -> Self {
+        self.rules.push(Rule::allow(pattern));
+        self
+    }
+    
+    /// Disallow paths matching `pattern`, in robots.txt syntax
+    pub fn disallow(mut self, pattern: &str) -> Self {
+        self.rules.push(Rule::disallow(pattern));
+        self
+    }
+    
+    /// Wait `delay` between requests to the domain
+    pub fn crawl_delay(mut self, delay: Duration) -> Self {
+        self.crawl_delay = Some(delay);
+        self
+    }
+}
+
+/// Rules used for a domain in place of its robots.txt
+#[derive(Clone, Debug, PartialEq)]
+pub enum RobotsOverride {
+    /// Crawl every path, as with an empty robots.txt
+    AllowAll,
+    /// Crawl nothing, as with `Disallow: /`
+    DenyAll,
+    /// Follow these rules instead
+    Rules(RobotsRules),
+}
+
+impl RobotsOverride {
+    fn rules(&self) -> RobotsRules {
+        match self {
+            RobotsOverride::AllowAll => RobotsRules::default(),
+            RobotsOverride::DenyAll => RobotsRules::new().disallow("/"),
+            RobotsOverride::Rules(rules) => rules.clone(),
+        }
+    }
+}
+
+/// Robots.txt checker with caching
+#[derive(Clone)]
+pub struct RobotsChecker {
+    cache: Arc<Mutex<HashMap<String, RobotsCache>>>,
+    cache_duration: Duration,
+    user_agent: String,
+    resolve_overrides: HashMap<String, SocketAddr>,
+    /// Fetcher used instead of a direct HTTP request, if set
+    fetcher: Option<Arc<dyn HttpFetcher>>,
+    /// Rules replacing robots.txt, by lowercase host name
+    overrides: Arc<std::sync::Mutex<HashMap<String, RobotsOverride>>>,
+    /// File the cache is loaded from and saved to, if persistent
+    persist_path: Option<PathBuf>,
+}
+
+impl RobotsChecker {
+    /// Create a new robots checker
+    pub fn new(user_agent: String) -> Self {
+        Self {
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            cache_duration: Duration::from_secs(3600), // Cache for 1 hour
+            user_agent,
+            resolve_overrides: HashMap::new(),
+            fetcher: None,
+            overrides: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            persist_path: None,
+        }
+    }
+    
+    /// Use `policy` instead of robots.txt for URLs on `domain`, without
+    /// fetching robots.txt there. Only the exact host is affected, not its
+    /// subdomains. Shared by clones of the checker.
+    pub fn set_override(&self, domain: &str, policy: RobotsOverride) {
+        self.overrides.lock().unwrap().insert(domain.to_lowercase(), policy);
+    }
+    
+    /// Go back to following robots.txt on `domain`
+    pub fn remove_override(&self, domain: &str) {
+        self.overrides.lock().unwrap().remove(&domain.to_lowercase());
+    }
+    
+    /// Resolve the given hosts to fixed addresses when fetching robots.txt
+    pub fn with_resolve_overrides(mut self, overrides: HashMap<String, SocketAddr>) -> Self {
+        self.resolve_overrides = overrides;
+        self
+    }
+    
+    /// Reuse fetched robots.txt files for `ttl` instead of an hour
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_duration = ttl;
+        self
+    }
+    
+    /// Keep the cache in a JSON file at `path` between runs: entries still
+    /// within the TTL are loaded from it now, and [`save`](Self::save)
+    /// writes the cache back. A missing or corrupt file starts an empty
+    /// cache. Set the TTL first, since expired entries aren't loaded.
+    pub fn with_persistence(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        match Self::load(&path, self.cache_duration) {
+            Ok(cache) => {
+                info!("Loaded {} cached robots.txt files from {}", cache.len(), path.display());
+                self.cache = Arc::new(Mutex::new(cache));
+            }
+            Err(Error::IoError(e)) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Ignoring robots.txt cache {}: {}", path.display(), e),
+        }
+        self.persist_path = Some(path);
+        self
+    }
+    
+    /// Read the entries of a persisted cache younger than `ttl`
+    fn load(path: &Path, ttl: Duration) -> Result<HashMap<String, RobotsCache>> {
+        let persisted: HashMap<String, PersistedRobots> = serde_json::from_slice(&fs::read(path)?)?;
+        let now = Utc::now();
+        Ok(persisted
+            .into_iter()
+            .filter_map(|(origin, entry)| {
+                let age = (now - entry.fetched_at).to_std().unwrap_or_default();
+                let fetched_at = Instant::now().checked_sub(age)?;
+                (age < ttl).then_some((origin, RobotsCache { rules: entry.rules, fetched_at }))
+            })
+            .collect())
+    }
+    
+    /// Write the unexpired cache entries to the persistence file, under a
+    /// temporary name renamed into place. Does nothing without persistence.
+    pub async fn save(&self) -> Result<()> {
+        let Some(path) = &self.persist_path else {
+            return Ok(());
+        };
+        let persisted: HashMap<String, PersistedRobots> = {
+            let now = Utc::now();
+            let cache = self.cache.lock().await;
+            cache
+                .iter()
+                .filter(|(_, entry)| entry.fetched_at.elapsed() < self.cache_duration)
+                .filter_map(|(origin, entry)| {
+                    let fetched_at = now - chrono::Duration::from_std(entry.fetched_at.elapsed()).ok()?;
+                    Some((origin.clone(), PersistedRobots { rules: entry.rules.clone(), fetched_at }))
+                })
+                .collect()
+        };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let mut temp_path = path.clone().into_os_string();
+        temp_path.push(".tmp");
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(&serde_json::to_vec(&persisted)?)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+    
+    /// Fetch robots.txt through `fetcher` instead of over HTTP directly
+    pub fn with_fetcher(mut self, fetcher: Arc<dyn HttpFetcher>) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+    
+    /// Check if a URL is allowed to be crawled
+    pub async fn is_allowed(&self, url: &Url) -> Result<bool> {
+        // Local files have no robots.txt
+        if url.scheme() == "file" {
+            return Ok(true);
+        }
+        url.domain()
+            .ok_or_else(|| Error::InvalidResponse("No domain in URL".to_string()))?;
+        
+        // Get robots.txt rules for this domain
+        let rules = self.get_rules(url).await?;
+        
+        // Rules match against the path and query
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        
+        // If no rules match, it's allowed
+        if matcher::matches(&rules.rules, &path) == Decision::Disallow {
+            info!("Robots.txt disallows crawling: {}", url);
+            return Ok(false);
+        }
+        Ok(true)
+    }
+    
+    /// Get crawl delay for a domain
+    pub async fn get_crawl_delay(&self, url: &Url) -> Result<Option<Duration>> {
+        let rules = self.get_rules(url).await?;
+        Ok(rules.crawl_delay)
+    }
+    
+    /// Get the sitemap URL advertised in a domain's robots.txt, if any
+    pub async fn get_sitemap(&self, url: &Url) -> Result<Option<Url>> {
+        let rules = self.get_rules(url).await?;
+        Ok(rules.sitemap.and_then(|sitemap| Url::parse(&sitemap).ok()))
+    }
+    
+    /// Get robots.txt rules for a domain (with caching).
+    /// Rules apply per origin, so the scheme and port are part of the key.
+    async fn get_rules(&self, url: &Url) -> Result<RobotsRules> {
+        if url.scheme() == "file" {
+            return Ok(RobotsRules::default());
+        }
+        let domain = url.domain()
+            .ok_or_else(|| Error::InvalidResponse("No domain in URL".to_string()))?;
+        if let Some(policy) = self.overrides.lock().unwrap().get(&domain.to_lowercase()) {
+            return Ok(policy.rules());
+        }
+        let origin = url.origin().ascii_serialization();
+        
+        // Check cache first
+        {
+            let cache = self.cache.lock().await;
+            if let Some(cached) = cache.get(&origin) {
+                if cached.fetched_at.elapsed() < self.cache_duration {
+                    return Ok(cached.rules.clone());
+                }
+            }
+        }
+        
+        // Fetch and parse robots.txt
+        let robots_url = url.join("/robots.txt").map_err(Error::UrlParseError)?;
+        
+        info!("Fetching robots.txt from {}", robots_url);
+        
+        let rules = match self.fetch_and_parse(&robots_url).await {
+            Ok(rules) => rules,
+            Err(e) => {
+                warn!("Failed to fetch robots.txt for {}: {}. Allowing crawl.", domain, e);
+                // If we can't fetch robots.txt, we allow crawling (standard practice)
+                RobotsRules::default()
+            }
+        };
+        
+        // Cache the rules
+        {
+            let mut cache = self.cache.lock().await;
+            cache.insert(
+                origin,
+                RobotsCache {
+                    rules: rules.clone(),
+                    fetched_at: Instant::now(),
+                },
+            );
+        }
+        
+        Ok(rules)
+    }
+    
+    /// Fetch and parse robots.txt
+    async fn fetch_and_parse(&self, robots_url: &Url) -> Result<RobotsRules> {
+        if let Some(fetcher) = &self.fetcher {
+            let response = fetcher.fetch(robots_url).await?;
+            return self.parse_robots_txt(&response.body);
+        }
+        
+        // Create a new fetcher for this request
+        let fetcher = crate::crawler::Fetcher::from_config(FetcherConfig {
+            user_agent: self.user_agent.clone(),
+            timeout_seconds: 10, // 10 second timeout
+            max_size: 1024 * 1024, // 1MB max
+            resolve_overrides: self.resolve_overrides.clone(),
+            ..FetcherConfig::default()
+        });
+        
+        // Use tokio to run the blocking fetch operation
+        let url = robots_url.clone();
+        let response = tokio::task::spawn_blocking(move || {
+            crate::crawler::Fetcher::fetch(&fetcher, &url)
+        }).await
+            .map_err(|e| Error::Unknown(format!("Task error: {}", e)))?;
+        
+        let response = response?;
+        
+        // Parse the robots.txt content
+        self.parse_robots_txt(&response.body)
+    }
+    
+    /// Parse robots.txt content
+    fn parse_robots_txt(&self, content: &str) -> Result<RobotsRules> {
+        let mut rules = RobotsRules::default();
+        let mut applies_to_us = false;
+        
+        for line in content.lines() {
+            let line = line.trim();
+            
+            // Skip comments and empty lines
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            
+            // Split directive and value
+            let parts: Vec<&str> = line.splitn(2, ':').collect();
+            if parts.len() != 2 {
+                continue;
+            }
+            
+            let directive = parts[0].trim().to_lowercase();
+            let value = parts[1].trim();
+            
+            match directive.as_str() {
+                "user-agent" => {
+                    let agent = value.to_lowercase();
+                    applies_to_us = agent == "*" || 
+                                   self.user_agent.to_lowercase().contains(&agent);
+                }
+                "disallow" if applies_to_us && !value.is_empty() => {
+                    rules.rules.push(Rule::disallow(value));
+                }
+                "allow" if applies_to_us && !value.is_empty() => {
+                    rules.rules.push(Rule::allow(value));
+                }
+                "crawl-delay" if applies_to_us => {
+                    if let Ok(seconds) = value.parse::<u64>() {
+                        rules.crawl_delay = Some(Duration::from_secs(seconds));
+                    }
+                }
+                "sitemap" => {
+                    rules.sitemap = Some(value.to_string());
+                }
+                _ => {}
+            }
+        }
+        
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_parse_robots_txt() {
+        let checker = RobotsChecker::new("TestBot".to_string());
+        let content = r#"
+User-agent: *
+Disallow: /private/
+Disallow: /tmp/
+Allow: /private/public.html
+Crawl-delay: 1
+
+User-agent: BadBot
+Disallow: /
+
+Sitemap: https://example.com/sitemap.xml
+"#;
+        
+        let rules = checker.parse_robots_txt(content).unwrap();
+        assert_eq!(rules.rules.iter().filter(|rule| !rule.allow).count(), 2);
+        assert_eq!(rules.rules.iter().filter(|rule| rule.allow).count(), 1);
+        assert_eq!(matcher::matches(&rules.rules, "/private/public.html"), Decision::Allow);
+        assert_eq!(matcher::matches(&rules.rules, "/private/secret.html"), Decision::Disallow);
+        assert_eq!(rules.crawl_delay, Some(Duration::from_secs(1)));
+        assert_eq!(rules.sitemap, Some("https://example.com/sitemap.xml".to_string()));
+    }
+    
+    #[tokio::test]
+    async fn test_deny_all_override_skips_fetch() {
+        let checker = RobotsChecker::new("TestBot".to_string());
+        checker.set_override("Example.com", RobotsOverride::DenyAll);
+        let url = Url::parse("https://example.com/page").unwrap();
+        
+        assert!(!checker.is_allowed(&url).await.unwrap());
+        assert_eq!(checker.get_crawl_delay(&url).await.unwrap(), None);
+        // Nothing was fetched or cached
+        assert!(checker.cache.lock().await.is_empty());
+        
+        let rules = RobotsRules::new().crawl_delay(Duration::from_secs(2));
+        checker.clone().set_override("example.com", RobotsOverride::Rules(rules));
+        assert!(checker.is_allowed(&url).await.unwrap());
+        assert_eq!(checker.get_crawl_delay(&url).await.unwrap(), Some(Duration::from_secs(2)));
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_persisted_cache_skips_fetch() {
+        use crate::crawler::mock_server::{MockResponse, MockServer};
+        
+        let server = MockServer::start();
+        server.route("/robots.txt", MockResponse::typed("text/plain", b"User-agent: *\nDisallow: /private\n"));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("robots.json");
+        let private = server.url("/private");
+        
+        let checker = RobotsChecker::new("TestBot".to_string()).with_persistence(&path);
+        assert!(!checker.is_allowed(&private).await.unwrap());
+        checker.save().await.unwrap();
+        assert_eq!(server.hits("/robots.txt"), 1);
+        
+        // A new checker reuses the saved rules within the TTL
+        let checker = RobotsChecker::new("TestBot".to_string()).with_persistence(&path);
+        assert!(!checker.is_allowed(&private).await.unwrap());
+        assert!(checker.is_allowed(&server.url("/public")).await.unwrap());
+        assert_eq!(server.hits("/robots.txt"), 1);
+        
+        // Expired entries are fetched again
+        let checker = RobotsChecker::new("TestBot".to_string())
+            .with_cache_ttl(Duration::ZERO)
+            .with_persistence(&path);
+        assert!(!checker.is_allowed(&private).await.unwrap());
+        assert_eq!(server.hits("/robots.txt"), 2);
+        
+        // So is everything when the file is corrupt
+        std::fs::write(&path, "{not json").unwrap();
+        let checker = RobotsChecker::new("TestBot".to_string()).with_persistence(&path);
+        assert!(!checker.is_allowed(&private).await.unwrap());
+        assert_eq!(server.hits("/robots.txt"), 3);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+use url::Url;
+
+/// Which hosts count as the same site as a seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSitePolicy {
+    /// Only the seed's exact host
+    ExactHost,
+    /// Any host under the seed's registrable domain, per the public suffix
+    /// list: seeding `blog.example.co.uk` allows `shop.example.co.uk`
+    RegistrableDomain,
+    /// The seed's host and its subdomains, but not its parents
+    IncludeSubdomains,
+}
+
+/// Sites the crawl is restricted to, built up from the seeds
+#[derive(Debug, Clone)]
+pub struct SiteScope {
+    policy: SameSitePolicy,
+    sites: HashSet<String>,
+}
+
+impl SiteScope {
+    pub fn new(policy: SameSitePolicy) -> Self {
+        Self {
+            policy,
+            sites: HashSet::new(),
+        }
+    }
+
+    /// Allow the site a seed URL belongs to
+    pub fn add_seed(&mut self, url: &Url) {
+        if let Some(site) = url.host_str().map(|host| self.site_of(host)) {
+            self.sites.insert(site);
+        }
+    }
+
+    /// Check whether a URL belongs to one of the seeds' sites
+    pub fn allows(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = host.to_lowercase();
+        match self.policy {
+            SameSitePolicy::ExactHost | SameSitePolicy::RegistrableDomain => {
+                self.sites.contains(&self.site_of(&host))
+            }
+            SameSitePolicy::IncludeSubdomains => self
+                .sites
+                .iter()
+                .any(|site| host == *site || host.ends_with(&format!(".{}", site))),
+        }
+    }
+
+    fn site_of(&self, host: &str) -> String {
+        let host = host.to_lowercase();
+        match self.policy {
+            SameSitePolicy::RegistrableDomain => registrable_domain(&host).unwrap_or(host),
+            SameSitePolicy::ExactHost | SameSitePolicy::IncludeSubdomains => host,
+        }
+    }
+}
+
+/// The registrable domain (eTLD+1) of a host name: `example.co.uk` for
+/// `www.example.co.uk`. `None` for IP addresses and bare public suffixes.
+pub fn registrable_domain(host: &str) -> Option<String> {
+    let host = host.trim_end_matches('.');
+    if host.parse::<std::net::IpAddr>().is_ok() {
+        return None;
+    }
+    psl::domain_str(host).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registrable_domain() {
+        assert_eq!(registrable_domain("www.example.co.uk").as_deref(), Some("example.co.uk"));
+        assert_eq!(registrable_domain("a.b.example.com").as_deref(), Some("example.com"));
+        assert_eq!(registrable_domain("co.uk"), None);
+        assert_eq!(registrable_domain("127.0.0.1"), None);
+    }
+
+    #[test]
+    fn test_policies() {
+        use SameSitePolicy::*;
+
+        let cases = [
+            (ExactHost, "https://www.example.co.uk/", true),
+            (ExactHost, "https://shop.example.co.uk/", false),
+            (ExactHost, "https://example.co.uk/", false),
+            (RegistrableDomain, "https://www.example.co.uk/", true),
+            (RegistrableDomain, "https://shop.example.co.uk/", true),
+            (RegistrableDomain, "https://example.co.uk/", true),
+            (RegistrableDomain, "https://other.co.uk/", false),
+            (IncludeSubdomains, "https://www.example.co.uk/", true),
+            (IncludeSubdomains, "https://cdn.www.example.co.uk/", true),
+            (IncludeSubdomains, "https://shop.example.co.uk/", false),
+            (IncludeSubdomains, "https://example.co.uk/", false),
+            (IncludeSubdomains, "https://evilwww.example.co.uk/", false),
+        ];
+
+        for (policy, url, allowed) in cases {
+            let mut scope = SiteScope::new(policy);
+            scope.add_seed(&Url::parse("https://WWW.example.co.uk/start").unwrap());
+            assert_eq!(scope.allows(&Url::parse(url).unwrap()), allowed, "{:?} {}", policy, url);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::common::config::SeedConfig;
+use crate::common::error::{Error, Result};
+use std::collections::HashMap;
+use url::Url;
+
+/// Overrides of a seed entry, applied to every URL on the seed's host
+#[derive(Debug, Clone, Default)]
+struct HostLimits {
+    max_depth: Option<usize>,
+    max_pages: Option<usize>,
+    delay_ms: Option<u64>,
+    /// Pages dispatched so far on the host
+    used: usize,
+}
+
+/// Per-host depth, page and delay overrides from the crawl's seed entries
+#[derive(Debug, Default)]
+pub struct SeedLimits {
+    hosts: HashMap<String, HostLimits>,
+}
+
+impl SeedLimits {
+    /// Collect the overrides of `seeds`, checking that each stays within the
+    /// crawl-wide `max_depth` and `max_pages` and is no faster than the
+    /// crawl-wide `delay_ms`
+    pub fn new(seeds: &[SeedConfig], max_depth: usize, max_pages: usize, delay_ms: u64) -> Result<Self> {
+        let mut hosts = HashMap::new();
+        for seed in seeds {
+            let url = Url::parse(&seed.url)?;
+            let host = url
+                .host_str()
+                .ok_or_else(|| Error::ConfigError(format!("Seed {} has no host", seed.url)))?;
+            if seed.max_depth.is_some_and(|depth| depth > max_depth) {
+                return Err(Error::ConfigError(format!(
+                    "Seed {} max_depth exceeds the crawl's max_depth of {}",
+                    seed.url, max_depth
+                )));
+            }
+            if seed.max_pages.is_some_and(|pages| pages > max_pages) {
+                return Err(Error::ConfigError(format!(
+                    "Seed {} max_pages exceeds the crawl's max_pages of {}",
+                    seed.url, max_pages
+                )));
+            }
+            if seed.delay_ms.is_some_and(|delay| delay < delay_ms) {
+                return Err(Error::ConfigError(format!(
+                    "Seed {} delay_ms is below the crawl's delay_ms of {}",
+                    seed.url, delay_ms
+                )));
+            }
+            hosts.insert(
+                host.to_lowercase(),
+                HostLimits {
+                    max_depth: seed.max_depth,
+                    max_pages: seed.max_pages,
+                    delay_ms: seed.delay_ms,
+                    used: 0,
+                },
+            );
+        }
+        Ok(Self { hosts })
+    }
+
+    /// Depth limit for the URL's host, if a seed overrides it
+    pub fn max_depth(&self, url: &Url) -> Option<usize> {
+        self.limits(url).and_then(|limits| limits.max_depth)
+    }
+
+    /// Delay between requests to the URL's host, if a seed overrides it
+    pub fn delay_ms(&self, url: &Url) -> Option<u64> {
+        self.limits(url).and_then(|limits| limits.delay_ms)
+    }
+
+    /// Take one page from the budget of the URL's host. Returns false when
+    /// a seed's `max_pages` for the host is used up.
+    pub fn try_acquire(&mut self, url: &Url) -> bool {
+        let Some(limits) = url.host_str().and_then(|host| self.hosts.get_mut(&host.to_lowercase())) else {
+            return true;
+        };
+        if limits.max_pages.is_some_and(|max| limits.used >= max) {
+            return false;
+        }
+        limits.used += 1;
+        true
+    }
+
+    fn limits(&self, url: &Url) -> Option<&HostLimits> {
+        self.hosts.get(&url.host_str()?.to_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seed(url: &str, max_depth: Option<usize>, max_pages: Option<usize>, delay_ms: Option<u64>) -> SeedConfig {
+        SeedConfig {
+            url: url.to_string(),
+            max_depth,
+            max_pages,
+            delay_ms,
+        }
+    }
+
+    #[test]
+    fn test_overrides_within_global_caps() {
+        assert!(SeedLimits::new(&[seed("https://a.com/", Some(6), None, None)], 5, 100, 1000).is_err());
+        assert!(SeedLimits::new(&[seed("https://a.com/", None, Some(101), None)], 5, 100, 1000).is_err());
+        assert!(SeedLimits::new(&[seed("https://a.com/", None, None, Some(500))], 5, 100, 1000).is_err());
+
+        let mut limits = SeedLimits::new(&[seed("https://A.com/", Some(2), Some(1), Some(2000))], 5, 100, 1000).unwrap();
+        let url = Url::parse("https://a.com/page").unwrap();
+        assert_eq!(limits.max_depth(&url), Some(2));
+        assert_eq!(limits.delay_ms(&url), Some(2000));
+        assert!(limits.try_acquire(&url));
+        assert!(!limits.try_acquire(&url));
+        assert!(limits.try_acquire(&Url::parse("https://b.com/").unwrap()));
+    }
+}
@@ -0,0 +1,221 @@
+use crate::crawler::frontier::DEFAULT_PRIORITY;
+use regex::Regex;
+use url::Url;
+
+/// A single `<url>` entry from a sitemap
+#[derive(Debug, Clone, PartialEq)]
+pub struct SitemapEntry {
+    pub loc: Url,
+    pub lastmod: Option<String>,
+    /// Value of `<priority>`, clamped to 0.0 - 1.0
+    pub priority: Option<f32>,
+    /// Image and video files listed for the page, from `<image:loc>` and
+    /// `<video:content_loc>`. Empty unless the parser captures media.
+    pub media_urls: Vec<Url>,
+}
+
+impl SitemapEntry {
+    /// Frontier priority for this entry, neutral when the sitemap gives none
+    pub fn crawl_priority(&self) -> f32 {
+        self.priority.unwrap_or(DEFAULT_PRIORITY)
+    }
+}
+
+/// Most levels of sitemap indexes followed below the first sitemap, so
+/// indexes pointing at each other can't be followed forever
+pub const MAX_SITEMAP_INDEX_DEPTH: usize = 3;
+
+/// Parser for XML sitemaps (https://www.sitemaps.org/protocol.html)
+pub struct SitemapParser {
+    url_block: Regex,
+    sitemap_block: Regex,
+    loc: Regex,
+    lastmod: Regex,
+    priority: Regex,
+    media_loc: Regex,
+    capture_media: bool,
+}
+
+impl SitemapParser {
+    pub fn new() -> Self {
+        Self {
+            url_block: Regex::new(r"(?s)<url>(.*?)</url>").unwrap(),
+            sitemap_block: Regex::new(r"(?s)<sitemap>(.*?)</sitemap>").unwrap(),
+            loc: Regex::new(r"(?s)<loc>\s*(.*?)\s*</loc>").unwrap(),
+            lastmod: Regex::new(r"(?s)<lastmod>\s*(.*?)\s*</lastmod>").unwrap(),
+            priority: Regex::new(r"(?s)<priority>\s*(.*?)\s*</priority>").unwrap(),
+            media_loc: Regex::new(r"(?s)<(image:loc|video:content_loc)>\s*(.*?)\s*</(?:image:loc|video:content_loc)>").unwrap(),
+            capture_media: false,
+        }
+    }
+
+    /// Collect the image and video URLs of image and video sitemaps into
+    /// [`SitemapEntry::media_urls`]
+    pub fn capture_media(mut self, capture: bool) -> Self {
+        self.capture_media = capture;
+        self
+    }
+
+    /// Parse a `<urlset>` sitemap into its entries, skipping invalid locations
+    pub fn parse(&self, xml: &str) -> Vec<SitemapEntry> {
+        self.url_block
+            .captures_iter(xml)
+            .filter_map(|block| {
+                let block = &block[1];
+                let loc = self.capture(&self.loc, block)?;
+                let loc = Url::parse(&unescape_xml(&loc)).ok()?;
+                let lastmod = self.capture(&self.lastmod, block);
+                let priority = self
+                    .capture(&self.priority, block)
+                    .and_then(|p| p.parse::<f32>().ok())
+                    .filter(|p| p.is_finite())
+                    .map(|p| p.clamp(0.0, 1.0));
+
+                let media_urls = if self.capture_media {
+                    self.media_loc
+                        .captures_iter(block)
+                        .filter_map(|media| Url::parse(&unescape_xml(&media[2])).ok())
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+
+                Some(SitemapEntry {
+                    loc,
+                    lastmod,
+                    priority,
+                    media_urls,
+                })
+            })
+            .collect()
+    }
+
+    /// Child sitemap URLs of a `<sitemapindex>`, skipping invalid locations
+    pub fn parse_index(&self, xml: &str) -> Vec<Url> {
+        self.sitemap_block
+            .captures_iter(xml)
+            .filter_map(|block| Url::parse(&unescape_xml(&self.capture(&self.loc, &block[1])?)).ok())
+            .collect()
+    }
+
+    fn capture(&self, regex: &Regex, text: &str) -> Option<String> {
+        regex.captures(text).map(|c| c[1].to_string())
+    }
+}
+
+impl Default for SitemapParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Unescape the predefined XML entities
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crawler::{Frontier, UrlFrontier};
+
+    const SITEMAP: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com/low</loc>
+    <priority>0.1</priority>
+  </url>
+  <url>
+    <loc>https://example.com/plain?a=1&amp;b=2</loc>
+    <lastmod>2024-01-01</lastmod>
+  </url>
+  <url>
+    <loc>https://example.com/high</loc>
+    <priority>1.0</priority>
+  </url>
+</urlset>"#;
+
+    #[test]
+    fn test_parse_sitemap() {
+        let entries = SitemapParser::new().parse(SITEMAP);
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].priority, Some(0.1));
+        assert_eq!(entries[1].loc.as_str(), "https://example.com/plain?a=1&b=2");
+        assert_eq!(entries[1].lastmod.as_deref(), Some("2024-01-01"));
+        assert_eq!(entries[1].crawl_priority(), DEFAULT_PRIORITY);
+    }
+
+    #[test]
+    fn test_capture_media() {
+        let sitemap = r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"
+        xmlns:image="http://www.google.com/schemas/sitemap-image/1.1"
+        xmlns:video="http://www.google.com/schemas/sitemap-video/1.1">
+  <url>
+    <loc>https://example.com/gallery</loc>
+    <image:image><image:loc>https://cdn.example.com/one.jpg</image:loc></image:image>
+    <image:image><image:loc>https://cdn.example.com/two.png?w=1&amp;h=2</image:loc></image:image>
+  </url>
+  <url>
+    <loc>https://example.com/talk</loc>
+    <video:video>
+      <video:thumbnail_loc>https://cdn.example.com/talk.jpg</video:thumbnail_loc>
+      <video:content_loc>https://cdn.example.com/talk.mp4</video:content_loc>
+    </video:video>
+  </url>
+  <url><loc>https://example.com/about</loc></url>
+</urlset>"#;
+
+        let entries = SitemapParser::new().parse(sitemap);
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|entry| entry.media_urls.is_empty()));
+
+        let entries = SitemapParser::new().capture_media(true).parse(sitemap);
+        let locs: Vec<&str> = entries.iter().map(|entry| entry.loc.as_str()).collect();
+        assert_eq!(locs, ["https://example.com/gallery", "https://example.com/talk", "https://example.com/about"]);
+        let media: Vec<Vec<&str>> = entries
+            .iter()
+            .map(|entry| entry.media_urls.iter().map(Url::as_str).collect())
+            .collect();
+        assert_eq!(
+            media,
+            [
+                vec!["https://cdn.example.com/one.jpg", "https://cdn.example.com/two.png?w=1&h=2"],
+                vec!["https://cdn.example.com/talk.mp4"],
+                vec![],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_sitemap_index() {
+        let index = r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap><loc>https://example.com/posts.xml.gz</loc><lastmod>2024-01-01</lastmod></sitemap>
+  <sitemap><loc>https://example.com/pages.xml?a=1&amp;b=2</loc></sitemap>
+  <sitemap><loc>not a url</loc></sitemap>
+</sitemapindex>"#;
+        let parser = SitemapParser::new();
+
+        let children = parser.parse_index(index);
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[1].as_str(), "https://example.com/pages.xml?a=1&b=2");
+        assert!(parser.parse(index).is_empty());
+        assert!(parser.parse_index(SITEMAP).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sitemap_priority_orders_frontier() {
+        let frontier = UrlFrontier::new(10);
+        for entry in SitemapParser::new().parse(SITEMAP) {
+            frontier.add_with_priority(entry.loc.clone(), 0, entry.crawl_priority()).await;
+        }
+
+        assert_eq!(frontier.pop().await.unwrap().url.path(), "/high");
+        assert_eq!(frontier.pop().await.unwrap().url.path(), "/plain");
+        assert_eq!(frontier.pop().await.unwrap().url.path(), "/low");
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+/// Parameters of the per-domain concurrency ramp-up
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlowStartConfig {
+    /// Concurrent requests a domain starts with
+    pub initial_limit: usize,
+    /// Concurrent requests a domain can ramp up to
+    pub max_limit: usize,
+    /// Successful responses needed to raise the limit by one
+    pub successes_per_increase: usize,
+    /// Factor the limit is multiplied by after an error (0.0 - 1.0)
+    pub backoff_factor: f64,
+}
+
+impl Default for SlowStartConfig {
+    fn default() -> Self {
+        Self {
+            initial_limit: 1,
+            max_limit: 8,
+            successes_per_increase: 5,
+            backoff_factor: 0.5,
+        }
+    }
+}
+
+/// Per-domain concurrency limit that adapts AIMD-style: it starts low,
+/// grows by one after every `successes_per_increase` successful responses
+/// and is cut by `backoff_factor` on each error. Never drops below one.
+#[derive(Debug)]
+pub struct DomainConcurrency {
+    config: SlowStartConfig,
+    domains: HashMap<String, DomainWindow>,
+}
+
+#[derive(Debug)]
+struct DomainWindow {
+    limit: usize,
+    in_flight: usize,
+    /// Successes since the limit last changed
+    successes: usize,
+}
+
+impl DomainConcurrency {
+    pub fn new(config: SlowStartConfig) -> Self {
+        Self {
+            config,
+            domains: HashMap::new(),
+        }
+    }
+
+    /// Current concurrency limit of a domain
+    pub fn limit(&self, domain: &str) -> usize {
+        self.domains
+            .get(domain)
+            .map_or_else(|| self.initial_limit(), |window| window.limit)
+    }
+
+    /// Requests currently in flight to a domain
+    pub fn in_flight(&self, domain: &str) -> usize {
+        self.domains.get(domain).map_or(0, |window| window.in_flight)
+    }
+
+    /// Start a request to a domain. Returns false when the domain is at its limit.
+    pub fn try_acquire(&mut self, domain: &str) -> bool {
+        let initial_limit = self.initial_limit();
+        let window = self.domains.entry(domain.to_string()).or_insert(DomainWindow {
+            limit: initial_limit,
+            in_flight: 0,
+            successes: 0,
+        });
+        if window.in_flight >= window.limit {
+            return false;
+        }
+        window.in_flight += 1;
+        true
+    }
+
+    /// Finish a request started with [`try_acquire`](Self::try_acquire),
+    /// adjusting the domain's limit by its outcome
+    pub fn release(&mut self, domain: &str, success: bool) {
+        let max_limit = self.config.max_limit.max(1);
+        let Some(window) = self.domains.get_mut(domain) else {
+            return;
+        };
+        window.in_flight = window.in_flight.saturating_sub(1);
+
+        if success {
+            window.successes += 1;
+            if window.successes >= self.config.successes_per_increase.max(1) && window.limit < max_limit {
+                window.limit += 1;
+                window.successes = 0;
+            }
+        } else {
+            let factor = self.config.backoff_factor.clamp(0.0, 1.0);
+            window.limit = ((window.limit as f64 * factor).floor() as usize).max(1);
+            window.successes = 0;
+        }
+    }
+
+    fn initial_limit(&self) -> usize {
+        self.config.initial_limit.clamp(1, self.config.max_limit.max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Issue as many requests as the limit allows, then complete them all
+    fn round(concurrency: &mut DomainConcurrency, success: bool) -> usize {
+        let mut started = 0;
+        while concurrency.try_acquire("example.com") {
+            started += 1;
+        }
+        for _ in 0..started {
+            concurrency.release("example.com", success);
+        }
+        started
+    }
+
+    #[test]
+    fn test_ramps_up_then_backs_off() {
+        let mut concurrency = DomainConcurrency::new(SlowStartConfig {
+            initial_limit: 1,
+            max_limit: 4,
+            successes_per_increase: 2,
+            backoff_factor: 0.5,
+        });
+
+        let ramp: Vec<usize> = (0..8).map(|_| round(&mut concurrency, true)).collect();
+        assert_eq!(ramp, vec![1, 1, 2, 3, 4, 4, 4, 4]);
+        assert_eq!(concurrency.limit("example.com"), 4);
+        assert_eq!(concurrency.limit("other.com"), 1);
+
+        // An error burst halves the limit on every error, down to one
+        assert!(concurrency.try_acquire("example.com"));
+        concurrency.release("example.com", false);
+        assert_eq!(concurrency.limit("example.com"), 2);
+        round(&mut concurrency, false);
+        assert_eq!(concurrency.limit("example.com"), 1);
+        round(&mut concurrency, false);
+        assert_eq!(concurrency.limit("example.com"), 1);
+        assert_eq!(concurrency.in_flight("example.com"), 0);
+
+        // And the ramp starts over
+        assert_eq!(round(&mut concurrency, true), 1);
+        assert_eq!(round(&mut concurrency, true), 1);
+        assert_eq!(round(&mut concurrency, true), 2);
+    }
+}
@@ -0,0 +1,188 @@
+//! Self-test of the whole pipeline: a built-in test site is served on
+//! `localhost`, fetched, parsed, crawled into an in-memory index and
+//! searched, without touching the network.
+
+use crate::common::error::{Error, Result};
+use crate::crawler::mock_server::MockServer;
+use crate::crawler::{CrawlerBuilder, Fetcher, Parser};
+use crate::indexer::Indexer;
+use crate::search::{SearchQuery, Searcher};
+use crate::storage::{InMemoryStore, Storage};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Stages of a full run, in order
+pub const STAGES: &[&str] = &["serve", "fetch", "parse", "crawl", "index", "search"];
+
+/// Word only the test site's article contains, searched for at the end
+const PROBE_TERM: &str = "xylophonist";
+
+/// Pages of the test site, by path
+const SITE: &[(&str, &str)] = &[
+    (
+        "/",
+        r#"<html><head><title>Doctor</title></head>
+           <body><a href="/article">article</a> <a href="/about">about</a></body></html>"#,
+    ),
+    (
+        "/article",
+        "<html><head><title>Article</title></head><body><p>The xylophonist played on.</p></body></html>",
+    ),
+    ("/about", "<html><head><title>About</title></head><body><p>About this site.</p></body></html>"),
+];
+
+/// Outcome of one stage of the self-test
+#[derive(Debug, Clone)]
+pub struct StageReport {
+    pub name: &'static str,
+    pub ok: bool,
+    pub elapsed: Duration,
+    /// What was checked, or why the stage failed
+    pub detail: String,
+}
+
+/// Outcomes of the self-test stages, in the order they ran. The stages
+/// after a failed one aren't run.
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    pub stages: Vec<StageReport>,
+}
+
+impl DoctorReport {
+    /// Whether every stage ran and passed
+    pub fn is_healthy(&self) -> bool {
+        self.stages.len() == STAGES.len() && self.stages.iter().all(|stage| stage.ok)
+    }
+
+    /// Record the result of a stage started at `started`, returning its
+    /// value if it passed
+    fn record<T>(&mut self, name: &'static str, started: Instant, result: Result<(T, String)>) -> Option<T> {
+        let (ok, detail, value) = match result {
+            Ok((value, detail)) => (true, detail, Some(value)),
+            Err(e) => (false, e.to_string(), None),
+        };
+        self.stages.push(StageReport {
+            name,
+            ok,
+            elapsed: started.elapsed(),
+            detail,
+        });
+        value
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for stage in &self.stages {
+            let status = if stage.ok { "OK" } else { "FAIL" };
+            writeln!(f, "{:<4} {:<6} {:>9.2?}  {}", status, stage.name, stage.elapsed, stage.detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check that a count matches what the test site should produce
+fn expect_pages(what: &str, count: usize) -> Result<()> {
+    if count != SITE.len() {
+        return Err(Error::InvalidResponse(format!("expected {} {}, found {}", SITE.len(), what, count)));
+    }
+    Ok(())
+}
+
+/// Run the self-test
+pub async fn run() -> DoctorReport {
+    let mut report = DoctorReport::default();
+
+    let started = Instant::now();
+    let server = MockServer::start();
+    for (path, html) in SITE {
+        server.html(path, html);
+    }
+    let home = server.url("/");
+    report.record("serve", started, Ok(((), format!("test site on {}", home))));
+
+    // The fetcher blocks, so it runs off the async workers
+    let started = Instant::now();
+    let fetched = {
+        let home = home.clone();
+        tokio::task::spawn_blocking(move || Fetcher::new("RustCrawler-doctor".to_string(), 10, 1024 * 1024).fetch(&home))
+            .await
+            .map_err(|e| Error::Unknown(format!("Task error: {}", e)))
+            .and_then(|fetched| fetched)
+    }
+    .map(|response| {
+        let detail = format!("{} returned {} bytes", home, response.body.len());
+        (response, detail)
+    });
+    let Some(response) = report.record("fetch", started, fetched) else {
+        return report;
+    };
+
+    let started = Instant::now();
+    let parsed = Parser::new().parse(&response.body, &response.url).and_then(|page| {
+        if page.links.len() != SITE.len() - 1 {
+            return Err(Error::InvalidResponse(format!("expected {} links, found {}", SITE.len() - 1, page.links.len())));
+        }
+        Ok(((), format!("found {} links", page.links.len())))
+    });
+    if report.record("parse", started, parsed).is_none() {
+        return report;
+    }
+
+    let started = Instant::now();
+    let store = InMemoryStore::new();
+    let crawled = async {
+        let indexer = Indexer::in_memory()?;
+        let crawler = CrawlerBuilder::new()
+            .delay_ms(0)
+            .max_pages(SITE.len())
+            .storage(Arc::new(store.clone()))
+            .indexer(indexer.clone())
+            .build();
+        crawler.add_seed(home.clone()).await?;
+        expect_pages("pages crawled", crawler.crawl().await?.pages_crawled)?;
+        expect_pages("pages stored", store.all_urls().await?.len())?;
+        Ok((indexer, format!("crawled and stored {} pages", SITE.len())))
+    }
+    .await;
+    let Some(indexer) = report.record("crawl", started, crawled) else {
+        return report;
+    };
+
+    let started = Instant::now();
+    let indexed = Searcher::for_indexer(&indexer).and_then(|searcher| {
+        searcher.reload()?;
+        let docs = indexer.index().reader()?.searcher().num_docs() as usize;
+        expect_pages("documents indexed", docs)?;
+        Ok((searcher, format!("{} documents indexed", docs)))
+    });
+    let Some(searcher) = report.record("index", started, indexed) else {
+        return report;
+    };
+
+    let started = Instant::now();
+    let expected = server.url("/article").to_string();
+    let found = searcher.search(&SearchQuery::new(PROBE_TERM)).and_then(|results| {
+        if results.total != 1 || results.hits[0].url != expected {
+            return Err(Error::InvalidResponse(format!("{:?} did not find just {}", PROBE_TERM, expected)));
+        }
+        Ok(((), format!("{:?} found {}", PROBE_TERM, expected)))
+    });
+    report.record("search", started, found);
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_healthy_build_passes() {
+        let report = run().await;
+        assert!(report.is_healthy(), "{}", report);
+        let names: Vec<&str> = report.stages.iter().map(|stage| stage.name).collect();
+        assert_eq!(names, STAGES);
+        assert!(report.to_string().lines().all(|line| line.starts_with("OK ")));
+    }
+}
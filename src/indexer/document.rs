@@ -0,0 +1,189 @@
+use crate::indexer::tokenizer::{analyzer_name, stemmed_language, DEFAULT_LANGUAGE, STEMMED_LANGUAGES};
+use crate::storage::StoredPage;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use tantivy::schema::{
+    Facet, FacetOptions, Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, FAST, INDEXED,
+    STORED, STRING, TEXT,
+};
+use tantivy::TantivyDocument;
+use url::Url;
+
+/// Search index schema and handles to its fields
+#[derive(Debug, Clone)]
+pub struct IndexSchema {
+    pub schema: Schema,
+    /// Page URL, indexed as a single term so it can be looked up exactly
+    pub url: Field,
+    /// Title, stemmed for the default language
+    pub title: Field,
+    /// Page text, stored and stemmed for the default language
+    pub body: Field,
+    /// Page text stemmed for its declared language, one unstored field per
+    /// other supported language
+    pub stemmed_bodies: Vec<(&'static str, Field)>,
+    /// Unstemmed title and text, the vocabulary for spelling suggestions
+    pub spelling: Field,
+    /// Page summary for result cards, stored only
+    pub description: Field,
+    /// Preview image URL for result cards, stored only
+    pub image: Field,
+    /// Crawl time, a fast field so results can be sorted by date
+    pub crawled_at: Field,
+    /// Publication date, a fast field for sorting and range filters; missing
+    /// on pages without a known date
+    pub published_at: Field,
+    /// Host of the URL, as a single-level facet
+    pub domain: Field,
+    /// MIME type without parameters, as a single-level facet
+    pub content_type: Field,
+    /// Crawl tags as `/name/value` facets, so `tags:/category/news` matches
+    /// pages crawled from seeds tagged `category=news`
+    pub tags: Field,
+}
+
+impl IndexSchema {
+    pub fn new() -> Self {
+        let mut builder = Schema::builder();
+        let url = builder.add_text_field("url", STRING | STORED | FAST);
+        let title = builder.add_text_field("title", stemmed_text(DEFAULT_LANGUAGE).set_stored());
+        let body = builder.add_text_field("body", stemmed_text(DEFAULT_LANGUAGE).set_stored());
+        let stemmed_bodies = STEMMED_LANGUAGES
+            .iter()
+            .filter(|(code, _)| *code != DEFAULT_LANGUAGE)
+            .map(|(code, _)| (*code, builder.add_text_field(&format!("body_{}", code), stemmed_text(code))))
+            .collect();
+        let spelling = builder.add_text_field("spelling", TEXT);
+        let description = builder.add_text_field("description", STORED);
+        let image = builder.add_text_field("image", STORED);
+        let crawled_at = builder.add_date_field("crawled_at", INDEXED | STORED | FAST);
+        let published_at = builder.add_date_field("published_at", INDEXED | STORED | FAST);
+        let domain = builder.add_facet_field("domain", FacetOptions::default());
+        let content_type = builder.add_facet_field("content_type", FacetOptions::default());
+        let tags = builder.add_facet_field("tags", FacetOptions::default());
+
+        Self {
+            schema: builder.build(),
+            url,
+            title,
+            body,
+            stemmed_bodies,
+            spelling,
+            description,
+            image,
+            crawled_at,
+            published_at,
+            domain,
+            content_type,
+            tags,
+        }
+    }
+}
+
+/// Options of a text field analyzed with a language's stemmer
+fn stemmed_text(language: &str) -> TextOptions {
+    TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_tokenizer(&analyzer_name(language))
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+    )
+}
+
+impl Default for IndexSchema {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A page as handed to the indexer
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexDocument {
+    pub url: String,
+    pub title: Option<String>,
+    pub body: String,
+    pub content_type: Option<String>,
+    /// Declared language of the page (`en`, `fr-CA`); text in a supported
+    /// language is also indexed with that language's stemmer
+    pub language: Option<String>,
+    /// Summary shown in result cards, from `og:description`
+    pub description: Option<String>,
+    /// Preview image shown in result cards, from `og:image`
+    pub image: Option<String>,
+    pub crawled_at: DateTime<Utc>,
+    /// When the page was published, as found in its markup or
+    /// `Last-Modified` header
+    pub published_at: Option<DateTime<Utc>>,
+    /// Tags of the seed the page was crawled from
+    pub tags: BTreeMap<String, String>,
+}
+
+impl IndexDocument {
+    /// Convert into a tantivy document for the given schema
+    pub fn to_tantivy(&self, fields: &IndexSchema) -> TantivyDocument {
+        let mut doc = TantivyDocument::default();
+        doc.add_text(fields.url, &self.url);
+        if let Some(title) = &self.title {
+            doc.add_text(fields.title, title);
+        }
+        doc.add_text(fields.body, &self.body);
+        let language = self.language.as_deref().and_then(stemmed_language);
+        if let Some((_, field)) = fields.stemmed_bodies.iter().find(|(code, _)| Some(*code) == language) {
+            doc.add_text(*field, &self.body);
+        }
+        if let Some(title) = &self.title {
+            doc.add_text(fields.spelling, title);
+        }
+        doc.add_text(fields.spelling, &self.body);
+        if let Some(description) = &self.description {
+            doc.add_text(fields.description, description);
+        }
+        if let Some(image) = &self.image {
+            doc.add_text(fields.image, image);
+        }
+        doc.add_date(fields.crawled_at, to_tantivy_date(self.crawled_at));
+        if let Some(published_at) = self.published_at {
+            doc.add_date(fields.published_at, to_tantivy_date(published_at));
+        }
+        if let Some(host) = Url::parse(&self.url).ok().as_ref().and_then(Url::host_str) {
+            doc.add_facet(fields.domain, Facet::from_path([host]));
+        }
+        if let Some(mime) = self.content_type.as_deref().and_then(mime_type) {
+            doc.add_facet(fields.content_type, Facet::from_path([mime]));
+        }
+        for (name, value) in &self.tags {
+            doc.add_facet(fields.tags, Facet::from_path([name, value]));
+        }
+        doc
+    }
+}
+
+impl From<&StoredPage> for IndexDocument {
+    fn from(page: &StoredPage) -> Self {
+        Self {
+            url: page.key().to_string(),
+            title: page.title.clone(),
+            body: page.text_content.clone(),
+            content_type: page.content_type.clone(),
+            language: None,
+            description: page.open_graph.get("og:description").cloned(),
+            image: page.open_graph.get("og:image").cloned(),
+            crawled_at: page.crawled_at,
+            published_at: None,
+            tags: page.tags.clone(),
+        }
+    }
+}
+
+/// `text/html; charset=utf-8` -> `text/html`
+fn mime_type(content_type: &str) -> Option<String> {
+    let mime = content_type.split(';').next()?.trim().to_lowercase();
+    (!mime.is_empty()).then_some(mime)
+}
+
+pub(crate) fn to_tantivy_date(date: DateTime<Utc>) -> tantivy::DateTime {
+    tantivy::DateTime::from_timestamp_micros(date.timestamp_micros())
+}
+
+pub(crate) fn from_tantivy_date(date: tantivy::DateTime) -> DateTime<Utc> {
+    DateTime::from_timestamp_micros(date.into_timestamp_micros()).unwrap_or_default()
+}
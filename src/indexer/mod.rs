@@ -0,0 +1,315 @@
+pub mod document;
+pub mod tokenizer;
+
+pub use document::{IndexDocument, IndexSchema};
+
+use crate::common::error::{Error, Result};
+use crate::storage::check_dir_writable;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tantivy::directory::MmapDirectory;
+use tantivy::{Directory, Index, IndexWriter, Term};
+use tracing::warn;
+
+/// Index metadata file listing the committed segments
+const META_FILE: &str = "meta.json";
+
+/// Memory budget of the index writer
+const WRITER_HEAP_BYTES: usize = 50_000_000;
+
+/// Documents added between automatic commits by default
+pub const DEFAULT_COMMIT_EVERY_DOCS: usize = 1000;
+
+/// Seconds between automatic commits by default
+pub const DEFAULT_COMMIT_EVERY_SECS: u64 = 30;
+
+/// Full-text indexer backed by tantivy.
+///
+/// Added documents are batched and committed every `commit_every_docs`
+/// documents or `commit_every_secs` seconds, whichever comes first. The
+/// interval is checked as documents are added. Pending documents are
+/// committed when the last clone of the indexer is dropped.
+#[derive(Clone)]
+pub struct Indexer {
+    index: Index,
+    fields: IndexSchema,
+    writer: Arc<Mutex<BatchingWriter>>,
+    /// Directory the index lives in; `None` for in-memory indexes
+    path: Option<PathBuf>,
+}
+
+struct BatchingWriter {
+    writer: IndexWriter,
+    /// Documents added or deleted since the last commit
+    pending: usize,
+    last_commit: Instant,
+    commits: u64,
+    commit_every_docs: usize,
+    commit_every: Duration,
+}
+
+impl BatchingWriter {
+    fn commit(&mut self) -> Result<()> {
+        self.writer.commit()?;
+        self.pending = 0;
+        self.last_commit = Instant::now();
+        self.commits += 1;
+        Ok(())
+    }
+
+    fn commit_due(&self) -> bool {
+        self.pending >= self.commit_every_docs || self.last_commit.elapsed() >= self.commit_every
+    }
+}
+
+impl Drop for BatchingWriter {
+    fn drop(&mut self) {
+        if self.pending > 0 {
+            if let Err(e) = self.commit() {
+                warn!("Failed to commit {} pending documents: {}", self.pending, e);
+            }
+        }
+    }
+}
+
+impl Indexer {
+    /// Open (or create) an index in the given directory
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        std::fs::create_dir_all(path.as_ref())?;
+        let fields = IndexSchema::new();
+        let directory = MmapDirectory::open(path.as_ref()).map_err(tantivy::TantivyError::from)?;
+        let index = Index::open_or_create(directory, fields.schema.clone())?;
+        let mut indexer = Self::from_index(index, fields)?;
+        indexer.path = Some(path.as_ref().to_path_buf());
+        Ok(indexer)
+    }
+
+    /// Create an index that lives only in memory
+    pub fn in_memory() -> Result<Self> {
+        let fields = IndexSchema::new();
+        let index = Index::create_in_ram(fields.schema.clone());
+        Self::from_index(index, fields)
+    }
+
+    fn from_index(index: Index, fields: IndexSchema) -> Result<Self> {
+        tokenizer::register_analyzers(&index);
+        let writer = index.writer(WRITER_HEAP_BYTES)?;
+        Ok(Self {
+            index,
+            fields,
+            writer: Arc::new(Mutex::new(BatchingWriter {
+                writer,
+                pending: 0,
+                last_commit: Instant::now(),
+                commits: 0,
+                commit_every_docs: DEFAULT_COMMIT_EVERY_DOCS,
+                commit_every: Duration::from_secs(DEFAULT_COMMIT_EVERY_SECS),
+            })),
+            path: None,
+        })
+    }
+
+    /// Commit automatically once this many documents are pending
+    pub fn commit_every_docs(self, docs: usize) -> Self {
+        self.writer.lock().unwrap().commit_every_docs = docs.max(1);
+        self
+    }
+
+    /// Commit automatically once this many seconds passed since the last commit
+    pub fn commit_every_secs(self, secs: u64) -> Self {
+        self.writer.lock().unwrap().commit_every = Duration::from_secs(secs);
+        self
+    }
+
+    /// Check that the index directory can be written, so commits won't
+    /// fail partway through a crawl. In-memory indexes always can.
+    pub fn check_writable(&self) -> Result<()> {
+        if let Some(path) = &self.path {
+            check_dir_writable(path).map_err(|e| tantivy::TantivyError::IoError(Arc::new(e)))?;
+        }
+        Ok(())
+    }
+
+    pub fn index(&self) -> &Index {
+        &self.index
+    }
+
+    pub fn fields(&self) -> &IndexSchema {
+        &self.fields
+    }
+
+    /// Queue a document; it becomes searchable after the next commit
+    pub fn add_document(&self, document: &IndexDocument) -> Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        writer.writer.add_document(document.to_tantivy(&self.fields))?;
+        writer.pending += 1;
+        if writer.commit_due() {
+            writer.commit()?;
+        }
+        Ok(())
+    }
+
+    /// Remove every document indexed under `url`; takes effect at the next commit
+    pub fn delete_by_url(&self, url: &str) -> Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        writer.writer.delete_term(Term::from_field_text(self.fields.url, url));
+        writer.pending += 1;
+        if writer.commit_due() {
+            writer.commit()?;
+        }
+        Ok(())
+    }
+
+    /// Commit queued documents to the index
+    pub fn commit(&self) -> Result<()> {
+        self.writer.lock().unwrap().commit()
+    }
+
+    /// Write a self-contained copy of the index to `dest`, which is created
+    /// if needed and must not already hold an index.
+    ///
+    /// Pending documents are committed and all segments merged into one
+    /// first; writes are blocked until the copy is complete. The snapshot can
+    /// be opened with [`Searcher::open_readonly`](crate::search::Searcher::open_readonly).
+    pub fn snapshot<P: AsRef<Path>>(&self, dest: P) -> Result<()> {
+        let dest = dest.as_ref();
+        let mut writer = self.writer.lock().unwrap();
+        writer.commit()?;
+        let segment_ids = self.index.searchable_segment_ids()?;
+        if !segment_ids.is_empty() {
+            writer.writer.merge(&segment_ids).wait()?;
+        }
+
+        std::fs::create_dir_all(dest)?;
+        if dest.join(META_FILE).exists() {
+            return Err(Error::IndexError(tantivy::TantivyError::IndexAlreadyExists));
+        }
+        let directory = self.index.directory();
+        let mut files: Vec<PathBuf> = Vec::new();
+        for segment in self.index.searchable_segment_metas()? {
+            files.extend(segment.list_files().into_iter().filter(|file| directory.exists(file).unwrap_or(false)));
+        }
+        for file in &files {
+            // Raw contents, including the footer `open_read` would strip
+            let bytes = directory.atomic_read(file).map_err(tantivy::TantivyError::from)?;
+            std::fs::write(dest.join(file), bytes)?;
+        }
+        // Written last, so a snapshot with metadata has all its segments
+        let meta = directory.atomic_read(Path::new(META_FILE)).map_err(tantivy::TantivyError::from)?;
+        std::fs::write(dest.join(META_FILE), meta)?;
+        Ok(())
+    }
+
+    /// Number of commits made so far, automatic or explicit
+    pub fn commit_count(&self) -> u64 {
+        self.writer.lock().unwrap().commits
+    }
+
+    /// Number of documents added or deleted since the last commit
+    pub fn pending_documents(&self) -> usize {
+        self.writer.lock().unwrap().pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{SearchQuery, Searcher};
+    use chrono::Utc;
+
+    fn document(i: usize) -> IndexDocument {
+        IndexDocument {
+            url: format!("https://example.com/{}", i),
+            title: None,
+            body: "batched document".to_string(),
+            content_type: None,
+            language: None,
+            description: None,
+            image: None,
+            crawled_at: Utc::now(),
+            published_at: None,
+            tags: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_commits_every_n_documents() {
+        let indexer = Indexer::in_memory().unwrap().commit_every_docs(5).commit_every_secs(3600);
+        for i in 0..6 {
+            indexer.add_document(&document(i)).unwrap();
+        }
+
+        assert_eq!(indexer.commit_count(), 1);
+        assert_eq!(indexer.pending_documents(), 1);
+        let searcher = Searcher::for_indexer(&indexer).unwrap();
+        searcher.reload().unwrap();
+        assert_eq!(searcher.search(&SearchQuery::new("batched")).unwrap().total, 5);
+
+        indexer.commit().unwrap();
+        searcher.reload().unwrap();
+        assert_eq!(searcher.search(&SearchQuery::new("batched")).unwrap().total, 6);
+    }
+
+    #[test]
+    fn test_delete_by_url() {
+        let indexer = Indexer::in_memory().unwrap();
+        indexer.add_document(&document(1)).unwrap();
+        indexer.add_document(&document(2)).unwrap();
+        indexer.commit().unwrap();
+        let searcher = Searcher::for_indexer(&indexer).unwrap();
+
+        indexer.delete_by_url("https://example.com/1").unwrap();
+        searcher.reload().unwrap();
+        assert_eq!(searcher.search(&SearchQuery::new("batched")).unwrap().total, 2);
+
+        indexer.commit().unwrap();
+        searcher.reload().unwrap();
+        let results = searcher.search(&SearchQuery::new("batched")).unwrap();
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].url, "https://example.com/2");
+    }
+
+    #[test]
+    fn test_snapshot_searchable_readonly() {
+        let indexer = Indexer::in_memory().unwrap();
+        indexer.add_document(&document(1)).unwrap();
+        indexer.commit().unwrap();
+        indexer.add_document(&document(2)).unwrap();
+        indexer.add_document(&document(3)).unwrap();
+        indexer.commit().unwrap();
+        indexer.delete_by_url("https://example.com/3").unwrap();
+        
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("snapshot");
+        indexer.snapshot(&dest).unwrap();
+        assert_eq!(indexer.pending_documents(), 0);
+        
+        // Changes after the snapshot don't reach it
+        indexer.add_document(&document(4)).unwrap();
+        indexer.commit().unwrap();
+        drop(indexer);
+        
+        let searcher = Searcher::open_readonly(&dest).unwrap();
+        assert_eq!(Index::open_in_dir(&dest).unwrap().searchable_segment_ids().unwrap().len(), 1);
+        let results = searcher.search(&SearchQuery::new("batched")).unwrap();
+        let mut urls: Vec<String> = results.hits.into_iter().map(|hit| hit.url).collect();
+        urls.sort();
+        assert_eq!(urls, vec!["https://example.com/1", "https://example.com/2"]);
+        
+        assert!(Indexer::in_memory().unwrap().snapshot(&dest).is_err());
+    }
+    
+    #[test]
+    fn test_pending_documents_committed_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let indexer = Indexer::open(dir.path()).unwrap();
+            indexer.add_document(&document(0)).unwrap();
+            assert_eq!(indexer.commit_count(), 0);
+        }
+
+        let searcher = Searcher::open(dir.path()).unwrap();
+        assert_eq!(searcher.search(&SearchQuery::new("batched")).unwrap().total, 1);
+    }
+}
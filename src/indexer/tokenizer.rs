@@ -0,0 +1,71 @@
+use tantivy::tokenizer::{Language, LowerCaser, RemoveLongFilter, SimpleTokenizer, Stemmer, TextAnalyzer};
+use tantivy::Index;
+
+/// Language assumed for pages that don't declare one
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Languages with a stemming analyzer, by ISO 639-1 code
+pub const STEMMED_LANGUAGES: &[(&str, Language)] = &[
+    ("ar", Language::Arabic),
+    ("da", Language::Danish),
+    ("de", Language::German),
+    ("el", Language::Greek),
+    ("en", Language::English),
+    ("es", Language::Spanish),
+    ("fi", Language::Finnish),
+    ("fr", Language::French),
+    ("hu", Language::Hungarian),
+    ("it", Language::Italian),
+    ("nl", Language::Dutch),
+    ("no", Language::Norwegian),
+    ("pt", Language::Portuguese),
+    ("ro", Language::Romanian),
+    ("ru", Language::Russian),
+    ("sv", Language::Swedish),
+    ("ta", Language::Tamil),
+    ("tr", Language::Turkish),
+];
+
+/// Tokens longer than this are dropped
+const MAX_TOKEN_LENGTH: usize = 40;
+
+/// Name of the stemming analyzer registered for a language code
+pub fn analyzer_name(language: &str) -> String {
+    format!("stem_{}", language)
+}
+
+/// Normalize a declared language (`en-US`, `FR`) to a code from
+/// [`STEMMED_LANGUAGES`], if it has a stemmer
+pub fn stemmed_language(language: &str) -> Option<&'static str> {
+    let primary = language.split(['-', '_']).next()?.trim().to_lowercase();
+    STEMMED_LANGUAGES
+        .iter()
+        .map(|(code, _)| *code)
+        .find(|code| *code == primary)
+}
+
+/// Register a stemming analyzer for every supported language on the index.
+/// Must be called before the index is written or queried.
+pub fn register_analyzers(index: &Index) {
+    for (code, language) in STEMMED_LANGUAGES {
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(RemoveLongFilter::limit(MAX_TOKEN_LENGTH))
+            .filter(LowerCaser)
+            .filter(Stemmer::new(*language))
+            .build();
+        index.tokenizers().register(&analyzer_name(code), analyzer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stemmed_language() {
+        assert_eq!(stemmed_language("en-US"), Some("en"));
+        assert_eq!(stemmed_language("FR"), Some("fr"));
+        assert_eq!(stemmed_language("pt_BR"), Some("pt"));
+        assert_eq!(stemmed_language("ja"), None);
+    }
+}
@@ -6,6 +6,7 @@
 pub mod api;
 pub mod common;
 pub mod crawler;
+pub mod doctor;
 pub mod indexer;
 pub mod search;
 pub mod storage;
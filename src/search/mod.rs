@@ -0,0 +1,682 @@
+pub mod query;
+pub mod suggest;
+
+pub use query::{CollapseBy, FacetField, SearchQuery, SortOrder};
+
+use crate::common::error::{Error, Result};
+use crate::indexer::document::from_tantivy_date;
+use crate::indexer::tokenizer::register_analyzers;
+use crate::indexer::{IndexSchema, Indexer};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use tantivy::collector::{Count, FacetCollector, TopDocs};
+use tantivy::query::{Query, QueryParser};
+use tantivy::schema::{Facet, Field, IndexRecordOption, Value};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::{
+    DocAddress, DocId, DocSet, Index, IndexReader, Order, ReloadPolicy, Score, SegmentReader, TantivyDocument, Term,
+    TERMINATED,
+};
+
+/// A single search result
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub url: String,
+    pub title: Option<String>,
+    /// Summary for result cards, from the page's OpenGraph tags
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Preview image URL for result cards, from the page's OpenGraph tags
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Relevance score, only set when sorting by relevance
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    pub crawled_at: DateTime<Utc>,
+    /// Publication date, when the page declares one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<DateTime<Utc>>,
+    /// HTML-escaped body excerpt with matched terms in `<mark>` tags,
+    /// only set when highlighting was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    /// Results left out for sharing this hit's domain or title, when
+    /// collapsing
+    #[serde(skip_serializing_if = "is_zero")]
+    pub collapsed: usize,
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+/// One page of search results
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResults {
+    /// Number of documents matching the query, across all pages. When
+    /// collapsing, the number of groups found, which is at least one more
+    /// than the results up to this page if there is a next page.
+    pub total: usize,
+    pub hits: Vec<SearchHit>,
+    /// Hit counts per value of each requested facet, over all matches
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub facets: BTreeMap<FacetField, BTreeMap<String, u64>>,
+}
+
+/// A matching document with its score, if ranked by relevance, and the
+/// number of documents collapsed into it
+type RankedDoc = (Option<f32>, TantivyDocument, usize);
+
+/// When collapsing, candidates fetched per result wanted in the first window
+pub const COLLAPSE_WINDOW_FACTOR: usize = 4;
+
+/// Maximum length of a highlighted snippet, in characters
+pub const SNIPPET_MAX_CHARS: usize = 200;
+
+/// Runs queries against the index written by the [`Indexer`].
+///
+/// Queries use tantivy's query syntax: `title:rust`, `rust AND async`,
+/// `rust -python`, `"exact phrase"`.
+#[derive(Clone)]
+pub struct Searcher {
+    fields: IndexSchema,
+    reader: IndexReader,
+    parser: QueryParser,
+    default_fields: Vec<Field>,
+}
+
+impl Searcher {
+    /// Open an existing index directory
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let index = Index::open_in_dir(path)?;
+        let reader = index.reader()?;
+        Self::from_index(index, reader)
+    }
+
+    /// Open an index that is never written to, such as one produced by
+    /// [`Indexer::snapshot`]. The directory isn't watched for commits.
+    pub fn open_readonly<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let index = Index::open_in_dir(path)?;
+        let reader = index.reader_builder().reload_policy(ReloadPolicy::Manual).try_into()?;
+        Self::from_index(index, reader)
+    }
+
+    /// Search the index an indexer writes to
+    pub fn for_indexer(indexer: &Indexer) -> Result<Self> {
+        let index = indexer.index().clone();
+        let reader = index.reader()?;
+        Self::from_index(index, reader)
+    }
+
+    fn from_index(index: Index, reader: IndexReader) -> Result<Self> {
+        register_analyzers(&index);
+        let fields = IndexSchema::new();
+        // Each query term is analyzed with every language's stemmer, so it
+        // matches pages in their own language
+        let mut default_fields = vec![fields.title, fields.body];
+        default_fields.extend(fields.stemmed_bodies.iter().map(|(_, field)| *field));
+        let parser = QueryParser::for_index(&index, default_fields.clone());
+        Ok(Self {
+            fields,
+            reader,
+            parser,
+            default_fields,
+        })
+    }
+    
+    /// Names of the fields searched when a term doesn't name one
+    pub fn default_fields(&self) -> Vec<&str> {
+        self.default_fields
+            .iter()
+            .map(|field| self.fields.schema.get_field_name(*field))
+            .collect()
+    }
+
+    /// Pick up commits made since the searcher was opened
+    pub fn reload(&self) -> Result<()> {
+        self.reader.reload()?;
+        Ok(())
+    }
+
+    /// Run a query and return the requested page of results
+    pub fn search(&self, query: &SearchQuery) -> Result<SearchResults> {
+        let searcher = self.reader.searcher();
+        let parsed = self.parse(&query.text)?;
+
+        let total = searcher.search(&parsed, &Count)?;
+        if query.limit == 0 || query.offset >= total {
+            return Ok(SearchResults {
+                total,
+                hits: Vec::new(),
+                facets: BTreeMap::new(),
+            });
+        }
+
+        let (docs, total) = match query.collapse_by {
+            CollapseBy::None => {
+                let mut docs = Vec::new();
+                for (score, address) in self.rank(&searcher, &*parsed, query, query.offset, query.limit)? {
+                    docs.push((score, searcher.doc(address)?, 0));
+                }
+                (docs, total)
+            }
+            by => {
+                let groups = self.collapsed_groups(&searcher, &*parsed, query, by, total)?;
+                let total = groups.len();
+                (groups.into_iter().skip(query.offset).take(query.limit).collect(), total)
+            }
+        };
+
+        let snippets = if query.highlight {
+            let mut generator = SnippetGenerator::create(&searcher, &*parsed, self.fields.body)?;
+            generator.set_max_num_chars(SNIPPET_MAX_CHARS);
+            Some(generator)
+        } else {
+            None
+        };
+
+        let mut hits = Vec::with_capacity(docs.len());
+        for (score, doc, collapsed) in docs {
+            let snippet = snippets.as_ref().map(|generator| {
+                let mut snippet = generator.snippet_from_doc(&doc);
+                snippet.set_snippet_prefix_postfix("<mark>", "</mark>");
+                snippet.to_html()
+            });
+            hits.push(SearchHit {
+                url: self.text(&doc, self.fields.url).unwrap_or_default(),
+                title: self.text(&doc, self.fields.title),
+                description: self.text(&doc, self.fields.description),
+                image: self.text(&doc, self.fields.image),
+                score,
+                crawled_at: doc
+                    .get_first(self.fields.crawled_at)
+                    .and_then(|v| v.as_datetime())
+                    .map(from_tantivy_date)
+                    .unwrap_or_default(),
+                published_at: doc
+                    .get_first(self.fields.published_at)
+                    .and_then(|v| v.as_datetime())
+                    .map(from_tantivy_date),
+                snippet,
+                collapsed,
+            });
+        }
+
+        Ok(SearchResults {
+            total,
+            hits,
+            facets: BTreeMap::new(),
+        })
+    }
+
+    /// Run a query and also count its matches per value of each facet
+    pub fn search_with_facets(&self, query: &SearchQuery, facets: &[FacetField]) -> Result<SearchResults> {
+        let mut results = self.search(query)?;
+        if facets.is_empty() {
+            return Ok(results);
+        }
+
+        let searcher = self.reader.searcher();
+        let parsed = self.parse(&query.text)?;
+        for facet in facets {
+            let mut collector = FacetCollector::for_field(facet.name());
+            collector.add_facet("/");
+            let counts = searcher.search(&parsed, &collector)?;
+            let values = counts
+                .get("/")
+                .filter_map(|(value, count)| Some((value.to_path().last()?.to_string(), count)))
+                .collect();
+            results.facets.insert(*facet, values);
+        }
+        Ok(results)
+    }
+
+    /// One page of the matches, in the query's sort order
+    fn rank(
+        &self,
+        searcher: &tantivy::Searcher,
+        parsed: &dyn Query,
+        query: &SearchQuery,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<(Option<f32>, DocAddress)>> {
+        let top = TopDocs::with_limit(limit).and_offset(offset);
+        Ok(match query.sort {
+            SortOrder::Relevance if !query.boosts.is_empty() => {
+                let boosts: Vec<(Term, f32)> = query
+                    .boosts
+                    .iter()
+                    .map(|(domain, factor)| (self.domain_term(domain), *factor))
+                    .collect();
+                let boosted = top.tweak_score(move |segment: &SegmentReader| {
+                    let factors = boost_factors(segment, &boosts);
+                    move |doc: DocId, score: Score| score * factors.get(&doc).copied().unwrap_or(1.0)
+                });
+                searcher
+                    .search(parsed, &boosted)?
+                    .into_iter()
+                    .map(|(score, address)| (Some(score), address))
+                    .collect()
+            }
+            SortOrder::Relevance => searcher
+                .search(parsed, &top)?
+                .into_iter()
+                .map(|(score, address)| (Some(score), address))
+                .collect(),
+            SortOrder::Date => searcher
+                .search(
+                    parsed,
+                    &top.order_by_fast_field::<tantivy::DateTime>("crawled_at", Order::Desc),
+                )?
+                .into_iter()
+                .map(|(_, address)| (None, address))
+                .collect(),
+            SortOrder::Published => searcher
+                .search(
+                    parsed,
+                    &top.order_by_fast_field::<tantivy::DateTime>("published_at", Order::Desc),
+                )?
+                .into_iter()
+                .map(|(_, address)| (None, address))
+                .collect(),
+            SortOrder::Url => {
+                // URL ordinals only compare within a segment, so rank by the
+                // URL text read from the fast field, smallest first
+                let by_url = top.custom_score(|segment: &SegmentReader| {
+                    let urls = segment.fast_fields().str("url").ok().flatten();
+                    move |doc: DocId| {
+                        let mut url = String::new();
+                        if let Some(urls) = &urls {
+                            if let Some(ord) = urls.term_ords(doc).next() {
+                                let _ = urls.ord_to_str(ord, &mut url);
+                            }
+                        }
+                        Reverse(url)
+                    }
+                });
+                searcher
+                    .search(parsed, &by_url)?
+                    .into_iter()
+                    .map(|(_, address)| (None, address))
+                    .collect()
+            }
+        })
+    }
+
+    /// Collapse the ranked matches into groups under `by`, fetching windows
+    /// of `(offset + limit) * COLLAPSE_WINDOW_FACTOR` candidates, growing
+    /// each time, until the requested page and one more group are filled.
+    /// Sibling counts cover the candidates examined.
+    fn collapsed_groups(
+        &self,
+        searcher: &tantivy::Searcher,
+        parsed: &dyn Query,
+        query: &SearchQuery,
+        by: CollapseBy,
+        total: usize,
+    ) -> Result<Vec<RankedDoc>> {
+        let wanted = query.offset + query.limit;
+        let mut window = wanted * COLLAPSE_WINDOW_FACTOR;
+        let mut kept: Vec<RankedDoc> = Vec::new();
+        let mut groups: HashMap<String, usize> = HashMap::new();
+        let mut fetched = 0;
+        while fetched < total && kept.len() <= wanted {
+            for (score, address) in self.rank(searcher, parsed, query, fetched, window)? {
+                let doc: TantivyDocument = searcher.doc(address)?;
+                let key = match by {
+                    CollapseBy::None => None,
+                    CollapseBy::Domain => self
+                        .text(&doc, self.fields.url)
+                        .and_then(|url| Some(url::Url::parse(&url).ok()?.host_str()?.to_lowercase())),
+                    CollapseBy::Title => self.text(&doc, self.fields.title),
+                };
+                if let Some(key) = key {
+                    if let Some(&representative) = groups.get(&key) {
+                        kept[representative].2 += 1;
+                        continue;
+                    }
+                    groups.insert(key, kept.len());
+                }
+                kept.push((score, doc, 0));
+            }
+            fetched += window;
+            window *= 2;
+        }
+        Ok(kept)
+    }
+
+    fn parse(&self, text: &str) -> Result<Box<dyn Query>> {
+        self.parser
+            .parse_query(text)
+            .map_err(|e| Error::QueryError(format!("{} in query {:?}", e, text)))
+    }
+
+    fn text(&self, doc: &TantivyDocument, field: Field) -> Option<String> {
+        doc.get_first(field).and_then(|v| v.as_str()).map(str::to_string)
+    }
+
+    fn domain_term(&self, domain: &str) -> Term {
+        Term::from_facet(self.fields.domain, &Facet::from_path([domain.to_lowercase()]))
+    }
+}
+
+/// Boost factor of each document in `segment` on a boosted domain
+fn boost_factors(segment: &SegmentReader, boosts: &[(Term, f32)]) -> HashMap<DocId, f32> {
+    let mut factors = HashMap::new();
+    for (term, factor) in boosts {
+        let postings = segment
+            .inverted_index(term.field())
+            .ok()
+            .and_then(|index| index.read_postings(term, IndexRecordOption::Basic).ok().flatten());
+        if let Some(mut postings) = postings {
+            let mut doc = postings.doc();
+            while doc != TERMINATED {
+                factors.insert(doc, *factor);
+                doc = postings.advance();
+            }
+        }
+    }
+    factors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::IndexDocument;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_facet_counts_per_domain() {
+        let mut docs = vec![
+            document("https://a.com/1", None, "rust crawler", 0),
+            document("https://a.com/2", None, "rust search", 0),
+            document("https://b.com/1", None, "rust", 0),
+            document("https://b.com/2", None, "python", 0),
+        ];
+        docs[0].content_type = Some("text/html; charset=utf-8".to_string());
+        docs[2].content_type = Some("text/plain".to_string());
+        let searcher = index_documents(&docs);
+
+        let results = searcher
+            .search_with_facets(&SearchQuery::new("rust"), &[FacetField::Domain, FacetField::ContentType])
+            .unwrap();
+
+        assert_eq!(results.total, 3);
+        let domains = &results.facets[&FacetField::Domain];
+        assert_eq!(domains.get("a.com"), Some(&2));
+        assert_eq!(domains.get("b.com"), Some(&1));
+        let types = &results.facets[&FacetField::ContentType];
+        assert_eq!(types.get("text/html"), Some(&1));
+        assert_eq!(types.get("text/plain"), Some(&1));
+    }
+
+    #[test]
+    fn test_collapse_by_title() {
+        let searcher = index_documents(&[
+            document("https://a.com/tag/1", Some("Tag archive"), "rust rust rust", 0),
+            document("https://a.com/tag/2", Some("Tag archive"), "rust", 0),
+            document("https://b.com/tag/3", Some("Tag archive"), "rust", 0),
+            document("https://a.com/post", Some("Async patterns"), "rust", 0),
+            document("https://b.com/", None, "rust rust", 0),
+        ]);
+
+        let results = searcher.search(&SearchQuery::new("rust").collapse_by(CollapseBy::Title)).unwrap();
+        assert_eq!(results.total, 3);
+        assert_eq!(results.hits.len(), 3);
+        let archive = results.hits.iter().find(|hit| hit.title.as_deref() == Some("Tag archive")).unwrap();
+        assert_eq!(archive.url, "https://a.com/tag/1");
+        assert_eq!(archive.collapsed, 2);
+        assert!(results.hits.iter().filter(|hit| hit.url != archive.url).all(|hit| hit.collapsed == 0));
+
+        let results = searcher.search(&SearchQuery::new("rust").collapse_by(CollapseBy::Domain)).unwrap();
+        let mut collapsed: Vec<(&str, usize)> = results.hits.iter().map(|hit| (hit.url.as_str(), hit.collapsed)).collect();
+        collapsed.sort();
+        assert_eq!(collapsed, [("https://a.com/tag/1", 2), ("https://b.com/", 1)]);
+
+        let plain = searcher.search(&SearchQuery::new("rust")).unwrap();
+        assert_eq!(plain.hits.len(), 5);
+    }
+
+    #[test]
+    fn test_collapse_examines_a_bounded_window() {
+        let mut docs: Vec<IndexDocument> = (0..3)
+            .map(|i| document(&format!("https://a.com/{}", i), None, "rust rust rust", 0))
+            .collect();
+        docs.extend((0..10).map(|i| document(&format!("https://site{}.com/", i), None, "rust and many other words", 0)));
+        let searcher = index_documents(&docs);
+        let query = SearchQuery::new("rust").collapse_by(CollapseBy::Domain).limit(1);
+
+        // The first window of 4 candidates fills the page and one more group
+        let results = searcher.search(&query).unwrap();
+        assert_eq!(results.total, 2);
+        assert_eq!(urls(&results), ["https://a.com/0"]);
+        assert_eq!(results.hits[0].collapsed, 2);
+
+        let results = searcher.search(&query.clone().offset(1)).unwrap();
+        assert_eq!(results.total, 6);
+        assert_eq!(results.hits.len(), 1);
+        assert!(results.hits[0].url.starts_with("https://site"));
+
+        let results = searcher.search(&query.clone().offset(20)).unwrap();
+        assert!(results.hits.is_empty());
+    }
+
+    #[test]
+    fn test_domain_boost() {
+        let searcher = index_documents(&[
+            document("https://a.com/", None, "rust crawler", 0),
+            document("https://trusted.org/", None, "rust crawler", 0),
+        ]);
+        let plain = searcher.search(&SearchQuery::new("rust")).unwrap();
+        assert_eq!(plain.hits[0].score, plain.hits[1].score);
+
+        for first in ["trusted.org", "a.com"] {
+            let results = searcher.search(&SearchQuery::new("rust").boost(first, 2.0)).unwrap();
+            assert_eq!(urls(&results)[0], format!("https://{}/", first));
+            let (boosted, other) = (results.hits[0].score.unwrap(), results.hits[1].score.unwrap());
+            assert!((boosted - 2.0 * other).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_stemmed_matches() {
+        let mut french = document("https://b.com/", None, "les chevaux mangeaient", 0);
+        french.language = Some("fr-FR".to_string());
+        let searcher = index_documents(&[
+            document("https://a.com/", Some("Running"), "the crawler keeps running", 0),
+            french,
+        ]);
+
+        let results = searcher.search(&SearchQuery::new("run")).unwrap();
+        assert_eq!(urls(&results), ["https://a.com/"]);
+
+        // French stemming maps both forms of the verb to the same stem
+        let results = searcher.search(&SearchQuery::new("mangeait")).unwrap();
+        assert_eq!(urls(&results), ["https://b.com/"]);
+    }
+
+    fn document(url: &str, title: Option<&str>, body: &str, day: i64) -> IndexDocument {
+        IndexDocument {
+            url: url.to_string(),
+            title: title.map(str::to_string),
+            body: body.to_string(),
+            content_type: None,
+            language: None,
+            description: None,
+            image: None,
+            crawled_at: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + chrono::Duration::days(day),
+            published_at: None,
+            tags: Default::default(),
+        }
+    }
+
+    fn indexed(docs: &[(&str, &str, i64)]) -> Searcher {
+        let docs: Vec<IndexDocument> = docs
+            .iter()
+            .map(|(url, body, day)| document(url, None, body, *day))
+            .collect();
+        index_documents(&docs)
+    }
+
+    fn index_documents(docs: &[IndexDocument]) -> Searcher {
+        let indexer = Indexer::in_memory().unwrap();
+        for doc in docs {
+            indexer.add_document(doc).unwrap();
+        }
+        indexer.commit().unwrap();
+        let searcher = Searcher::for_indexer(&indexer).unwrap();
+        searcher.reload().unwrap();
+        searcher
+    }
+
+    fn urls(results: &SearchResults) -> Vec<&str> {
+        results.hits.iter().map(|hit| hit.url.as_str()).collect()
+    }
+
+    #[test]
+    fn test_paging_through_results() {
+        let docs: Vec<(String, i64)> = (0..25).map(|i| (format!("https://a.com/{:02}", i), i)).collect();
+        let docs: Vec<(&str, &str, i64)> = docs.iter().map(|(u, d)| (u.as_str(), "rust crawler", *d)).collect();
+        let searcher = indexed(&docs);
+
+        let mut seen = Vec::new();
+        for offset in (0..30).step_by(10) {
+            let results = searcher.search(&SearchQuery::new("rust").offset(offset).limit(10)).unwrap();
+            assert_eq!(results.total, 25);
+            seen.extend(urls(&results).into_iter().map(str::to_string));
+        }
+
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 25);
+        let past_end = searcher.search(&SearchQuery::new("rust").offset(30)).unwrap();
+        assert!(past_end.hits.is_empty());
+    }
+
+    #[test]
+    fn test_sort_by_date_and_url() {
+        let searcher = indexed(&[
+            ("https://b.com/", "rust", 5),
+            ("https://c.com/", "rust", 1),
+            ("https://a.com/", "rust", 3),
+            ("https://d.com/", "python", 9),
+        ]);
+
+        let by_date = searcher.search(&SearchQuery::new("rust").sort(SortOrder::Date)).unwrap();
+        assert_eq!(urls(&by_date), ["https://b.com/", "https://a.com/", "https://c.com/"]);
+        assert!(by_date.hits[0].crawled_at > by_date.hits[1].crawled_at);
+
+        let by_url = searcher
+            .search(&SearchQuery::new("rust").sort(SortOrder::Url).offset(1))
+            .unwrap();
+        assert_eq!(by_url.total, 3);
+        assert_eq!(urls(&by_url), ["https://b.com/", "https://c.com/"]);
+    }
+
+    #[test]
+    fn test_sort_by_url_across_segments() {
+        let indexer = Indexer::in_memory().unwrap();
+        for batch in [["https://d.com/", "https://b.com/"], ["https://c.com/", "https://a.com/"]] {
+            for url in batch {
+                indexer.add_document(&document(url, None, "rust", 0)).unwrap();
+            }
+            indexer.commit().unwrap();
+        }
+        let searcher = Searcher::for_indexer(&indexer).unwrap();
+        searcher.reload().unwrap();
+        assert_eq!(searcher.reader.searcher().segment_readers().len(), 2);
+
+        let results = searcher
+            .search(&SearchQuery::new("rust").sort(SortOrder::Url).offset(1).limit(2))
+            .unwrap();
+        assert_eq!(results.total, 4);
+        assert_eq!(urls(&results), ["https://b.com/", "https://c.com/"]);
+    }
+
+    #[test]
+    fn test_sort_by_published_date() {
+        let published = |url: &str, day: Option<i64>| IndexDocument {
+            published_at: day.map(|day| Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap() + chrono::Duration::days(day)),
+            ..document(url, None, "rust", 0)
+        };
+        let searcher = index_documents(&[
+            published("https://a.com/", Some(1)),
+            published("https://b.com/", None),
+            published("https://c.com/", Some(7)),
+        ]);
+
+        let results = searcher.search(&SearchQuery::new("rust").sort(SortOrder::Published)).unwrap();
+        assert_eq!(urls(&results), ["https://c.com/", "https://a.com/", "https://b.com/"]);
+        assert_eq!(results.hits[2].published_at, None);
+
+        let recent = searcher
+            .search(&SearchQuery::new("published_at:[2020-01-05T00:00:00Z TO *]"))
+            .unwrap();
+        assert_eq!(urls(&recent), ["https://c.com/"]);
+    }
+
+    fn query_fixture() -> Searcher {
+        index_documents(&[
+            document("https://a.com/", Some("Rust async"), "tokio runtime and futures", 0),
+            document("https://b.com/", Some("Rust book"), "ownership and async functions", 0),
+            document("https://c.com/", Some("Python async"), "asyncio event loop", 0),
+        ])
+    }
+
+    #[test]
+    fn test_fielded_query() {
+        let searcher = query_fixture();
+        let default_fields = searcher.default_fields();
+        assert_eq!(&default_fields[..2], ["title", "body"]);
+        assert!(default_fields.contains(&"body_fr"));
+
+        // "async" appears in every doc, but only two titles mention rust
+        let results = searcher.search(&SearchQuery::new("title:rust").sort(SortOrder::Url)).unwrap();
+        assert_eq!(urls(&results), ["https://a.com/", "https://b.com/"]);
+
+        let results = searcher.search(&SearchQuery::new(r#"body:"event loop""#)).unwrap();
+        assert_eq!(urls(&results), ["https://c.com/"]);
+    }
+
+    #[test]
+    fn test_boolean_and_negated_queries() {
+        let searcher = query_fixture();
+
+        let results = searcher.search(&SearchQuery::new("title:rust AND body:async")).unwrap();
+        assert_eq!(urls(&results), ["https://b.com/"]);
+
+        let results = searcher.search(&SearchQuery::new("async -rust")).unwrap();
+        assert_eq!(urls(&results), ["https://c.com/"]);
+    }
+
+    #[test]
+    fn test_invalid_query_is_reported() {
+        let searcher = query_fixture();
+
+        let err = searcher.search(&SearchQuery::new("author:ferris")).unwrap_err();
+        assert!(matches!(err, Error::QueryError(_)));
+        assert!(err.to_string().contains("author"));
+    }
+
+    #[test]
+    fn test_highlighted_snippet_escapes_html() {
+        let searcher = index_documents(&[document(
+            "https://a.com/",
+            None,
+            "Use <script> tags & the Rust compiler",
+            0,
+        )]);
+
+        let results = searcher.search(&SearchQuery::new("rust").highlight(true)).unwrap();
+        assert_eq!(
+            results.hits[0].snippet.as_deref(),
+            Some("Use &lt;script&gt; tags &amp; the <mark>Rust</mark> compiler")
+        );
+
+        let plain = searcher.search(&SearchQuery::new("rust")).unwrap();
+        assert!(plain.hits[0].snippet.is_none());
+    }
+}
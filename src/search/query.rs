@@ -0,0 +1,129 @@
+use crate::common::error::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Number of hits returned when no limit is given
+pub const DEFAULT_LIMIT: usize = 10;
+
+/// Order of search results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// Best match first
+    #[default]
+    Relevance,
+    /// Most recently crawled first
+    Date,
+    /// Most recently published first, pages without a known date last
+    Published,
+    /// Alphabetically by URL
+    Url,
+}
+
+/// Which results stand for one another, so only the best of them is shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CollapseBy {
+    /// Show every result
+    #[default]
+    None,
+    /// One result per host
+    Domain,
+    /// One result per title; untitled results are never collapsed
+    Title,
+}
+
+/// Field whose values can be counted across the hits of a query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FacetField {
+    Domain,
+    ContentType,
+}
+
+impl FacetField {
+    /// Name of the facet field in the index schema
+    pub fn name(&self) -> &'static str {
+        match self {
+            FacetField::Domain => "domain",
+            FacetField::ContentType => "content_type",
+        }
+    }
+}
+
+impl FromStr for FacetField {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "domain" => Ok(FacetField::Domain),
+            "content_type" => Ok(FacetField::ContentType),
+            other => Err(Error::QueryError(format!("Unknown facet {:?}", other))),
+        }
+    }
+}
+
+/// A search request: query text plus paging and ordering
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuery {
+    pub text: String,
+    /// Number of hits to skip
+    pub offset: usize,
+    /// Maximum number of hits to return
+    pub limit: usize,
+    pub sort: SortOrder,
+    /// Attach a body snippet with matched terms wrapped in `<mark>` tags
+    pub highlight: bool,
+    /// Score multipliers for hits on these hosts, applied when sorting by
+    /// relevance
+    pub boosts: HashMap<String, f32>,
+    /// Show only the best of results sharing a domain or title, with a
+    /// count of the others
+    pub collapse_by: CollapseBy,
+}
+
+impl SearchQuery {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            offset: 0,
+            limit: DEFAULT_LIMIT,
+            sort: SortOrder::default(),
+            highlight: false,
+            boosts: HashMap::new(),
+            collapse_by: CollapseBy::default(),
+        }
+    }
+
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn sort(mut self, sort: SortOrder) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    pub fn highlight(mut self, highlight: bool) -> Self {
+        self.highlight = highlight;
+        self
+    }
+
+    /// Multiply the scores of hits on `domain` by `factor`
+    pub fn boost(mut self, domain: &str, factor: f32) -> Self {
+        self.boosts.insert(domain.to_lowercase(), factor);
+        self
+    }
+
+    pub fn collapse_by(mut self, collapse_by: CollapseBy) -> Self {
+        self.collapse_by = collapse_by;
+        self
+    }
+}
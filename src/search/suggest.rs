@@ -0,0 +1,167 @@
+use crate::common::error::Result;
+use crate::search::Searcher;
+use std::collections::HashMap;
+use tantivy::Term;
+
+/// Maximum number of "did you mean" suggestions
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// Maximum edit distance between a query word and a suggested term
+const MAX_EDIT_DISTANCE: usize = 2;
+
+impl Searcher {
+    /// Suggest corrected spellings of a query.
+    ///
+    /// Plain words that match nothing in the title or body are replaced by
+    /// the closest indexed terms (fewest edits, then most frequent). Returns
+    /// at most [`MAX_SUGGESTIONS`] corrected queries, best first, or nothing
+    /// when every word is already in the index.
+    pub fn suggest(&self, query: &str) -> Result<Vec<String>> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_string).collect();
+
+        let mut corrections: Vec<(usize, Vec<String>)> = Vec::new();
+        for (i, word) in words.iter().enumerate() {
+            // Leave query syntax such as `title:x`, `-x` or `AND` alone
+            if !word.chars().all(char::is_alphanumeric) || word == "AND" || word == "OR" {
+                continue;
+            }
+            let word = word.to_lowercase();
+            if self.term_doc_freq(&word)? > 0 {
+                continue;
+            }
+            let candidates = self.closest_terms(&word)?;
+            if !candidates.is_empty() {
+                corrections.push((i, candidates));
+            }
+        }
+        if corrections.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Best candidate for every word first, then alternatives one word at a time
+        let mut best = words.clone();
+        for (i, candidates) in &corrections {
+            best[*i] = candidates[0].clone();
+        }
+        let mut suggestions = vec![best.join(" ")];
+        for rank in 1..MAX_SUGGESTIONS {
+            for (i, candidates) in &corrections {
+                if suggestions.len() >= MAX_SUGGESTIONS {
+                    return Ok(suggestions);
+                }
+                if let Some(candidate) = candidates.get(rank) {
+                    let mut alternative = best.clone();
+                    alternative[*i] = candidate.clone();
+                    suggestions.push(alternative.join(" "));
+                }
+            }
+        }
+        Ok(suggestions)
+    }
+
+    /// Documents containing `word` in the title or body
+    fn term_doc_freq(&self, word: &str) -> Result<u64> {
+        let term = Term::from_field_text(self.fields.spelling, word);
+        Ok(self.reader.searcher().doc_freq(&term)?)
+    }
+
+    /// Indexed terms within the edit distance of `word`, closest and most
+    /// frequent first
+    fn closest_terms(&self, word: &str) -> Result<Vec<String>> {
+        // Short words tolerate a single edit, or everything would match
+        let max_distance = if word.chars().count() <= 4 { 1 } else { MAX_EDIT_DISTANCE };
+        let word: Vec<char> = word.chars().collect();
+
+        let mut doc_freqs: HashMap<String, u32> = HashMap::new();
+        // Suggestions come from the unstemmed vocabulary so they are real words
+        for segment in self.reader.searcher().segment_readers() {
+            let inverted_index = segment.inverted_index(self.fields.spelling)?;
+            let mut terms = inverted_index.terms().stream()?;
+            while terms.advance() {
+                let Ok(term) = std::str::from_utf8(terms.key()) else {
+                    continue;
+                };
+                if term.chars().count().abs_diff(word.len()) > max_distance {
+                    continue;
+                }
+                *doc_freqs.entry(term.to_string()).or_default() += terms.value().doc_freq;
+            }
+        }
+
+        let mut candidates: Vec<(usize, u32, String)> = doc_freqs
+            .into_iter()
+            .filter_map(|(term, doc_freq)| {
+                let distance = edit_distance(&word, &term.chars().collect::<Vec<_>>());
+                (distance <= max_distance).then_some((distance, doc_freq, term))
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(&b.2)));
+        Ok(candidates
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, _, term)| term)
+            .collect())
+    }
+}
+
+/// Levenshtein distance between two words
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::{IndexDocument, Indexer};
+    use chrono::Utc;
+
+    fn searcher(bodies: &[&str]) -> Searcher {
+        let indexer = Indexer::in_memory().unwrap();
+        for (i, body) in bodies.iter().enumerate() {
+            indexer
+                .add_document(&IndexDocument {
+                    url: format!("https://example.com/{}", i),
+                    title: None,
+                    body: body.to_string(),
+                    content_type: None,
+                    language: None,
+                    description: None,
+                    image: None,
+                    crawled_at: Utc::now(),
+                    published_at: None,
+                    tags: Default::default(),
+                })
+                .unwrap();
+        }
+        indexer.commit().unwrap();
+        let searcher = Searcher::for_indexer(&indexer).unwrap();
+        searcher.reload().unwrap();
+        searcher
+    }
+
+    #[test]
+    fn test_suggests_indexed_spelling() {
+        let searcher = searcher(&["asynchronous crawler", "asynchronous runtime", "crawling spiders"]);
+
+        assert_eq!(searcher.suggest("asyncronous").unwrap()[0], "asynchronous");
+        assert_eq!(searcher.suggest("fast crawlr").unwrap()[0], "fast crawler");
+        assert!(searcher.suggest("crawler").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars("crawlr"), &chars("crawler")), 1);
+        assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+    }
+}
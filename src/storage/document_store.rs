@@ -0,0 +1,339 @@
+use crate::common::error::Result;
+use crate::storage::{check_dir_writable, Storage};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// A crawled page as persisted in the page store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredPage {
+    pub url: String,
+    pub status_code: u16,
+    pub content_type: Option<String>,
+    pub title: Option<String>,
+    pub text_content: String,
+    pub depth: usize,
+    pub crawled_at: DateTime<Utc>,
+    /// Response headers keyed by lowercase name; repeated headers keep every value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<BTreeMap<String, Vec<String>>>,
+    /// OpenGraph metadata keyed by property name (`og:image`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub open_graph: BTreeMap<String, String>,
+    /// Canonical URL declared by the page. Pages sharing a canonical URL
+    /// are stored once, under it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical_url: Option<String>,
+    /// Tags of the seed the page was crawled from
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+}
+
+impl StoredPage {
+    /// URL the page is stored and indexed under: its canonical URL if it
+    /// declares one, else the URL it was fetched from
+    pub fn key(&self) -> &str {
+        self.canonical_url.as_deref().unwrap_or(&self.url)
+    }
+
+    /// Approximate in-memory size of the page in bytes
+    pub fn approx_size(&self) -> usize {
+        let headers: usize = self
+            .headers
+            .iter()
+            .flatten()
+            .map(|(name, values)| name.len() + values.iter().map(String::len).sum::<usize>())
+            .sum();
+        std::mem::size_of::<Self>()
+            + self.url.len()
+            + self.content_type.as_ref().map_or(0, String::len)
+            + self.title.as_ref().map_or(0, String::len)
+            + self.text_content.len()
+            + headers
+            + self.open_graph.iter().map(|(k, v)| k.len() + v.len()).sum::<usize>()
+    }
+
+    /// Normalize raw `(name, value)` header pairs into a map, collecting
+    /// duplicate header names into a single entry
+    pub fn normalize_headers(headers: &[(String, String)]) -> BTreeMap<String, Vec<String>> {
+        let mut map: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (name, value) in headers {
+            map.entry(name.to_lowercase()).or_default().push(value.clone());
+        }
+        map
+    }
+}
+
+/// Persistent page store backed by sled
+#[derive(Clone)]
+pub struct PageStore {
+    db: sled::Db,
+    /// Discovered URL -> shallowest depth seen, as big-endian u64
+    discovered: sled::Tree,
+    /// Fetched URL -> canonical URL its page is stored under
+    aliases: sled::Tree,
+    store_headers: bool,
+    path: PathBuf,
+}
+
+impl PageStore {
+    /// Open (or create) a page store at the given path
+    pub fn open<P: AsRef<Path>>(path: P, store_headers: bool) -> Result<Self> {
+        let db = sled::open(path.as_ref())?;
+        let discovered = db.open_tree("discovered")?;
+        let aliases = db.open_tree("aliases")?;
+        Ok(Self {
+            db,
+            discovered,
+            aliases,
+            store_headers,
+            path: path.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Whether response headers are kept when inserting pages
+    pub fn stores_headers(&self) -> bool {
+        self.store_headers
+    }
+
+    /// Insert or replace a page, keyed by its canonical URL if it has one.
+    /// The URL it was fetched from is then recorded as an alias.
+    pub fn insert(&self, page: &StoredPage) -> Result<()> {
+        let key = page.key();
+        if key != page.url {
+            self.aliases.insert(page.url.as_bytes(), key.as_bytes())?;
+            self.db.remove(page.url.as_bytes())?;
+        } else if page.canonical_url.is_some() {
+            self.aliases.insert(page.url.as_bytes(), key.as_bytes())?;
+        }
+
+        let value = if self.store_headers || page.headers.is_none() {
+            serde_json::to_vec(page)?
+        } else {
+            let mut page = page.clone();
+            page.headers = None;
+            serde_json::to_vec(&page)?
+        };
+        self.db.insert(key.as_bytes(), value)?;
+        Ok(())
+    }
+
+    /// Look up a page by URL, or by any alias of its canonical URL
+    pub fn get(&self, url: &str) -> Result<Option<StoredPage>> {
+        match self.db.get(self.resolve(url)?)? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Fetched URLs recorded as aliases of a canonical URL
+    pub fn aliases_of(&self, canonical_url: &str) -> Result<Vec<String>> {
+        let mut aliases = Vec::new();
+        for entry in self.aliases.iter() {
+            let (alias, canonical) = entry?;
+            if canonical == canonical_url.as_bytes() {
+                aliases.push(String::from_utf8_lossy(&alias).into_owned());
+            }
+        }
+        Ok(aliases)
+    }
+
+    /// Key a URL's page is stored under
+    fn resolve(&self, url: &str) -> Result<sled::IVec> {
+        Ok(self.aliases.get(url.as_bytes())?.unwrap_or_else(|| url.as_bytes().into()))
+    }
+
+    /// Number of stored pages
+    pub fn len(&self) -> usize {
+        self.db.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.db.is_empty()
+    }
+
+    /// Write every stored page as one JSON object per line
+    pub fn export_jsonl<W: Write>(&self, mut writer: W) -> Result<()> {
+        for entry in self.db.iter() {
+            let (_, value) = entry?;
+            writer.write_all(&value)?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// Flush pending writes to disk
+    pub fn flush(&self) -> Result<()> {
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Storage for PageStore {
+    async fn insert_page(&self, page: StoredPage) -> Result<()> {
+        self.insert(&page)
+    }
+
+    async fn get_page(&self, url: &str) -> Result<Option<StoredPage>> {
+        self.get(url)
+    }
+
+    async fn all_urls(&self) -> Result<Vec<String>> {
+        self.db
+            .iter()
+            .keys()
+            .map(|key| Ok(String::from_utf8_lossy(&key?).into_owned()))
+            .collect()
+    }
+
+    async fn exists(&self, url: &str) -> Result<bool> {
+        Ok(self.db.contains_key(self.resolve(url)?)?)
+    }
+
+    async fn aliases(&self, url: &str) -> Result<Vec<String>> {
+        self.aliases_of(url)
+    }
+    
+    async fn insert_discovered(&self, urls: &[(String, usize)]) -> Result<()> {
+        for (url, depth) in urls {
+            let depth = *depth as u64;
+            self.discovered.fetch_and_update(url.as_bytes(), |known| {
+                let known = known.and_then(decode_depth).unwrap_or(u64::MAX);
+                Some(known.min(depth).to_be_bytes().to_vec())
+            })?;
+        }
+        Ok(())
+    }
+    
+    async fn uncrawled_discovered(&self) -> Result<Vec<(String, usize)>> {
+        let mut uncrawled = Vec::new();
+        for entry in self.discovered.iter() {
+            let (url, depth) = entry?;
+            if self.db.contains_key(&url)? || self.aliases.contains_key(&url)? {
+                continue;
+            }
+            let depth = decode_depth(&depth).unwrap_or_default() as usize;
+            uncrawled.push((String::from_utf8_lossy(&url).into_owned(), depth));
+        }
+        Ok(uncrawled)
+    }
+
+    async fn check_writable(&self) -> Result<()> {
+        check_dir_writable(&self.path).map_err(sled::Error::Io)?;
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+fn decode_depth(bytes: &[u8]) -> Option<u64> {
+    bytes.try_into().ok().map(u64::from_be_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(headers: &[(&str, &str)]) -> StoredPage {
+        let headers: Vec<(String, String)> = headers
+            .iter()
+            .map(|(n, v)| (n.to_string(), v.to_string()))
+            .collect();
+        StoredPage {
+            url: "https://example.com/".to_string(),
+            status_code: 200,
+            content_type: Some("text/html".to_string()),
+            title: Some("Example".to_string()),
+            text_content: "Example Domain".to_string(),
+            depth: 0,
+            crawled_at: Utc::now(),
+            headers: Some(StoredPage::normalize_headers(&headers)),
+            open_graph: BTreeMap::new(),
+            canonical_url: None,
+            tags: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_headers_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = PageStore::open(dir.path(), true).unwrap();
+        let page = page(&[
+            ("Content-Type", "text/html"),
+            ("Set-Cookie", "a=1"),
+            ("set-cookie", "b=2"),
+        ]);
+
+        store.insert(&page).unwrap();
+        let loaded = store.get("https://example.com/").unwrap().unwrap();
+
+        assert_eq!(loaded, page);
+        let headers = loaded.headers.unwrap();
+        assert_eq!(headers["content-type"], vec!["text/html"]);
+        assert_eq!(headers["set-cookie"], vec!["a=1", "b=2"]);
+
+        let mut out = Vec::new();
+        store.export_jsonl(&mut out).unwrap();
+        let line: serde_json::Value = serde_json::from_slice(out.trim_ascii_end()).unwrap();
+        assert_eq!(line["headers"]["set-cookie"], serde_json::json!(["a=1", "b=2"]));
+    }
+
+    #[tokio::test]
+    async fn test_uncrawled_discovered() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = PageStore::open(dir.path(), false).unwrap();
+        store.insert(&page(&[])).unwrap();
+
+        store
+            .insert_discovered(&[
+                ("https://example.com/".to_string(), 0),
+                ("https://example.com/next".to_string(), 3),
+            ])
+            .await
+            .unwrap();
+        store.insert_discovered(&[("https://example.com/next".to_string(), 1)]).await.unwrap();
+        store.insert_discovered(&[("https://example.com/next".to_string(), 2)]).await.unwrap();
+
+        let uncrawled = store.uncrawled_discovered().await.unwrap();
+        assert_eq!(uncrawled, vec![("https://example.com/next".to_string(), 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_pages_merged_under_canonical_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = PageStore::open(dir.path(), false).unwrap();
+        let canonical = "https://example.com/article";
+        for url in ["https://example.com/article?utm_source=feed", "https://m.example.com/article"] {
+            store
+                .insert(&StoredPage {
+                    url: url.to_string(),
+                    canonical_url: Some(canonical.to_string()),
+                    ..page(&[])
+                })
+                .unwrap();
+        }
+
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.all_urls().await.unwrap(), vec![canonical.to_string()]);
+        let mut aliases = store.aliases(canonical).await.unwrap();
+        aliases.sort();
+        assert_eq!(aliases, ["https://example.com/article?utm_source=feed", "https://m.example.com/article"]);
+        let by_alias = store.get("https://m.example.com/article").unwrap().unwrap();
+        assert_eq!(by_alias.key(), canonical);
+        assert!(store.exists("https://example.com/article?utm_source=feed").await.unwrap());
+    }
+
+    #[test]
+    fn test_headers_dropped_when_disabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = PageStore::open(dir.path(), false).unwrap();
+
+        store.insert(&page(&[("Server", "test")])).unwrap();
+        let loaded = store.get("https://example.com/").unwrap().unwrap();
+
+        assert!(loaded.headers.is_none());
+    }
+}
@@ -0,0 +1,225 @@
+use crate::common::error::Result;
+use crate::storage::{Storage, StoredPage};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// In-memory page store, mainly useful for tests and short crawls.
+///
+/// With a byte budget it behaves as an LRU cache: once the stored pages
+/// exceed the budget, the least recently accessed pages are evicted.
+#[derive(Clone, Default)]
+pub struct InMemoryStore {
+    inner: Arc<Mutex<LruPages>>,
+}
+
+#[derive(Default)]
+struct LruPages {
+    pages: HashMap<String, Entry>,
+    /// Access tick -> URL, oldest first
+    recency: BTreeMap<u64, String>,
+    next_tick: u64,
+    total_bytes: usize,
+    capacity: Option<usize>,
+    /// Discovered URL -> shallowest depth seen
+    discovered: HashMap<String, usize>,
+    /// Fetched URL -> canonical URL its page is stored under
+    aliases: HashMap<String, String>,
+}
+
+struct Entry {
+    page: StoredPage,
+    size: usize,
+    tick: u64,
+}
+
+impl LruPages {
+    /// Key a URL's page is stored under
+    fn resolve<'a>(&'a self, url: &'a str) -> &'a str {
+        self.aliases.get(url).map_or(url, String::as_str)
+    }
+
+    fn touch(&mut self, url: &str) {
+        let tick = self.next_tick;
+        if let Some(entry) = self.pages.get_mut(url) {
+            self.recency.remove(&entry.tick);
+            entry.tick = tick;
+            self.recency.insert(tick, url.to_string());
+            self.next_tick += 1;
+        }
+    }
+
+    fn remove(&mut self, url: &str) -> Option<Entry> {
+        let entry = self.pages.remove(url)?;
+        self.recency.remove(&entry.tick);
+        self.total_bytes -= entry.size;
+        Some(entry)
+    }
+
+    fn evict_over_capacity(&mut self) {
+        let Some(capacity) = self.capacity else {
+            return;
+        };
+        while self.total_bytes > capacity {
+            let Some((_, url)) = self.recency.pop_first() else {
+                break;
+            };
+            self.remove(&url);
+        }
+    }
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a store that evicts least recently accessed pages once the
+    /// stored pages exceed `bytes`
+    pub fn with_capacity(bytes: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(LruPages {
+                capacity: Some(bytes),
+                ..LruPages::default()
+            })),
+        }
+    }
+
+    /// Number of stored pages
+    pub async fn len(&self) -> usize {
+        self.inner.lock().await.pages.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.inner.lock().await.pages.is_empty()
+    }
+
+    /// Approximate bytes held by the stored pages
+    pub async fn size_bytes(&self) -> usize {
+        self.inner.lock().await.total_bytes
+    }
+}
+
+#[async_trait]
+impl Storage for InMemoryStore {
+    async fn insert_page(&self, page: StoredPage) -> Result<()> {
+        let mut inner = self.inner.lock().await;
+        let url = page.key().to_string();
+        let size = page.approx_size();
+
+        if page.canonical_url.is_some() {
+            inner.aliases.insert(page.url.clone(), url.clone());
+            inner.remove(&page.url);
+        }
+        inner.remove(&url);
+        let tick = inner.next_tick;
+        inner.next_tick += 1;
+        inner.recency.insert(tick, url.clone());
+        inner.pages.insert(url, Entry { page, size, tick });
+        inner.total_bytes += size;
+        inner.evict_over_capacity();
+        Ok(())
+    }
+
+    async fn get_page(&self, url: &str) -> Result<Option<StoredPage>> {
+        let mut inner = self.inner.lock().await;
+        let url = inner.resolve(url).to_string();
+        inner.touch(&url);
+        Ok(inner.pages.get(&url).map(|entry| entry.page.clone()))
+    }
+
+    async fn all_urls(&self) -> Result<Vec<String>> {
+        Ok(self.inner.lock().await.pages.keys().cloned().collect())
+    }
+
+    async fn exists(&self, url: &str) -> Result<bool> {
+        let inner = self.inner.lock().await;
+        Ok(inner.pages.contains_key(inner.resolve(url)))
+    }
+
+    async fn aliases(&self, canonical_url: &str) -> Result<Vec<String>> {
+        let inner = self.inner.lock().await;
+        Ok(inner
+            .aliases
+            .iter()
+            .filter(|(_, canonical)| *canonical == canonical_url)
+            .map(|(alias, _)| alias.clone())
+            .collect())
+    }
+    
+    async fn insert_discovered(&self, urls: &[(String, usize)]) -> Result<()> {
+        let mut inner = self.inner.lock().await;
+        for (url, depth) in urls {
+            let known = inner.discovered.entry(url.clone()).or_insert(*depth);
+            *known = (*known).min(*depth);
+        }
+        Ok(())
+    }
+    
+    /// Evicted pages count as uncrawled again
+    async fn uncrawled_discovered(&self) -> Result<Vec<(String, usize)>> {
+        let inner = self.inner.lock().await;
+        Ok(inner
+            .discovered
+            .iter()
+            .filter(|(url, _)| !inner.pages.contains_key(inner.resolve(url)))
+            .map(|(url, depth)| (url.clone(), *depth))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn page(url: &str, text_len: usize) -> StoredPage {
+        StoredPage {
+            url: url.to_string(),
+            status_code: 200,
+            content_type: None,
+            title: None,
+            text_content: "x".repeat(text_len),
+            depth: 0,
+            crawled_at: Utc::now(),
+            headers: None,
+            open_graph: Default::default(),
+            canonical_url: None,
+            tags: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_evicts_least_recently_accessed() {
+        let size = page("https://a.com/1", 1000).approx_size();
+        let store = InMemoryStore::with_capacity(size * 3);
+
+        store.insert_page(page("https://a.com/1", 1000)).await.unwrap();
+        store.insert_page(page("https://a.com/2", 1000)).await.unwrap();
+        store.insert_page(page("https://a.com/3", 1000)).await.unwrap();
+        assert_eq!(store.len().await, 3);
+
+        // Reading page 1 makes page 2 the least recently used
+        store.get_page("https://a.com/1").await.unwrap();
+        store.insert_page(page("https://a.com/4", 1000)).await.unwrap();
+
+        assert_eq!(store.len().await, 3);
+        assert!(!store.exists("https://a.com/2").await.unwrap());
+        assert!(store.exists("https://a.com/1").await.unwrap());
+
+        // Without further reads, the oldest insert goes next
+        store.insert_page(page("https://a.com/5", 1000)).await.unwrap();
+        assert!(!store.exists("https://a.com/3").await.unwrap());
+        assert!(store.size_bytes().await <= size * 3);
+    }
+
+    #[tokio::test]
+    async fn test_unbounded_keeps_everything() {
+        let store = InMemoryStore::new();
+        for i in 0..100 {
+            store.insert_page(page(&format!("https://a.com/{}", i), 1000)).await.unwrap();
+        }
+        assert_eq!(store.len().await, 100);
+    }
+}
@@ -0,0 +1,129 @@
+pub mod document_store;
+pub mod memory_store;
+pub mod record_writer;
+
+pub use document_store::{PageStore, StoredPage};
+pub use memory_store::InMemoryStore;
+pub use record_writer::{FlushPolicy, RecordWriter};
+
+use crate::common::error::Result;
+use async_trait::async_trait;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+/// File created and removed again to check that a directory is writable
+const WRITE_PROBE_FILE: &str = ".write-probe";
+
+/// Backend for persisting crawled pages
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Insert or replace a page, keyed by its canonical URL if it declares
+    /// one and by the URL it was fetched from otherwise
+    async fn insert_page(&self, page: StoredPage) -> Result<()>;
+    
+    /// Look up a page by URL
+    async fn get_page(&self, url: &str) -> Result<Option<StoredPage>>;
+    
+    /// URLs of every stored page
+    async fn all_urls(&self) -> Result<Vec<String>>;
+    
+    /// Check whether a page is stored for a URL
+    async fn exists(&self, url: &str) -> Result<bool>;
+    
+    /// Fetched URLs whose pages were merged under a canonical URL. Backends
+    /// that don't track aliases return none.
+    async fn aliases(&self, _canonical_url: &str) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+    
+    /// Remember URLs found while crawling, with the depth they were found
+    /// at. A URL keeps the shallowest depth it was seen at.
+    async fn insert_discovered(&self, urls: &[(String, usize)]) -> Result<()>;
+    
+    /// Discovered URLs that have no stored page yet, with their depth
+    async fn uncrawled_discovered(&self) -> Result<Vec<(String, usize)>>;
+    
+    /// Check that pages can be written, so an unusable backend fails a
+    /// crawl before it starts rather than partway through
+    async fn check_writable(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Check that files can be created and synced in `dir`. Directories
+/// without write permission are refused even when the process could
+/// write them anyway, as root can.
+pub(crate) fn check_dir_writable(dir: &Path) -> io::Result<()> {
+    let in_dir = |e: io::Error| io::Error::new(e.kind(), format!("{}: {}", dir.display(), e));
+    if fs::metadata(dir).map_err(in_dir)?.permissions().readonly() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} is read-only", dir.display()),
+        ));
+    }
+    let probe = dir.join(WRITE_PROBE_FILE);
+    let mut file = fs::File::create(&probe).map_err(in_dir)?;
+    file.write_all(b"probe").and_then(|_| file.sync_all()).map_err(in_dir)?;
+    fs::remove_file(&probe).map_err(in_dir)
+}
+
+/// Storage backend that discards every page
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopStore;
+
+#[async_trait]
+impl Storage for NoopStore {
+    async fn insert_page(&self, _page: StoredPage) -> Result<()> {
+        Ok(())
+    }
+    
+    async fn get_page(&self, _url: &str) -> Result<Option<StoredPage>> {
+        Ok(None)
+    }
+    
+    async fn all_urls(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+    
+    async fn exists(&self, _url: &str) -> Result<bool> {
+        Ok(false)
+    }
+    
+    async fn insert_discovered(&self, _urls: &[(String, usize)]) -> Result<()> {
+        Ok(())
+    }
+    
+    async fn uncrawled_discovered(&self) -> Result<Vec<(String, usize)>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Storage backend selection
+#[derive(Debug, Clone, Default)]
+pub enum StorageBackend {
+    /// Don't store pages
+    #[default]
+    None,
+    /// Keep pages in memory, evicting least recently used pages past an optional byte budget
+    Memory { capacity_bytes: Option<usize> },
+    /// Persist pages in a sled database at the given path
+    Sled { path: String, store_headers: bool },
+}
+
+impl StorageBackend {
+    /// Open the selected backend
+    pub fn open(&self) -> Result<Arc<dyn Storage>> {
+        Ok(match self {
+            StorageBackend::None => Arc::new(NoopStore),
+            StorageBackend::Memory { capacity_bytes: None } => Arc::new(InMemoryStore::new()),
+            StorageBackend::Memory { capacity_bytes: Some(bytes) } => {
+                Arc::new(InMemoryStore::with_capacity(*bytes))
+            }
+            StorageBackend::Sled { path, store_headers } => {
+                Arc::new(PageStore::open(path, *store_headers)?)
+            }
+        })
+    }
+}
@@ -0,0 +1,106 @@
+use crate::common::error::Result;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use tracing::warn;
+
+/// How often a [`RecordWriter`] pushes buffered records to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlushPolicy {
+    /// Records buffered before they're flushed to the OS; 1 flushes each
+    /// record as it's written
+    pub flush_every: usize,
+    /// Also fsync on every flush, so flushed records survive a crash of
+    /// the machine and not just of the process
+    pub fsync: bool,
+}
+
+impl Default for FlushPolicy {
+    fn default() -> Self {
+        Self {
+            flush_every: 1,
+            fsync: false,
+        }
+    }
+}
+
+/// Line-oriented output file, buffered in memory and flushed every
+/// `flush_every` records. Records still buffered are flushed when the
+/// writer is dropped.
+#[derive(Debug)]
+pub struct RecordWriter {
+    file: BufWriter<File>,
+    policy: FlushPolicy,
+    pending: usize,
+}
+
+impl RecordWriter {
+    /// Create the file at `path`, replacing any existing file
+    pub fn create(path: impl AsRef<Path>, policy: FlushPolicy) -> Result<Self> {
+        let file = OpenOptions::new().create(true).write(true).truncate(true).open(path)?;
+        Ok(Self {
+            file: BufWriter::new(file),
+            policy,
+            pending: 0,
+        })
+    }
+
+    /// Append `record` as one line
+    pub fn write_record(&mut self, record: &str) -> Result<()> {
+        self.file.write_all(record.as_bytes())?;
+        self.file.write_all(b"\n")?;
+        self.pending += 1;
+        if self.pending >= self.policy.flush_every.max(1) {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Flush buffered records, and fsync if the policy asks for it
+    pub fn flush(&mut self) -> Result<()> {
+        self.file.flush()?;
+        if self.policy.fsync {
+            self.file.get_ref().sync_data()?;
+        }
+        self.pending = 0;
+        Ok(())
+    }
+}
+
+impl Drop for RecordWriter {
+    fn drop(&mut self) {
+        if self.pending > 0 {
+            if let Err(e) = self.flush() {
+                warn!("Failed to flush output on close: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flushes_after_configured_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.jsonl");
+        let policy = FlushPolicy {
+            flush_every: 3,
+            fsync: true,
+        };
+        let mut writer = RecordWriter::create(&path, policy).unwrap();
+        let on_disk = || std::fs::read_to_string(&path).unwrap();
+
+        writer.write_record("{\"n\":1}").unwrap();
+        writer.write_record("{\"n\":2}").unwrap();
+        assert_eq!(on_disk(), "");
+        writer.write_record("{\"n\":3}").unwrap();
+        assert_eq!(on_disk().lines().count(), 3);
+
+        writer.write_record("{\"n\":4}").unwrap();
+        assert_eq!(on_disk().lines().count(), 3);
+        drop(writer);
+        assert_eq!(on_disk().lines().count(), 4);
+    }
+}